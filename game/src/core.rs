@@ -1,9 +1,59 @@
-use wasm_bindgen::prelude::*;
-
 pub type GameTime = f64;
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = Math)]
-    pub fn random() -> f32;
+/// Deterministic random numbers (SplitMix64). Seeded from the piece, so a piece sounds
+/// the same on every playback and when rendered natively
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+}
+
+/// Consumers of random numbers, each draws from its own stream so drawing more numbers
+/// in one place doesn't change what the others get
+// NOTE: keys of existing streams must stay the same, or saved pieces change their sound
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RandomStream {
+    /// Noise buffer shared by the noise and drum voices of a lane
+    Noise,
+    /// Impulse response of the reverb at an index of the effect chain
+    Reverb(usize),
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn from_stream(seed: u64, stream: RandomStream) -> Self {
+        let mut mixer = Self::new(seed ^ stream.key());
+        Self::new(mixer.next_u64())
+    }
+
+    /// FNV-1a hash of `bytes`, for seeds derived from data
+    pub fn seed_from_bytes(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl RandomStream {
+    fn key(self) -> u64 {
+        match self {
+            RandomStream::Noise => 1 << 32,
+            RandomStream::Reverb(index) => 2 << 32 | index as u64,
+        }
+    }
 }
@@ -8,6 +8,7 @@ use macroquad::text::draw_multiline_text;
 use macroquad::time::get_fps;
 use macroquad::time::get_time;
 
+use crate::engine::audio_engine::VoiceStats;
 use crate::render::Render;
 use crate::render::RenderCtx;
 
@@ -16,6 +17,7 @@ pub struct DebugHud {
     latency_queue: RefCell<VecDeque<f64>>,
     buffer_window_size: Cell<usize>,
     previous_time: Cell<f64>,
+    voice_stats: Cell<VoiceStats>,
 }
 
 impl DebugHud {
@@ -24,13 +26,19 @@ impl DebugHud {
         let latency_queue = RefCell::new(VecDeque::new());
         let buffer_window_size = Cell::new(buffer_window_size);
         let previous_time = Cell::new(0.0);
+        let voice_stats = Cell::new(VoiceStats::default());
         Self {
             fps_queue,
             latency_queue,
             buffer_window_size,
             previous_time,
+            voice_stats,
         }
     }
+
+    pub fn set_voice_stats(&self, voice_stats: VoiceStats) {
+        self.voice_stats.set(voice_stats);
+    }
 }
 
 impl Render for DebugHud {
@@ -44,8 +52,12 @@ impl Render for DebugHud {
                 100,
             )) as i32;
 
+        let VoiceStats { voices, steals } = self.voice_stats.get();
+
         draw_multiline_text(
-            &format!("FPS avg: {fps} | Latency avg: {latency_ms}ms",),
+            &format!(
+                "FPS avg: {fps} | Latency avg: {latency_ms}ms\nVoices: {voices} | Stolen: {steals}",
+            ),
             20.0,
             40.0,
             30.0,
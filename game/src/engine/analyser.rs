@@ -0,0 +1,118 @@
+use crate::core::GameTime;
+
+use super::fft::fft;
+use super::fft::hann_window;
+use super::fft::Complex;
+
+/// Samples the analyser holds, the length of the waveform and of every FFT
+pub const ANALYSER_SIZE: usize = 2048;
+/// Level silent bins are clamped to, the bottom of the spectrum
+pub const MIN_DECIBELS: f32 = -100.0;
+
+/// Level in dB of every frequency bin of a block of samples
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spectrum {
+    sample_rate: f32,
+    /// From 0Hz up to just below the Nyquist frequency
+    decibels: Vec<f32>,
+}
+
+impl Spectrum {
+    /// Spectrum of `samples` under a Hann window, their length must be a power of two
+    pub fn new(samples: &[f32], sample_rate: f32) -> Spectrum {
+        let window = hann_window(samples.len());
+        let mut values: Vec<Complex> = samples
+            .iter()
+            .zip(&window)
+            .map(|(sample, weight)| Complex::new(sample * weight, 0.0))
+            .collect();
+        fft(&mut values);
+
+        // NOTE: half the energy is in the negative frequencies and the window halves the
+        // amplitude, so a full scale sine peaks at 0dB
+        let scale = 4.0 / samples.len() as f32;
+        let decibels = values
+            .iter()
+            .take(samples.len() / 2)
+            .map(|value| (20.0 * (value.magnitude() * scale).log10()).max(MIN_DECIBELS))
+            .collect();
+        Spectrum {
+            sample_rate,
+            decibels,
+        }
+    }
+
+    pub fn decibels(&self) -> &[f32] {
+        &self.decibels
+    }
+
+    /// Width of a bin in Hz
+    pub fn bin_width(&self) -> f32 {
+        self.sample_rate / (2 * self.decibels.len()) as f32
+    }
+
+    /// Center frequency of the loudest bin
+    pub fn peak_frequency(&self) -> f32 {
+        let peak = self
+            .decibels
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(bin, _)| bin);
+        peak as f32 * self.bin_width()
+    }
+
+    /// Loudest level in each of `count` bands spread evenly on a log frequency axis, from
+    /// `min_frequency` up to the Nyquist frequency
+    pub fn log_bands(&self, count: usize, min_frequency: f32) -> Vec<f32> {
+        let nyquist = self.sample_rate / 2.0;
+        let bin_width = self.bin_width();
+        let edge = |band: usize| {
+            min_frequency * (nyquist / min_frequency).powf(band as f32 / count as f32)
+        };
+        (0..count)
+            .map(|band| {
+                // NOTE: low bands are narrower than a bin, they repeat the bin they fall in
+                let start = ((edge(band) / bin_width) as usize).min(self.decibels.len() - 1);
+                let end = ((edge(band + 1) / bin_width).ceil() as usize)
+                    .clamp(start + 1, self.decibels.len());
+                self.decibels[start..end]
+                    .iter()
+                    .copied()
+                    .fold(MIN_DECIBELS, f32::max)
+            })
+            .collect()
+    }
+}
+
+/// Stereo audio rendered ahead of playing it
+pub struct RenderedAudio {
+    pub sample_rate: f32,
+    /// Left and right channel
+    pub channels: [Vec<f32>; 2],
+}
+
+/// Latest samples of the mix and their spectrum, what the analyser shows in a frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis {
+    /// Mono, the oldest sample first
+    pub waveform: Vec<f32>,
+    pub spectrum: Spectrum,
+}
+
+impl Analysis {
+    pub fn new(waveform: Vec<f32>, sample_rate: f32) -> Analysis {
+        let spectrum = Spectrum::new(&waveform, sample_rate);
+        Analysis { waveform, spectrum }
+    }
+
+    /// What the analyser of the engine would hold at `time` while playing the rendered
+    /// audio: the last `ANALYSER_SIZE` samples mixed down to mono
+    pub fn of_rendered(audio: &RenderedAudio, time: GameTime) -> Analysis {
+        let end = ((time * audio.sample_rate as GameTime) as usize).min(audio.channels[0].len());
+        let start = end.saturating_sub(ANALYSER_SIZE);
+        let mut waveform = vec![0.0; ANALYSER_SIZE - (end - start)];
+        waveform.extend((start..end).map(|i| (audio.channels[0][i] + audio.channels[1][i]) / 2.0));
+        Analysis::new(waveform, audio.sample_rate)
+    }
+}
@@ -0,0 +1,172 @@
+use crate::core::GameTime;
+use crate::engine::errors::GameResult;
+use crate::nodes::audio_effect::FilterType;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod native;
+pub mod recording;
+#[cfg(target_arch = "wasm32")]
+pub mod web_audio;
+
+/// Backend the game plays through: Web Audio in the browser, the Rust renderer natively
+#[cfg(target_arch = "wasm32")]
+pub type PlatformBackend = web_audio::WebAudioBackend;
+#[cfg(not(target_arch = "wasm32"))]
+pub type PlatformBackend = native::NativeBackend;
+
+/// Everything `AudioEngine` asks of an audio API: creating nodes, wiring them together
+/// and automating their params. Web Audio plays the nodes, other backends let the engine
+/// run natively
+pub trait AudioBackend: 'static {
+    type Node: Clone;
+    type Buffer: Clone;
+    type Wave: Clone;
+
+    fn sample_rate(&self) -> f32;
+    fn current_time(&self) -> GameTime;
+    fn destination(&self) -> Self::Node;
+    /// Whether the worklet processor `name` can be used in `NodeKind::Worklet`
+    fn has_processor(&self, name: &str) -> bool;
+
+    fn create_node(&self, kind: NodeKind) -> GameResult<Self::Node>;
+    /// Buffer holding a channel per entry of `channels`
+    fn create_buffer(&self, sample_rate: f32, channels: &[Vec<f32>]) -> GameResult<Self::Buffer>;
+    fn create_periodic_wave(&self, real: &[f32], imag: &[f32]) -> GameResult<Self::Wave>;
+
+    fn connect(&self, from: &Self::Node, to: &Self::Node) -> GameResult<()>;
+    fn connect_param(&self, from: &Self::Node, to: &Param<Self::Node>) -> GameResult<()>;
+    fn disconnect(&self, node: &Self::Node) -> GameResult<()>;
+
+    fn set_oscillator_type(&self, node: &Self::Node, wave: OscillatorType) -> GameResult<()>;
+    fn set_periodic_wave(&self, node: &Self::Node, wave: &Self::Wave) -> GameResult<()>;
+    fn set_filter_type(&self, node: &Self::Node, filter_type: FilterType) -> GameResult<()>;
+    fn set_curve(&self, node: &Self::Node, curve: &[f32]) -> GameResult<()>;
+    fn set_oversample(&self, node: &Self::Node, oversample: Oversample) -> GameResult<()>;
+    fn set_buffer(&self, node: &Self::Node, buffer: &Self::Buffer) -> GameResult<()>;
+    /// Loops a buffer source between `points` in seconds, or over the whole buffer
+    fn set_loop(&self, node: &Self::Node, points: Option<(GameTime, GameTime)>) -> GameResult<()>;
+    fn set_channel_count_mode(&self, node: &Self::Node, mode: ChannelCountMode) -> GameResult<()>;
+
+    /// Value of the param right now, scheduled automation aside
+    fn param_value(&self, param: &Param<Self::Node>) -> GameResult<f32>;
+    fn automate(&self, param: &Param<Self::Node>, automation: Automation) -> GameResult<()>;
+
+    fn start(&self, node: &Self::Node, when: GameTime) -> GameResult<()>;
+    /// Stops a source at `when`, or right away
+    fn stop(&self, node: &Self::Node, when: Option<GameTime>) -> GameResult<()>;
+
+    /// Latest samples that went through an analyser mixed down to mono, the oldest first
+    fn read_waveform(&self, node: &Self::Node, samples: &mut [f32]) -> GameResult<()>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeKind {
+    Gain,
+    Oscillator,
+    BiquadFilter,
+    WaveShaper,
+    Convolver,
+    BufferSource,
+    StereoPanner,
+    Delay {
+        max_delay_time: GameTime,
+    },
+    DynamicsCompressor,
+    /// Passes its input through and keeps the latest `fft_size` samples of it
+    Analyser {
+        fft_size: usize,
+    },
+    /// Node running a registered worklet processor
+    Worklet(&'static str),
+}
+
+impl NodeKind {
+    /// Value a param of this kind of node starts at in Web Audio
+    pub fn default_value(&self, name: ParamName) -> f32 {
+        match (self, name) {
+            (NodeKind::Oscillator, ParamName::Frequency) => 440.0,
+            (NodeKind::BiquadFilter, ParamName::Frequency) => 350.0,
+            (NodeKind::BiquadFilter, ParamName::Gain) => 0.0,
+            (_, ParamName::Gain | ParamName::Q | ParamName::PlaybackRate) => 1.0,
+            (_, ParamName::Threshold) => -24.0,
+            (_, ParamName::Knee) => 30.0,
+            (_, ParamName::Ratio) => 12.0,
+            (_, ParamName::Attack) => 0.003,
+            (_, ParamName::Release) => 0.25,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParamName {
+    Gain,
+    Frequency,
+    Detune,
+    Q,
+    Pan,
+    DelayTime,
+    PlaybackRate,
+    Threshold,
+    Knee,
+    Ratio,
+    Attack,
+    Release,
+    /// Param declared by a worklet processor
+    Worklet(&'static str),
+}
+
+/// Automatable param of a node
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Param<N> {
+    pub node: N,
+    pub name: ParamName,
+}
+
+impl<N: Clone> Param<N> {
+    pub fn new(node: &N, name: ParamName) -> Self {
+        Self {
+            node: node.clone(),
+            name,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Automation {
+    /// Jumps to the value right away
+    Value(f32),
+    ValueAtTime(f32, GameTime),
+    LinearRamp(f32, GameTime),
+    ExponentialRamp(f32, GameTime),
+    /// Approaches the value exponentially from `start` on
+    Target {
+        value: f32,
+        start: GameTime,
+        time_constant: GameTime,
+    },
+    /// Drops the events scheduled at or after the time
+    Cancel(GameTime),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OscillatorType {
+    Sine,
+    Square,
+    Sawtooth,
+    Triangle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Oversample {
+    None,
+    TwoTimes,
+    FourTimes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelCountMode {
+    Max,
+    ClampedMax,
+    Explicit,
+}
@@ -0,0 +1,1419 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::core::GameTime;
+use crate::engine::dsp::convolver_normalization;
+use crate::engine::dsp::pan_frame;
+use crate::engine::dsp::read_cycle;
+use crate::engine::dsp::shape_sample;
+use crate::engine::dsp::Biquad;
+use crate::engine::dsp::Compressor;
+use crate::engine::errors::GameError;
+use crate::engine::errors::GameResult;
+use crate::engine::fft::fft;
+use crate::engine::fft::ifft;
+use crate::engine::fft::Complex;
+use crate::engine::wav::DecodedAudio;
+use crate::engine::wav::WavWriter;
+use crate::nodes::audio_effect::CompressorParameters;
+use crate::nodes::audio_effect::FilterParameters;
+use crate::nodes::audio_effect::FilterType;
+use crate::nodes::oscillator::WaveShape;
+
+use super::AudioBackend;
+use super::Automation;
+use super::ChannelCountMode;
+use super::NodeKind;
+use super::OscillatorType;
+use super::Oversample;
+use super::Param;
+use super::ParamName;
+
+/// Frames rendered at once, the Web Audio render quantum. Delays in feedback loops are at
+/// least a block long, like in Web Audio
+pub const BLOCK_SIZE: usize = 128;
+const WAVETABLE_SIZE: usize = 2048;
+const SAMPLE_RATE: f32 = 44100.0;
+/// Path of the WAV file native builds record to, nothing is recorded when it isn't set
+const WAV_OUTPUT_VAR: &str = "AUDIO_WAV_OUTPUT";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// Where rendered blocks go
+pub enum AudioSink {
+    /// Drops the audio, the graph is rendered all the same
+    Null,
+    Wav(WavWriter),
+}
+
+impl AudioSink {
+    /// Records stereo to the file named by `AUDIO_WAV_OUTPUT`, or to nothing when it isn't set
+    pub fn from_env(sample_rate: f32) -> GameResult<Self> {
+        match std::env::var(WAV_OUTPUT_VAR) {
+            Ok(path) => Ok(AudioSink::Wav(WavWriter::create(
+                &path,
+                sample_rate as u32,
+                2,
+            )?)),
+            Err(_) => Ok(AudioSink::Null),
+        }
+    }
+
+    fn write(&mut self, channels: &[Vec<f32>]) -> GameResult<()> {
+        match self {
+            AudioSink::Null => Ok(()),
+            AudioSink::Wav(writer) => writer.write(channels),
+        }
+    }
+}
+
+/// Backend rendering the node graph in Rust, a block at a time, so the engine plays
+/// without a browser. Nodes follow the Web Audio processing model and formulas
+// NOTE: nodes the destination can't be reached from anymore are dropped at the next
+// block, so nodes have to be connected in the same call they are created in
+pub struct NativeBackend {
+    sample_rate: f32,
+    /// Blocks rendered so far, the clock of `current_time`
+    blocks: Cell<u64>,
+    graph: RefCell<Graph>,
+    sink: RefCell<AudioSink>,
+    /// Single cycles of the basic oscillator types
+    basic_waves: Vec<(OscillatorType, Rc<Vec<f32>>)>,
+    created_at: Instant,
+}
+
+impl NativeBackend {
+    pub const DESTINATION: NodeId = NodeId(0);
+
+    pub fn new(sample_rate: f32, sink: AudioSink) -> Self {
+        let basic_waves = [
+            (OscillatorType::Sine, WaveShape::Sine),
+            (OscillatorType::Square, WaveShape::Square),
+            (OscillatorType::Sawtooth, WaveShape::Sawtooth),
+            (OscillatorType::Triangle, WaveShape::Triangle),
+        ]
+        .into_iter()
+        .map(|(wave, shape)| (wave, Rc::new(shape.single_cycle(WAVETABLE_SIZE))))
+        .collect();
+
+        Self {
+            sample_rate,
+            blocks: Cell::new(0),
+            graph: RefCell::new(Graph::new(sample_rate)),
+            sink: RefCell::new(sink),
+            basic_waves,
+            created_at: Instant::now(),
+        }
+    }
+
+    /// Plays at 44.1kHz to the sink picked by `AudioSink::from_env`
+    pub fn from_env() -> GameResult<Self> {
+        Ok(Self::new(SAMPLE_RATE, AudioSink::from_env(SAMPLE_RATE)?))
+    }
+
+    /// Renders whole blocks up to `time`, as fast as it can
+    pub fn render_until(&self, time: GameTime) -> GameResult<()> {
+        let block_duration = BLOCK_SIZE as GameTime / self.sample_rate as GameTime;
+        while self.current_time() + block_duration <= time {
+            let block = self.graph.borrow_mut().render_block(self.current_time());
+            self.blocks.set(self.blocks.get() + 1);
+            self.sink.borrow_mut().write(&block)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the clock in step with the wall clock, called every frame
+    pub fn render_realtime(&self) -> GameResult<()> {
+        self.render_until(self.created_at.elapsed().as_secs_f64())
+    }
+
+    fn basic_wave(&self, wave: OscillatorType) -> Rc<Vec<f32>> {
+        self.basic_waves
+            .iter()
+            .find(|(w, _)| *w == wave)
+            .map(|(_, cycle)| cycle.clone())
+            .unwrap_or_default()
+    }
+
+    /// Runs `f` on a node, calls on nodes that were already dropped do nothing
+    fn update_node(
+        &self,
+        node: &NodeId,
+        f: impl FnOnce(&mut NativeNode) -> GameResult<()>,
+    ) -> GameResult<()> {
+        match self.graph.borrow_mut().nodes.get_mut(node) {
+            Some(node) => f(node),
+            None => Ok(()),
+        }
+    }
+
+    fn update_playback(
+        &self,
+        node: &NodeId,
+        f: impl FnOnce(&mut Playback) -> GameResult<()>,
+    ) -> GameResult<()> {
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::Oscillator(oscillator) => f(&mut oscillator.playback),
+            Processor::BufferSource(source) => f(&mut source.playback),
+            _ => Err(unexpected_kind()),
+        })
+    }
+}
+
+impl AudioBackend for NativeBackend {
+    type Node = NodeId;
+    type Buffer = Rc<DecodedAudio>;
+    type Wave = Rc<Vec<f32>>;
+
+    fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    fn current_time(&self) -> GameTime {
+        (self.blocks.get() * BLOCK_SIZE as u64) as GameTime / self.sample_rate as GameTime
+    }
+
+    fn destination(&self) -> NodeId {
+        Self::DESTINATION
+    }
+
+    fn has_processor(&self, _name: &str) -> bool {
+        false
+    }
+
+    fn create_node(&self, kind: NodeKind) -> GameResult<NodeId> {
+        let processor = match kind {
+            NodeKind::Gain => Processor::Gain,
+            NodeKind::Oscillator => Processor::Oscillator(Oscillator {
+                cycle: self.basic_wave(OscillatorType::Sine),
+                phase: 0.0,
+                playback: Playback::default(),
+            }),
+            NodeKind::BiquadFilter => Processor::Filter {
+                filter_type: FilterType::LowPass,
+                biquads: vec![],
+            },
+            NodeKind::WaveShaper => Processor::WaveShaper { curve: vec![] },
+            NodeKind::Convolver => Processor::Convolver(None),
+            NodeKind::BufferSource => Processor::BufferSource(BufferSource::default()),
+            NodeKind::StereoPanner => Processor::Panner,
+            NodeKind::Delay { max_delay_time } => {
+                Processor::Delay(DelayLine::new(max_delay_time, self.sample_rate))
+            }
+            NodeKind::DynamicsCompressor => Processor::Compressor(Compressor::default()),
+            NodeKind::Analyser { fft_size } => Processor::Analyser {
+                fft_size,
+                samples: VecDeque::with_capacity(fft_size),
+            },
+            NodeKind::Worklet(_) => {
+                return Err(GameError::msg("Worklet processors can't run natively"))
+            }
+        };
+        Ok(self.graph.borrow_mut().add(Some(kind), processor))
+    }
+
+    fn create_buffer(&self, sample_rate: f32, channels: &[Vec<f32>]) -> GameResult<Self::Buffer> {
+        Ok(Rc::new(DecodedAudio {
+            sample_rate,
+            channels: channels.to_vec(),
+        }))
+    }
+
+    fn create_periodic_wave(&self, real: &[f32], imag: &[f32]) -> GameResult<Self::Wave> {
+        // Like Web Audio the wave is normalized and the DC term ignored
+        let mut cycle: Vec<f32> = (0..WAVETABLE_SIZE)
+            .map(|i| {
+                let phase = 2.0 * PI * i as f32 / WAVETABLE_SIZE as f32;
+                (1..real.len().max(imag.len()))
+                    .map(|k| {
+                        let (sin, cos) = (k as f32 * phase).sin_cos();
+                        real.get(k).unwrap_or(&0.0) * cos + imag.get(k).unwrap_or(&0.0) * sin
+                    })
+                    .sum()
+            })
+            .collect();
+        let peak = cycle.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        if peak > 0.0 {
+            cycle.iter_mut().for_each(|s| *s /= peak);
+        }
+        Ok(Rc::new(cycle))
+    }
+
+    fn connect(&self, from: &NodeId, to: &NodeId) -> GameResult<()> {
+        if !self.graph.borrow().nodes.contains_key(from) {
+            return Ok(());
+        }
+        self.update_node(to, |node| {
+            if !node.inputs.contains(from) {
+                node.inputs.push(*from);
+            }
+            Ok(())
+        })
+    }
+
+    fn connect_param(&self, from: &NodeId, to: &Param<NodeId>) -> GameResult<()> {
+        if !self.graph.borrow().nodes.contains_key(from) {
+            return Ok(());
+        }
+        self.update_node(&to.node, |node| {
+            let timeline = node.timeline(to.name);
+            if !timeline.inputs.contains(from) {
+                timeline.inputs.push(*from);
+            }
+            Ok(())
+        })
+    }
+
+    fn disconnect(&self, node: &NodeId) -> GameResult<()> {
+        for other in self.graph.borrow_mut().nodes.values_mut() {
+            other.inputs.retain(|input| input != node);
+            for timeline in other.params.values_mut() {
+                timeline.inputs.retain(|input| input != node);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_oscillator_type(&self, node: &NodeId, wave: OscillatorType) -> GameResult<()> {
+        let cycle = self.basic_wave(wave);
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::Oscillator(oscillator) => {
+                oscillator.cycle = cycle;
+                Ok(())
+            }
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    fn set_periodic_wave(&self, node: &NodeId, wave: &Self::Wave) -> GameResult<()> {
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::Oscillator(oscillator) => {
+                oscillator.cycle = wave.clone();
+                Ok(())
+            }
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    fn set_filter_type(&self, node: &NodeId, filter_type: FilterType) -> GameResult<()> {
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::Filter {
+                filter_type: current,
+                ..
+            } => {
+                *current = filter_type;
+                Ok(())
+            }
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    fn set_curve(&self, node: &NodeId, curve: &[f32]) -> GameResult<()> {
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::WaveShaper { curve: current } => {
+                *current = curve.to_vec();
+                Ok(())
+            }
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    // NOTE: curves are applied without oversampling, so hard shaping aliases a bit more
+    // than in the browser
+    fn set_oversample(&self, node: &NodeId, _oversample: Oversample) -> GameResult<()> {
+        self.update_node(node, |node| match node.processor {
+            Processor::WaveShaper { .. } => Ok(()),
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    fn set_buffer(&self, node: &NodeId, buffer: &Self::Buffer) -> GameResult<()> {
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::BufferSource(source) => {
+                source.buffer = Some(buffer.clone());
+                Ok(())
+            }
+            Processor::Convolver(convolver) => {
+                *convolver = Some(Convolver::new(buffer));
+                Ok(())
+            }
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    fn set_loop(&self, node: &NodeId, points: Option<(GameTime, GameTime)>) -> GameResult<()> {
+        self.update_node(node, |node| match &mut node.processor {
+            Processor::BufferSource(source) => {
+                source.looping = true;
+                source.loop_points = points;
+                Ok(())
+            }
+            _ => Err(unexpected_kind()),
+        })
+    }
+
+    fn set_channel_count_mode(&self, node: &NodeId, mode: ChannelCountMode) -> GameResult<()> {
+        self.update_node(node, |node| {
+            node.channel_count_mode = mode;
+            Ok(())
+        })
+    }
+
+    fn param_value(&self, param: &Param<NodeId>) -> GameResult<f32> {
+        let time = self.current_time();
+        let mut graph = self.graph.borrow_mut();
+        let node = graph
+            .nodes
+            .get_mut(&param.node)
+            .ok_or(GameError::msg("Audio node no longer exists"))?;
+        let timeline = node.timeline(param.name);
+        Ok(value_at(&timeline.events, timeline.default, time))
+    }
+
+    fn automate(&self, param: &Param<NodeId>, automation: Automation) -> GameResult<()> {
+        // Setting the value right away is a set value event at the current time
+        let automation = match automation {
+            Automation::Value(value) => Automation::ValueAtTime(value, self.current_time()),
+            automation => automation,
+        };
+        self.update_node(&param.node, |node| {
+            let events = &mut node.timeline(param.name).events;
+            if let Automation::Cancel(when) = automation {
+                events.retain(|event| event_time(event) < when);
+                return Ok(());
+            }
+            let at = event_time(&automation);
+            let index = events.partition_point(|event| event_time(event) <= at);
+            events.insert(index, automation);
+            Ok(())
+        })
+    }
+
+    fn start(&self, node: &NodeId, when: GameTime) -> GameResult<()> {
+        self.update_playback(node, |playback| {
+            playback.start = Some(when);
+            Ok(())
+        })
+    }
+
+    fn stop(&self, node: &NodeId, when: Option<GameTime>) -> GameResult<()> {
+        let when = when.unwrap_or(self.current_time());
+        self.update_playback(node, |playback| {
+            playback.stop = Some(when);
+            Ok(())
+        })
+    }
+
+    fn read_waveform(&self, node: &NodeId, samples: &mut [f32]) -> GameResult<()> {
+        samples.fill(0.0);
+        self.update_node(node, |node| {
+            let Processor::Analyser {
+                samples: analysed, ..
+            } = &node.processor
+            else {
+                return Err(unexpected_kind());
+            };
+            // NOTE: until it has heard enough, the analyser starts with silence
+            let offset = samples.len().saturating_sub(analysed.len());
+            let skip = analysed.len().saturating_sub(samples.len());
+            for (sample, analysed) in samples[offset..].iter_mut().zip(analysed.iter().skip(skip)) {
+                *sample = *analysed;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn unexpected_kind() -> GameError {
+    GameError::msg("Audio node is not of the expected type")
+}
+
+/// Nodes keyed by id, `NodeId(0)` is the destination
+struct Graph {
+    sample_rate: f32,
+    nodes: HashMap<NodeId, NativeNode>,
+    next_id: usize,
+}
+
+impl Graph {
+    fn new(sample_rate: f32) -> Self {
+        let mut graph = Self {
+            sample_rate,
+            nodes: HashMap::new(),
+            next_id: 0,
+        };
+        graph.add(None, Processor::Destination);
+        graph
+    }
+
+    fn add(&mut self, kind: Option<NodeKind>, processor: Processor) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            NativeNode {
+                kind,
+                inputs: vec![],
+                params: HashMap::new(),
+                channel_count_mode: ChannelCountMode::Max,
+                processor,
+                output: silence(1),
+            },
+        );
+        id
+    }
+
+    /// Renders the block starting at `time` and returns what reached the destination
+    fn render_block(&mut self, time: GameTime) -> Vec<Vec<f32>> {
+        self.nodes
+            .retain(|_, node| !node.processor.is_finished(time));
+        let cyclic = self.cyclic_delays();
+        let mut order = vec![];
+        let mut visiting = HashSet::new();
+        let mut visited = HashSet::new();
+        self.visit(
+            NativeBackend::DESTINATION,
+            &cyclic,
+            &mut visiting,
+            &mut visited,
+            &mut order,
+        );
+
+        self.nodes.retain(|id, _| visited.contains(id));
+        for node in self.nodes.values_mut() {
+            node.inputs.retain(|input| visited.contains(input));
+            for timeline in node.params.values_mut() {
+                timeline.inputs.retain(|input| visited.contains(input));
+                timeline.forget_before(time);
+            }
+        }
+
+        for id in order {
+            let Some(mut node) = self.nodes.remove(&id) else {
+                continue;
+            };
+            node.output = node.render(&self.nodes, time, self.sample_rate, cyclic.contains(&id));
+            self.nodes.insert(id, node);
+        }
+        // Delays in feedback loops played what earlier blocks wrote, now this block is
+        // written for the next ones
+        for id in &cyclic {
+            let Some(mut node) = self.nodes.remove(id) else {
+                continue;
+            };
+            let input = mix(&node.inputs, &self.nodes, node.channel_count_mode);
+            if let Processor::Delay(delay) = &mut node.processor {
+                delay.write(&input);
+                delay.advance();
+            }
+            self.nodes.insert(*id, node);
+        }
+
+        self.nodes
+            .get(&NativeBackend::DESTINATION)
+            .map_or(silence(2), |node| node.output.clone())
+    }
+
+    /// Everything a node reads in a block: its inputs and the inputs of its params
+    fn dependencies(&self, id: &NodeId) -> Vec<NodeId> {
+        self.nodes.get(id).map_or(vec![], |node| {
+            node.params
+                .values()
+                .flat_map(|timeline| timeline.inputs.iter())
+                .chain(node.inputs.iter())
+                .copied()
+                .collect()
+        })
+    }
+
+    fn is_delay(&self, id: &NodeId) -> bool {
+        self.nodes
+            .get(id)
+            .is_some_and(|node| matches!(node.processor, Processor::Delay(_)))
+    }
+
+    /// Delays on a feedback loop, they play from earlier blocks so the loop can be cut there
+    fn cyclic_delays(&self) -> HashSet<NodeId> {
+        let mut cyclic = HashSet::new();
+        let mut stack = vec![];
+        let mut done = HashSet::new();
+        self.find_cycles(
+            NativeBackend::DESTINATION,
+            &mut stack,
+            &mut done,
+            &mut cyclic,
+        );
+        cyclic
+    }
+
+    fn find_cycles(
+        &self,
+        id: NodeId,
+        stack: &mut Vec<NodeId>,
+        done: &mut HashSet<NodeId>,
+        cyclic: &mut HashSet<NodeId>,
+    ) {
+        if let Some(position) = stack.iter().position(|node| *node == id) {
+            cyclic.extend(stack[position..].iter().filter(|node| self.is_delay(node)));
+            return;
+        }
+        if !done.insert(id) {
+            return;
+        }
+        stack.push(id);
+        for input in self.dependencies(&id) {
+            self.find_cycles(input, stack, done, cyclic);
+        }
+        stack.pop();
+    }
+
+    /// Orders nodes after everything they read, cyclic delays go before their inputs.
+    /// Loops without a delay read the output of the previous block
+    fn visit(
+        &self,
+        id: NodeId,
+        cyclic: &HashSet<NodeId>,
+        visiting: &mut HashSet<NodeId>,
+        visited: &mut HashSet<NodeId>,
+        order: &mut Vec<NodeId>,
+    ) {
+        if visited.contains(&id) || visiting.contains(&id) {
+            return;
+        }
+        let Some(node) = self.nodes.get(&id) else {
+            return;
+        };
+        visiting.insert(id);
+        for input in node.params.values().flat_map(|timeline| &timeline.inputs) {
+            self.visit(*input, cyclic, visiting, visited, order);
+        }
+        if cyclic.contains(&id) {
+            visiting.remove(&id);
+            visited.insert(id);
+            order.push(id);
+            for input in &node.inputs {
+                self.visit(*input, cyclic, visiting, visited, order);
+            }
+        } else {
+            for input in &node.inputs {
+                self.visit(*input, cyclic, visiting, visited, order);
+            }
+            visiting.remove(&id);
+            visited.insert(id);
+            order.push(id);
+        }
+    }
+}
+
+struct NativeNode {
+    /// `None` for the destination
+    kind: Option<NodeKind>,
+    inputs: Vec<NodeId>,
+    /// Params that were automated or connected to, the others keep their default
+    params: HashMap<ParamName, ParamTimeline>,
+    channel_count_mode: ChannelCountMode,
+    processor: Processor,
+    /// Output of the latest block, a buffer per channel
+    output: Vec<Vec<f32>>,
+}
+
+impl NativeNode {
+    fn timeline(&mut self, name: ParamName) -> &mut ParamTimeline {
+        let default = self.kind.map_or(0.0, |kind| kind.default_value(name));
+        self.params.entry(name).or_insert(ParamTimeline {
+            default,
+            events: vec![],
+            inputs: vec![],
+        })
+    }
+
+    /// Value of a param at every frame of the block, connected nodes added on top
+    fn param(
+        &self,
+        name: ParamName,
+        nodes: &HashMap<NodeId, NativeNode>,
+        time: GameTime,
+        sample_rate: f32,
+    ) -> Vec<f32> {
+        let Some(timeline) = self.params.get(&name) else {
+            return vec![self.kind.map_or(0.0, |kind| kind.default_value(name)); BLOCK_SIZE];
+        };
+        let mut values = if timeline.is_settled(time) {
+            vec![value_at(&timeline.events, timeline.default, time); BLOCK_SIZE]
+        } else {
+            (0..BLOCK_SIZE)
+                .map(|i| {
+                    let time = time + i as GameTime / sample_rate as GameTime;
+                    value_at(&timeline.events, timeline.default, time)
+                })
+                .collect()
+        };
+        // Params take a single channel, stereo inputs are mixed down
+        for output in timeline.inputs.iter().filter_map(|id| nodes.get(id)) {
+            let scale = 1.0 / output.output.len() as f32;
+            for channel in &output.output {
+                for (value, sample) in values.iter_mut().zip(channel) {
+                    *value += sample * scale;
+                }
+            }
+        }
+        values
+    }
+
+    fn render(
+        &mut self,
+        nodes: &HashMap<NodeId, NativeNode>,
+        time: GameTime,
+        sample_rate: f32,
+        cyclic: bool,
+    ) -> Vec<Vec<f32>> {
+        let param = |name| self.param(name, nodes, time, sample_rate);
+        let frame_time = |i: usize| time + i as GameTime / sample_rate as GameTime;
+
+        match &self.processor {
+            Processor::Destination => {
+                let mut input = mix(&self.inputs, nodes, self.channel_count_mode);
+                upmix(&mut input);
+                input
+            }
+            Processor::Gain => {
+                if self.inputs.is_empty() {
+                    return silence(1);
+                }
+                let gain = param(ParamName::Gain);
+                let mut input = mix(&self.inputs, nodes, self.channel_count_mode);
+                for channel in input.iter_mut() {
+                    for (sample, gain) in channel.iter_mut().zip(&gain) {
+                        *sample *= gain;
+                    }
+                }
+                input
+            }
+            Processor::Oscillator(_) => {
+                let frequency = param(ParamName::Frequency);
+                let detune = param(ParamName::Detune);
+                let Processor::Oscillator(oscillator) = &mut self.processor else {
+                    unreachable!()
+                };
+                let mut output = vec![0.0; BLOCK_SIZE];
+                for (i, sample) in output.iter_mut().enumerate() {
+                    if !oscillator.playback.is_playing(frame_time(i)) {
+                        continue;
+                    }
+                    *sample = read_cycle(&oscillator.cycle, oscillator.phase);
+                    let frequency = frequency[i] * 2f32.powf(detune[i] / 1200.0);
+                    oscillator.phase = (oscillator.phase + frequency / sample_rate).rem_euclid(1.0);
+                }
+                vec![output]
+            }
+            Processor::BufferSource(_) => {
+                let playback_rate = param(ParamName::PlaybackRate);
+                let detune = param(ParamName::Detune);
+                let Processor::BufferSource(source) = &mut self.processor else {
+                    unreachable!()
+                };
+                let Some(buffer) = source.buffer.clone() else {
+                    return silence(1);
+                };
+                let frames = buffer.frames() as f64;
+                let ratio = (buffer.sample_rate / sample_rate) as f64;
+                let loop_frames = source.loop_frames(&buffer);
+                let mut output = vec![vec![0.0; BLOCK_SIZE]; buffer.channels.len().max(1)];
+                for i in 0..BLOCK_SIZE {
+                    if source.ended || !source.playback.is_playing(frame_time(i)) {
+                        continue;
+                    }
+                    match loop_frames {
+                        Some((start, end)) => {
+                            while source.position >= end {
+                                source.position -= end - start;
+                            }
+                        }
+                        None if source.position >= frames => {
+                            source.ended = true;
+                            continue;
+                        }
+                        None => (),
+                    }
+                    for (out, channel) in output.iter_mut().zip(&buffer.channels) {
+                        out[i] = interpolate(channel, source.position);
+                    }
+                    let rate = playback_rate[i] * 2f32.powf(detune[i] / 1200.0);
+                    source.position += rate as f64 * ratio;
+                }
+                output
+            }
+            Processor::Filter { .. } => {
+                // NOTE: coefficients are updated once per block
+                let detune = param(ParamName::Detune)[0];
+                let parameters = FilterParameters {
+                    filter_type: FilterType::LowPass,
+                    frequency: (param(ParamName::Frequency)[0] * 2f32.powf(detune / 1200.0))
+                        .clamp(0.0, sample_rate / 2.0),
+                    q: param(ParamName::Q)[0],
+                    gain: param(ParamName::Gain)[0],
+                };
+                let mut input = mix(&self.inputs, nodes, self.channel_count_mode);
+                let Processor::Filter {
+                    filter_type,
+                    biquads,
+                } = &mut self.processor
+                else {
+                    unreachable!()
+                };
+                let parameters = FilterParameters {
+                    filter_type: *filter_type,
+                    ..parameters
+                };
+                biquads.resize_with(input.len(), Biquad::default);
+                for (channel, biquad) in input.iter_mut().zip(biquads.iter_mut()) {
+                    biquad.set_coefficients(&parameters, sample_rate);
+                    biquad.process(channel);
+                }
+                input
+            }
+            Processor::WaveShaper { curve } => {
+                let mut input = mix(&self.inputs, nodes, self.channel_count_mode);
+                if curve.len() >= 2 {
+                    for sample in input.iter_mut().flatten() {
+                        *sample = shape_sample(curve, *sample);
+                    }
+                }
+                input
+            }
+            Processor::Convolver(_) => {
+                let input = mix(&self.inputs, nodes, self.channel_count_mode);
+                let Processor::Convolver(convolver) = &mut self.processor else {
+                    unreachable!()
+                };
+                match convolver {
+                    Some(convolver) => convolver.process(&input),
+                    None => silence(1),
+                }
+            }
+            Processor::Panner => {
+                let pan = param(ParamName::Pan);
+                let input = mix(&self.inputs, nodes, self.channel_count_mode);
+                let mut output = silence(2);
+                for (i, pan) in pan.iter().enumerate() {
+                    let frame = match input.as_slice() {
+                        [mono] => pan_frame(&[mono[i]], *pan),
+                        [left, right, ..] => pan_frame(&[left[i], right[i]], *pan),
+                        [] => [0.0, 0.0],
+                    };
+                    output[0][i] = frame[0];
+                    output[1][i] = frame[1];
+                }
+                output
+            }
+            Processor::Delay(_) => {
+                let delay_time = param(ParamName::DelayTime);
+                let input = (!cyclic).then(|| mix(&self.inputs, nodes, self.channel_count_mode));
+                let Processor::Delay(delay) = &mut self.processor else {
+                    unreachable!()
+                };
+                match input {
+                    Some(input) => {
+                        delay.write(&input);
+                        let output = delay.read(&delay_time, 0.0, sample_rate);
+                        delay.advance();
+                        output
+                    }
+                    // Written once the whole block is rendered
+                    None => delay.read(&delay_time, BLOCK_SIZE as f32, sample_rate),
+                }
+            }
+            Processor::Compressor(_) => {
+                // NOTE: like Web Audio the compressor params are read once per block
+                let parameters = CompressorParameters {
+                    threshold: param(ParamName::Threshold)[0],
+                    knee: param(ParamName::Knee)[0],
+                    ratio: param(ParamName::Ratio)[0],
+                    attack: param(ParamName::Attack)[0],
+                    release: param(ParamName::Release)[0],
+                };
+                let mut input = mix(&self.inputs, nodes, self.channel_count_mode);
+                let Processor::Compressor(compressor) = &mut self.processor else {
+                    unreachable!()
+                };
+                compressor.process(&parameters, &mut input, sample_rate);
+                input
+            }
+            Processor::Analyser { .. } => {
+                let input = mix(&self.inputs, nodes, self.channel_count_mode);
+                let Processor::Analyser { fft_size, samples } = &mut self.processor else {
+                    unreachable!()
+                };
+                let scale = 1.0 / input.len() as f32;
+                for i in 0..BLOCK_SIZE {
+                    samples.push_back(input.iter().map(|channel| channel[i]).sum::<f32>() * scale);
+                }
+                while samples.len() > *fft_size {
+                    samples.pop_front();
+                }
+                input
+            }
+        }
+    }
+}
+
+enum Processor {
+    Destination,
+    Gain,
+    Oscillator(Oscillator),
+    BufferSource(BufferSource),
+    Filter {
+        filter_type: FilterType,
+        /// One per channel
+        biquads: Vec<Biquad>,
+    },
+    WaveShaper {
+        curve: Vec<f32>,
+    },
+    /// Plays silence until it has a buffer
+    Convolver(Option<Convolver>),
+    Panner,
+    Delay(DelayLine),
+    Compressor(Compressor),
+    Analyser {
+        fft_size: usize,
+        /// Mono mix of the latest input, the oldest sample first
+        samples: VecDeque<f32>,
+    },
+}
+
+impl Processor {
+    /// Sources that won't play anymore, they are dropped like Web Audio drops ended sources
+    fn is_finished(&self, time: GameTime) -> bool {
+        match self {
+            Processor::Oscillator(oscillator) => oscillator.playback.is_stopped(time),
+            Processor::BufferSource(source) => source.ended || source.playback.is_stopped(time),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Playback {
+    start: Option<GameTime>,
+    stop: Option<GameTime>,
+}
+
+impl Playback {
+    fn is_playing(&self, time: GameTime) -> bool {
+        self.start.is_some_and(|start| time >= start) && self.stop.is_none_or(|stop| time < stop)
+    }
+
+    fn is_stopped(&self, time: GameTime) -> bool {
+        self.start.is_some() && self.stop.is_some_and(|stop| time >= stop)
+    }
+}
+
+// NOTE: Web Audio band limits the basic waves, here square and sawtooth alias at high
+// frequencies
+struct Oscillator {
+    cycle: Rc<Vec<f32>>,
+    /// Position in the cycle, from 0 to 1
+    phase: f32,
+    playback: Playback,
+}
+
+#[derive(Default)]
+struct BufferSource {
+    buffer: Option<Rc<DecodedAudio>>,
+    looping: bool,
+    loop_points: Option<(GameTime, GameTime)>,
+    /// Frame of the buffer playing, between frames when the rates differ
+    position: f64,
+    ended: bool,
+    playback: Playback,
+}
+
+impl BufferSource {
+    /// Frames looped over, the whole buffer unless the loop points are valid
+    fn loop_frames(&self, buffer: &DecodedAudio) -> Option<(f64, f64)> {
+        if !self.looping || buffer.frames() == 0 {
+            return None;
+        }
+        let frames = buffer.frames() as f64;
+        let rate = buffer.sample_rate as f64;
+        match self.loop_points {
+            Some((start, end)) if start >= 0.0 && end > start && end * rate <= frames => {
+                Some((start * rate, end * rate))
+            }
+            _ => Some((0.0, frames)),
+        }
+    }
+}
+
+/// Uniformly partitioned convolution: the impulse is split in blocks whose spectra are
+/// multiplied with the spectra of the latest input blocks, so there is no added latency
+struct Convolver {
+    /// Spectrum of every block of the impulse, per channel of the impulse
+    partitions: Vec<Vec<Vec<Complex>>>,
+    /// Spectra of the latest input blocks, newest first, per output channel
+    history: Vec<VecDeque<Vec<Complex>>>,
+    /// Tail of the previous block, per output channel
+    overlap: Vec<Vec<f32>>,
+}
+
+impl Convolver {
+    fn new(impulse: &DecodedAudio) -> Self {
+        let gain = convolver_normalization(&impulse.channels.concat(), impulse.sample_rate);
+        let partitions = impulse
+            .channels
+            .iter()
+            .map(|channel| {
+                channel
+                    .chunks(BLOCK_SIZE)
+                    .map(|chunk| spectrum(chunk, gain))
+                    .collect()
+            })
+            .collect();
+        Self {
+            partitions,
+            history: vec![],
+            overlap: vec![],
+        }
+    }
+
+    /// A mono input is convolved with every channel of a stereo impulse
+    fn process(&mut self, input: &[Vec<f32>]) -> Vec<Vec<f32>> {
+        if self.partitions.is_empty() {
+            return silence(1);
+        }
+        let channels = input.len().max(self.partitions.len()).min(2);
+        self.history.resize_with(channels, VecDeque::new);
+        self.overlap.resize(channels, vec![0.0; BLOCK_SIZE]);
+
+        (0..channels)
+            .map(|c| {
+                let partitions = &self.partitions[c.min(self.partitions.len() - 1)];
+                let history = &mut self.history[c];
+                history.push_front(spectrum(&input[c.min(input.len() - 1)], 1.0));
+                history.truncate(partitions.len());
+
+                let mut sum = vec![Complex::default(); 2 * BLOCK_SIZE];
+                for (input, partition) in history.iter().zip(partitions) {
+                    for ((s, x), h) in sum.iter_mut().zip(input).zip(partition) {
+                        *s = *s + *x * *h;
+                    }
+                }
+                ifft(&mut sum);
+
+                let overlap = &mut self.overlap[c];
+                let output = (0..BLOCK_SIZE).map(|i| sum[i].re + overlap[i]).collect();
+                *overlap = sum[BLOCK_SIZE..].iter().map(|value| value.re).collect();
+                output
+            })
+            .collect()
+    }
+}
+
+/// Spectrum of a block zero padded to twice its size, so products don't wrap around
+fn spectrum(samples: &[f32], gain: f32) -> Vec<Complex> {
+    let mut values: Vec<Complex> = samples
+        .iter()
+        .map(|&x| Complex::new(x * gain, 0.0))
+        .collect();
+    values.resize(2 * BLOCK_SIZE, Complex::default());
+    fft(&mut values);
+    values
+}
+
+/// Stereo ring buffer, read between frames for fractional delays
+struct DelayLine {
+    lines: [Vec<f32>; 2],
+    /// Channels written so far, a mono input keeps the output mono
+    channels: usize,
+    /// Where the frames of the current block go
+    block_start: usize,
+    max_delay: f32,
+}
+
+impl DelayLine {
+    fn new(max_delay_time: GameTime, sample_rate: f32) -> Self {
+        let max_delay = (max_delay_time as f32 * sample_rate).max(BLOCK_SIZE as f32);
+        let len = max_delay.ceil() as usize + 2 * BLOCK_SIZE;
+        Self {
+            lines: [vec![0.0; len], vec![0.0; len]],
+            channels: 1,
+            block_start: 0,
+            max_delay,
+        }
+    }
+
+    fn write(&mut self, input: &[Vec<f32>]) {
+        self.channels = self.channels.max(input.len().min(2));
+        let len = self.lines[0].len();
+        for (c, line) in self.lines.iter_mut().enumerate() {
+            let channel = &input[c.min(input.len() - 1)];
+            for (i, sample) in channel.iter().enumerate() {
+                line[(self.block_start + i) % len] = *sample;
+            }
+        }
+    }
+
+    /// Reads the block delayed by `delay_time` seconds, but at least `min_delay` frames
+    fn read(&self, delay_time: &[f32], min_delay: f32, sample_rate: f32) -> Vec<Vec<f32>> {
+        let len = self.lines[0].len() as f64;
+        self.lines[..self.channels]
+            .iter()
+            .map(|line| {
+                delay_time
+                    .iter()
+                    .enumerate()
+                    .map(|(i, delay)| {
+                        let delay = (delay * sample_rate).clamp(min_delay, self.max_delay);
+                        let position =
+                            (self.block_start as f64 + i as f64 - delay as f64).rem_euclid(len);
+                        let index = position as usize;
+                        let fraction = (position - index as f64) as f32;
+                        let next = line[(index + 1) % line.len()];
+                        line[index] * (1.0 - fraction) + next * fraction
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn advance(&mut self) {
+        self.block_start = (self.block_start + BLOCK_SIZE) % self.lines[0].len();
+    }
+}
+
+/// Automation events of a param in time order, interpreted the way `AudioParam` does
+struct ParamTimeline {
+    default: f32,
+    /// Never holds `Automation::Value`, which becomes an event at the current time, or
+    /// `Automation::Cancel`, which drops events instead
+    events: Vec<Automation>,
+    inputs: Vec<NodeId>,
+}
+
+impl ParamTimeline {
+    /// Whether the value stays the same from `time` on
+    fn is_settled(&self, time: GameTime) -> bool {
+        self.events.iter().all(|event| event_time(event) <= time)
+            && !matches!(self.events.last(), Some(Automation::Target { .. }))
+    }
+
+    /// Replaces events that are over with the value they left the param at
+    fn forget_before(&mut self, time: GameTime) {
+        let started = self
+            .events
+            .partition_point(|event| event_time(event) <= time);
+        if started < 2 {
+            return;
+        }
+        let last = started - 1;
+        let at = event_time(&self.events[last]);
+        let value = match self.events[last] {
+            Automation::Target { .. } => value_at(&self.events[..last], self.default, at),
+            _ => value_at(&self.events[..=last], self.default, at),
+        };
+        let keep = match self.events[last] {
+            Automation::Target { .. } => last,
+            _ => last + 1,
+        };
+        self.events.drain(..keep);
+        self.events.insert(0, Automation::ValueAtTime(value, at));
+    }
+}
+
+/// Time the event starts at, or ends at for ramps
+fn event_time(event: &Automation) -> GameTime {
+    match *event {
+        Automation::Value(_) => 0.0,
+        Automation::ValueAtTime(_, time)
+        | Automation::LinearRamp(_, time)
+        | Automation::ExponentialRamp(_, time)
+        | Automation::Cancel(time) => time,
+        Automation::Target { start, .. } => start,
+    }
+}
+
+/// Value of a param at `time`: ramps go from the previous event to their own, set targets
+/// start from whatever value the param had
+fn value_at(events: &[Automation], default: f32, time: GameTime) -> f32 {
+    let mut value = default;
+    let mut from = 0.0;
+    let mut target: Option<(f32, GameTime, GameTime)> = None;
+    for event in events {
+        match *event {
+            Automation::Value(v) | Automation::ValueAtTime(v, _) => {
+                if event_time(event) > time {
+                    break;
+                }
+                (value, from, target) = (v, event_time(event), None);
+            }
+            Automation::LinearRamp(v, end) | Automation::ExponentialRamp(v, end) => {
+                if end > time {
+                    let progress = ((time - from) / (end - from)) as f32;
+                    return match event {
+                        Automation::LinearRamp(..) => value + (v - value) * progress,
+                        // Exponential ramps can't cross or start at zero, they hold instead
+                        _ if value * v > 0.0 => value * (v / value).powf(progress),
+                        _ => value,
+                    };
+                }
+                (value, from, target) = (v, end, None);
+            }
+            Automation::Target {
+                value: goal,
+                start,
+                time_constant,
+            } => {
+                if start > time {
+                    break;
+                }
+                value = approach(value, target, start);
+                from = start;
+                target = Some((goal, start, time_constant));
+            }
+            Automation::Cancel(_) => {}
+        }
+    }
+    approach(value, target, time)
+}
+
+/// Value a set target curve reached at `time`, or `value` without one
+fn approach(value: f32, target: Option<(f32, GameTime, GameTime)>, time: GameTime) -> f32 {
+    match target {
+        Some((goal, start, time_constant)) if time_constant > 0.0 => {
+            goal + (value - goal) * (-(time - start) / time_constant).exp() as f32
+        }
+        Some((goal, _, _)) => goal,
+        None => value,
+    }
+}
+
+/// Sums the outputs of `inputs`, mono inputs are copied to both channels of a stereo mix
+fn mix(
+    inputs: &[NodeId],
+    nodes: &HashMap<NodeId, NativeNode>,
+    mode: ChannelCountMode,
+) -> Vec<Vec<f32>> {
+    let outputs: Vec<&Vec<Vec<f32>>> = inputs
+        .iter()
+        .filter_map(|id| nodes.get(id))
+        .map(|node| &node.output)
+        .collect();
+    let channels = match mode {
+        ChannelCountMode::Explicit => 2,
+        _ => outputs
+            .iter()
+            .map(|output| output.len())
+            .max()
+            .unwrap_or(1)
+            .clamp(1, 2),
+    };
+
+    let mut mixed = silence(channels);
+    for output in outputs {
+        for (c, channel) in mixed.iter_mut().enumerate() {
+            for (sample, input) in channel.iter_mut().zip(&output[c.min(output.len() - 1)]) {
+                *sample += input;
+            }
+        }
+    }
+    mixed
+}
+
+fn upmix(channels: &mut Vec<Vec<f32>>) {
+    if channels.len() == 1 {
+        channels.push(channels[0].clone());
+    }
+}
+
+fn silence(channels: usize) -> Vec<Vec<f32>> {
+    vec![vec![0.0; BLOCK_SIZE]; channels]
+}
+
+fn interpolate(channel: &[f32], position: f64) -> f32 {
+    let index = position as usize;
+    let fraction = (position - index as f64) as f32;
+    let current = channel.get(index).copied().unwrap_or(0.0);
+    let next = channel.get(index + 1).copied().unwrap_or(0.0);
+    current * (1.0 - fraction) + next * fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::analyser::Analysis;
+    use crate::engine::audio_engine::AudioEngine;
+    use crate::engine::audio_engine::BITCRUSHER_CURVE_SIZE;
+    use crate::engine::test_fixtures::audio_config;
+    use crate::engine::test_fixtures::graph;
+    use crate::nodes::audio_effect::BitcrusherPreset;
+    use crate::nodes::note_generator::NoteName;
+    use crate::nodes::oscillator::WavetablePreset;
+    use crate::render::widgets::card_widget::CardType;
+    use crate::render::Samples;
+
+    const BPM: u32 = 120;
+    /// A hundred times A3, so every period of the note is exactly 100 samples
+    const SAMPLE_RATE: f32 = 44000.0;
+    const PERIOD: usize = 100;
+
+    /// What the analyser holds while A3 plays through `cards`, well into the first note
+    fn play_a3(cards: Vec<CardType>) -> Analysis {
+        let audio_config = audio_config();
+        let mut engine = AudioEngine::new(
+            NativeBackend::new(SAMPLE_RATE, AudioSink::Null),
+            Samples::new(),
+            &audio_config,
+        )
+        .expect("Engine should start");
+        let cards = [vec![CardType::NoteGenerator(Some(NoteName::A))], cards].concat();
+        engine
+            .interpret_graph(BPM, &graph(cards), &audio_config)
+            .expect("Graph should play");
+        engine
+            .schedule_notes(1.0, &audio_config)
+            .expect("Notes should be scheduled");
+        engine
+            .backend()
+            .render_until(0.3)
+            .expect("Native backend should render");
+        engine.analysis().expect("Analyser should be readable")
+    }
+
+    /// `length` samples rising evenly from -1.0 to 1.0
+    fn ramp(length: usize) -> Vec<f32> {
+        (0..length)
+            .map(|i| i as f32 / (length - 1) as f32 * 2.0 - 1.0)
+            .collect()
+    }
+
+    /// Amplitude of a harmonic of the note over the last whole periods of the waveform
+    fn harmonic_amplitude(waveform: &[f32], harmonic: usize) -> f32 {
+        let window = &waveform[waveform.len() - 20 * PERIOD..];
+        let (sin, cos) = window
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(sin, cos), (i, sample)| {
+                let angle = 2.0 * PI * (harmonic * i) as f32 / PERIOD as f32;
+                (sin + sample * angle.sin(), cos + sample * angle.cos())
+            });
+        2.0 * f32::hypot(sin, cos) / window.len() as f32
+    }
+
+    /// Checks the harmonics relative to the fundamental against `expected`, starting at
+    /// the fundamental
+    fn assert_harmonics(waveform: &[f32], expected: &[f32]) {
+        let fundamental = harmonic_amplitude(waveform, 1);
+        for (i, expected) in expected.iter().enumerate() {
+            let relative = harmonic_amplitude(waveform, i + 1) / fundamental;
+            assert!(
+                (relative - expected).abs() < 0.02,
+                "harmonic {} is {relative}, expected {expected}",
+                i + 1
+            );
+        }
+    }
+
+    #[test]
+    fn sawtooth_harmonics_fall_off_as_one_over_n() {
+        let analysis = play_a3(vec![CardType::Oscillator(WaveShape::Sawtooth)]);
+
+        assert_harmonics(
+            &analysis.waveform,
+            &[1.0, 1.0 / 2.0, 1.0 / 3.0, 1.0 / 4.0, 1.0 / 5.0],
+        );
+    }
+
+    #[test]
+    fn triangle_has_only_odd_harmonics_falling_off_as_one_over_n_squared() {
+        let analysis = play_a3(vec![CardType::Oscillator(WaveShape::Triangle)]);
+
+        assert_harmonics(&analysis.waveform, &[1.0, 0.0, 1.0 / 9.0, 0.0, 1.0 / 25.0]);
+    }
+
+    #[test]
+    fn wavetable_plays_the_harmonics_of_its_preset() {
+        for preset in [
+            WavetablePreset::Organ,
+            WavetablePreset::Brass,
+            WavetablePreset::Bell,
+        ] {
+            let analysis = play_a3(vec![CardType::Oscillator(WaveShape::Wavetable(preset))]);
+
+            let harmonics = preset.harmonics();
+            let expected: Vec<f32> = harmonics[1..].iter().map(|h| h / harmonics[1]).collect();
+            assert_harmonics(&analysis.waveform, &expected);
+        }
+    }
+
+    #[test]
+    fn sine_peaks_at_the_note_frequency_and_output_gain() {
+        let analysis = play_a3(vec![CardType::Oscillator(WaveShape::Sine)]);
+
+        let peak = analysis.spectrum.peak_frequency();
+        assert!((peak - 440.0).abs() <= analysis.spectrum.bin_width());
+        let amplitude = harmonic_amplitude(&analysis.waveform, 1);
+        assert!((amplitude - audio_config().output_gain).abs() < 0.01);
+    }
+
+    #[test]
+    fn four_bit_quantize_maps_a_ramp_onto_16_levels() {
+        let params = BitcrusherPreset::FourBit.parameters();
+
+        let mut levels: Vec<f32> = ramp(1000).iter().map(|x| params.quantize(*x)).collect();
+        levels.dedup();
+
+        assert_eq!(levels.len(), 16);
+        assert_eq!(levels[0], -1.0);
+        assert_eq!(levels[15], 7.0 / 8.0);
+        for pair in levels.windows(2) {
+            assert_eq!(pair[1] - pair[0], 1.0 / 8.0);
+        }
+    }
+
+    #[test]
+    fn staircase_curve_matches_quantize() {
+        for preset in [
+            BitcrusherPreset::EightBit,
+            BitcrusherPreset::FourBit,
+            BitcrusherPreset::Telephone,
+        ] {
+            let params = preset.parameters();
+            let curve = params.curve(BITCRUSHER_CURVE_SIZE);
+            let steps = 2f32.powf(params.bits - 1.0);
+            let point_width = 2.0 / (BITCRUSHER_CURVE_SIZE - 1) as f32;
+
+            for x in ramp(10_000) {
+                // NOTE: the wave shaper interpolates between the points on either side
+                // of a step, so only inputs clear of the step edges are exact
+                let to_edge = ((x * steps + 0.5).round() - (x * steps + 0.5)).abs() / steps;
+                if to_edge < point_width {
+                    continue;
+                }
+                assert_eq!(shape_sample(&curve, x), params.quantize(x), "at {x}");
+            }
+        }
+    }
+
+    #[test]
+    fn four_bit_bitcrusher_plays_a_sine_on_at_most_16_levels() {
+        let analysis = play_a3(vec![
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Bitcrusher(BitcrusherPreset::FourBit),
+        ]);
+
+        let held = &analysis.waveform[analysis.waveform.len() - 20 * PERIOD..];
+        let mut levels = held.to_vec();
+        levels.sort_by(f32::total_cmp);
+        levels.dedup();
+        assert!(levels.len() <= 16, "sine plays on {} levels", levels.len());
+        assert!(levels.len() > 2, "bitcrusher played silence");
+    }
+}
@@ -0,0 +1,325 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::core::GameTime;
+use crate::engine::errors::GameError;
+use crate::engine::errors::GameResult;
+use crate::nodes::audio_effect::FilterType;
+
+use super::AudioBackend;
+use super::Automation;
+use super::ChannelCountMode;
+use super::NodeKind;
+use super::OscillatorType;
+use super::Oversample;
+use super::Param;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BufferId(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WaveId(usize);
+
+/// Every call the engine made, in order
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackendEvent {
+    CreateNode(NodeId, NodeKind),
+    CreateBuffer(BufferId),
+    CreatePeriodicWave(WaveId),
+    Connect(NodeId, NodeId),
+    ConnectParam(NodeId, Param<NodeId>),
+    Disconnect(NodeId),
+    SetOscillatorType(NodeId, OscillatorType),
+    SetPeriodicWave(NodeId, WaveId),
+    SetFilterType(NodeId, FilterType),
+    SetCurve(NodeId, Vec<f32>),
+    SetOversample(NodeId, Oversample),
+    SetBuffer(NodeId, BufferId),
+    SetLoop(NodeId, Option<(GameTime, GameTime)>),
+    SetChannelCountMode(NodeId, ChannelCountMode),
+    Automate(Param<NodeId>, Automation),
+    Start(NodeId, GameTime),
+    Stop(NodeId, Option<GameTime>),
+}
+
+/// Backend that plays nothing and records every call instead, so the node graph and
+/// automation timeline the engine builds can be inspected natively
+pub struct RecordingBackend {
+    sample_rate: f32,
+    current_time: Cell<GameTime>,
+    processors: Vec<String>,
+    /// Kind of every created node, `NodeId(0)` is the destination
+    nodes: RefCell<Vec<NodeKind>>,
+    buffers: RefCell<Vec<Vec<Vec<f32>>>>,
+    waves: Cell<usize>,
+    /// Values set right away, params never set keep the Web Audio default
+    values: RefCell<HashMap<Param<NodeId>, f32>>,
+    events: RefCell<Vec<BackendEvent>>,
+}
+
+impl RecordingBackend {
+    pub const DESTINATION: NodeId = NodeId(0);
+
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            current_time: Cell::new(0.0),
+            processors: vec![],
+            nodes: RefCell::new(vec![]),
+            buffers: RefCell::new(vec![]),
+            waves: Cell::new(0),
+            values: RefCell::new(HashMap::new()),
+            events: RefCell::new(vec![]),
+        }
+    }
+
+    /// Pretends the worklet module of `processor` loaded
+    pub fn with_processor(mut self, processor: &str) -> Self {
+        self.processors.push(processor.to_string());
+        self
+    }
+
+    pub fn set_current_time(&self, time: GameTime) {
+        self.current_time.set(time);
+    }
+
+    pub fn events(&self) -> Vec<BackendEvent> {
+        self.events.borrow().clone()
+    }
+
+    pub fn clear_events(&self) {
+        self.events.borrow_mut().clear();
+    }
+
+    /// Kind of a created node, `None` for the destination
+    pub fn node_kind(&self, node: NodeId) -> Option<NodeKind> {
+        node.0
+            .checked_sub(1)
+            .and_then(|i| self.nodes.borrow().get(i).copied())
+    }
+
+    pub fn nodes_of(&self, kind: NodeKind) -> Vec<NodeId> {
+        self.nodes
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, k)| **k == kind)
+            .map(|(i, _)| NodeId(i + 1))
+            .collect()
+    }
+
+    /// Node to node connections, disconnections aside
+    pub fn connections(&self) -> Vec<(NodeId, NodeId)> {
+        self.events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                BackendEvent::Connect(from, to) => Some((*from, *to)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Automation of a single param in the order it was scheduled
+    pub fn automations(&self, param: &Param<NodeId>) -> Vec<Automation> {
+        self.events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                BackendEvent::Automate(p, automation) if p == param => Some(*automation),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn buffer(&self, buffer: BufferId) -> Option<Vec<Vec<f32>>> {
+        self.buffers.borrow().get(buffer.0).cloned()
+    }
+
+    fn record(&self, event: BackendEvent) {
+        self.events.borrow_mut().push(event);
+    }
+
+    fn check_node(&self, node: &NodeId) -> GameResult<()> {
+        if node.0 > self.nodes.borrow().len() {
+            return Err(GameError::msg("Audio node was not created by this backend"));
+        }
+        Ok(())
+    }
+
+    fn check_kind(&self, node: &NodeId, kinds: &[NodeKind]) -> GameResult<()> {
+        self.check_node(node)?;
+        match self.node_kind(*node) {
+            Some(kind) if kinds.contains(&kind) => Ok(()),
+            _ => Err(GameError::msg("Audio node is not of the expected type")),
+        }
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    type Node = NodeId;
+    type Buffer = BufferId;
+    type Wave = WaveId;
+
+    fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    fn current_time(&self) -> GameTime {
+        self.current_time.get()
+    }
+
+    fn destination(&self) -> NodeId {
+        Self::DESTINATION
+    }
+
+    fn has_processor(&self, name: &str) -> bool {
+        self.processors.iter().any(|p| p == name)
+    }
+
+    fn create_node(&self, kind: NodeKind) -> GameResult<NodeId> {
+        if let NodeKind::Worklet(processor) = kind {
+            if !self.has_processor(processor) {
+                return Err(GameError::msg("Worklet processor is not registered"));
+            }
+        }
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(kind);
+        let node = NodeId(nodes.len());
+        self.record(BackendEvent::CreateNode(node, kind));
+        Ok(node)
+    }
+
+    fn create_buffer(&self, _sample_rate: f32, channels: &[Vec<f32>]) -> GameResult<BufferId> {
+        let mut buffers = self.buffers.borrow_mut();
+        let buffer = BufferId(buffers.len());
+        buffers.push(channels.to_vec());
+        self.record(BackendEvent::CreateBuffer(buffer));
+        Ok(buffer)
+    }
+
+    fn create_periodic_wave(&self, _real: &[f32], _imag: &[f32]) -> GameResult<WaveId> {
+        let wave = WaveId(self.waves.get());
+        self.waves.set(wave.0 + 1);
+        self.record(BackendEvent::CreatePeriodicWave(wave));
+        Ok(wave)
+    }
+
+    fn connect(&self, from: &NodeId, to: &NodeId) -> GameResult<()> {
+        self.check_node(from)?;
+        self.check_node(to)?;
+        self.record(BackendEvent::Connect(*from, *to));
+        Ok(())
+    }
+
+    fn connect_param(&self, from: &NodeId, to: &Param<NodeId>) -> GameResult<()> {
+        self.check_node(from)?;
+        self.check_node(&to.node)?;
+        self.record(BackendEvent::ConnectParam(*from, to.clone()));
+        Ok(())
+    }
+
+    fn disconnect(&self, node: &NodeId) -> GameResult<()> {
+        self.check_node(node)?;
+        self.record(BackendEvent::Disconnect(*node));
+        Ok(())
+    }
+
+    fn set_oscillator_type(&self, node: &NodeId, wave: OscillatorType) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::Oscillator])?;
+        self.record(BackendEvent::SetOscillatorType(*node, wave));
+        Ok(())
+    }
+
+    fn set_periodic_wave(&self, node: &NodeId, wave: &WaveId) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::Oscillator])?;
+        self.record(BackendEvent::SetPeriodicWave(*node, *wave));
+        Ok(())
+    }
+
+    fn set_filter_type(&self, node: &NodeId, filter_type: FilterType) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::BiquadFilter])?;
+        self.record(BackendEvent::SetFilterType(*node, filter_type));
+        Ok(())
+    }
+
+    fn set_curve(&self, node: &NodeId, curve: &[f32]) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::WaveShaper])?;
+        self.record(BackendEvent::SetCurve(*node, curve.to_vec()));
+        Ok(())
+    }
+
+    fn set_oversample(&self, node: &NodeId, oversample: Oversample) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::WaveShaper])?;
+        self.record(BackendEvent::SetOversample(*node, oversample));
+        Ok(())
+    }
+
+    fn set_buffer(&self, node: &NodeId, buffer: &BufferId) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::BufferSource, NodeKind::Convolver])?;
+        self.record(BackendEvent::SetBuffer(*node, *buffer));
+        Ok(())
+    }
+
+    fn set_loop(&self, node: &NodeId, points: Option<(GameTime, GameTime)>) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::BufferSource])?;
+        self.record(BackendEvent::SetLoop(*node, points));
+        Ok(())
+    }
+
+    fn set_channel_count_mode(&self, node: &NodeId, mode: ChannelCountMode) -> GameResult<()> {
+        self.check_node(node)?;
+        self.record(BackendEvent::SetChannelCountMode(*node, mode));
+        Ok(())
+    }
+
+    fn param_value(&self, param: &Param<NodeId>) -> GameResult<f32> {
+        self.check_node(&param.node)?;
+        let kind = self
+            .node_kind(param.node)
+            .ok_or(GameError::msg("Audio node has no such param"))?;
+        Ok(self
+            .values
+            .borrow()
+            .get(param)
+            .copied()
+            .unwrap_or(kind.default_value(param.name)))
+    }
+
+    fn automate(&self, param: &Param<NodeId>, automation: Automation) -> GameResult<()> {
+        self.check_node(&param.node)?;
+        if let Automation::Value(value) = automation {
+            self.values.borrow_mut().insert(param.clone(), value);
+        }
+        self.record(BackendEvent::Automate(param.clone(), automation));
+        Ok(())
+    }
+
+    fn start(&self, node: &NodeId, when: GameTime) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::Oscillator, NodeKind::BufferSource])?;
+        self.record(BackendEvent::Start(*node, when));
+        Ok(())
+    }
+
+    fn stop(&self, node: &NodeId, when: Option<GameTime>) -> GameResult<()> {
+        self.check_kind(node, &[NodeKind::Oscillator, NodeKind::BufferSource])?;
+        self.record(BackendEvent::Stop(*node, when));
+        Ok(())
+    }
+
+    /// Nothing plays, analysers only ever hold silence
+    fn read_waveform(&self, node: &NodeId, samples: &mut [f32]) -> GameResult<()> {
+        self.check_node(node)?;
+        match self.node_kind(*node) {
+            Some(NodeKind::Analyser { .. }) => {
+                samples.fill(0.0);
+                Ok(())
+            }
+            _ => Err(GameError::msg("Audio node is not of the expected type")),
+        }
+    }
+}
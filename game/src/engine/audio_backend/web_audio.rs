@@ -0,0 +1,372 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::Float32Array;
+use web_sys::AnalyserNode;
+use web_sys::AudioBuffer;
+use web_sys::AudioBufferSourceNode;
+use web_sys::AudioContext;
+use web_sys::AudioNode;
+use web_sys::AudioParam;
+use web_sys::AudioScheduledSourceNode;
+use web_sys::AudioWorkletNode;
+use web_sys::BiquadFilterNode;
+use web_sys::BiquadFilterType;
+use web_sys::ConvolverNode;
+use web_sys::DelayNode;
+use web_sys::DynamicsCompressorNode;
+use web_sys::GainNode;
+use web_sys::OscillatorNode;
+use web_sys::OverSampleType;
+use web_sys::PeriodicWave;
+use web_sys::StereoPannerNode;
+use web_sys::WaveShaperNode;
+
+use crate::core::GameTime;
+use crate::engine::errors::GameError;
+use crate::engine::errors::GameResult;
+use crate::nodes::audio_effect::FilterType;
+
+use super::AudioBackend;
+use super::Automation;
+use super::ChannelCountMode;
+use super::NodeKind;
+use super::OscillatorType;
+use super::Oversample;
+use super::Param;
+use super::ParamName;
+
+pub struct WebAudioBackend {
+    audio_context: AudioContext,
+    /// Processors of the worklet modules that loaded
+    processors: RefCell<Vec<String>>,
+}
+
+impl WebAudioBackend {
+    pub fn new() -> GameResult<Self> {
+        let audio_context =
+            AudioContext::new().map_err(GameError::js("Could not construct AudioContext"))?;
+        Ok(Self {
+            audio_context,
+            processors: RefCell::new(vec![]),
+        })
+    }
+
+    /// Loads a worklet module registering `processor`
+    pub async fn add_worklet_module(&self, url: &str, processor: &str) -> GameResult<()> {
+        // NOTE: AudioWorklet is only available in secure contexts
+        let worklet = self
+            .audio_context
+            .audio_worklet()
+            .map_err(GameError::js("AudioWorklet is not available"))?;
+        let promise = worklet
+            .add_module(url)
+            .map_err(GameError::js("Could not add worklet module"))?;
+        JsFuture::from(promise)
+            .await
+            .map_err(GameError::js("Could not load worklet module"))?;
+        self.processors.borrow_mut().push(processor.to_string());
+        Ok(())
+    }
+}
+
+impl AudioBackend for WebAudioBackend {
+    type Node = AudioNode;
+    type Buffer = AudioBuffer;
+    type Wave = PeriodicWave;
+
+    fn sample_rate(&self) -> f32 {
+        self.audio_context.sample_rate()
+    }
+
+    fn current_time(&self) -> GameTime {
+        self.audio_context.current_time()
+    }
+
+    fn destination(&self) -> AudioNode {
+        self.audio_context.destination().into()
+    }
+
+    fn has_processor(&self, name: &str) -> bool {
+        self.processors.borrow().iter().any(|p| p == name)
+    }
+
+    fn create_node(&self, kind: NodeKind) -> GameResult<AudioNode> {
+        let context = &self.audio_context;
+        let node: AudioNode = match kind {
+            NodeKind::Gain => context
+                .create_gain()
+                .map_err(GameError::js("Could not create gain node"))?
+                .into(),
+            NodeKind::Oscillator => context
+                .create_oscillator()
+                .map_err(GameError::js("Could not create oscillator"))?
+                .into(),
+            NodeKind::BiquadFilter => context
+                .create_biquad_filter()
+                .map_err(GameError::js("Could not create biquad filter"))?
+                .into(),
+            NodeKind::WaveShaper => context
+                .create_wave_shaper()
+                .map_err(GameError::js("Could not create wave shaper"))?
+                .into(),
+            NodeKind::Convolver => context
+                .create_convolver()
+                .map_err(GameError::js("Could not create convolver node"))?
+                .into(),
+            NodeKind::BufferSource => context
+                .create_buffer_source()
+                .map_err(GameError::js("Could not create buffer source"))?
+                .into(),
+            NodeKind::StereoPanner => context
+                .create_stereo_panner()
+                .map_err(GameError::js("Could not create stereo panner"))?
+                .into(),
+            NodeKind::Delay { max_delay_time } => context
+                .create_delay_with_max_delay_time(max_delay_time)
+                .map_err(GameError::js("Could not create delay node"))?
+                .into(),
+            NodeKind::DynamicsCompressor => context
+                .create_dynamics_compressor()
+                .map_err(GameError::js("Could not create compressor"))?
+                .into(),
+            NodeKind::Analyser { fft_size } => {
+                let analyser = context
+                    .create_analyser()
+                    .map_err(GameError::js("Could not create analyser"))?;
+                analyser.set_fft_size(fft_size as u32);
+                analyser.into()
+            }
+            NodeKind::Worklet(processor) => AudioWorkletNode::new(context, processor)
+                .map_err(GameError::js("Could not create worklet node"))?
+                .into(),
+        };
+        Ok(node)
+    }
+
+    fn create_buffer(&self, sample_rate: f32, channels: &[Vec<f32>]) -> GameResult<AudioBuffer> {
+        let frames = channels.first().map(Vec::len).unwrap_or(0);
+        let buffer = self
+            .audio_context
+            .create_buffer(channels.len() as u32, frames as u32, sample_rate)
+            .map_err(GameError::js("Could not create audio buffer"))?;
+        for (i, channel) in channels.iter().enumerate() {
+            buffer
+                .copy_to_channel(channel, i as i32)
+                .map_err(GameError::js("Could not copy data to audio buffer"))?;
+        }
+        Ok(buffer)
+    }
+
+    fn create_periodic_wave(&self, real: &[f32], imag: &[f32]) -> GameResult<PeriodicWave> {
+        self.audio_context
+            .create_periodic_wave(&mut real.to_vec(), &mut imag.to_vec())
+            .map_err(GameError::js("Could not create periodic wave"))
+    }
+
+    fn connect(&self, from: &AudioNode, to: &AudioNode) -> GameResult<()> {
+        from.connect_with_audio_node(to)
+            .map_err(GameError::js("Could not connect audio nodes"))?;
+        Ok(())
+    }
+
+    fn connect_param(&self, from: &AudioNode, to: &Param<AudioNode>) -> GameResult<()> {
+        from.connect_with_audio_param(&audio_param(to)?)
+            .map_err(GameError::js("Could not connect audio node to param"))
+    }
+
+    fn disconnect(&self, node: &AudioNode) -> GameResult<()> {
+        node.disconnect()
+            .map_err(GameError::js("Could not disconnect audio node"))
+    }
+
+    fn set_oscillator_type(&self, node: &AudioNode, wave: OscillatorType) -> GameResult<()> {
+        let wave = match wave {
+            OscillatorType::Sine => web_sys::OscillatorType::Sine,
+            OscillatorType::Square => web_sys::OscillatorType::Square,
+            OscillatorType::Sawtooth => web_sys::OscillatorType::Sawtooth,
+            OscillatorType::Triangle => web_sys::OscillatorType::Triangle,
+        };
+        cast::<OscillatorNode>(node)?.set_type(wave);
+        Ok(())
+    }
+
+    fn set_periodic_wave(&self, node: &AudioNode, wave: &PeriodicWave) -> GameResult<()> {
+        cast::<OscillatorNode>(node)?.set_periodic_wave(wave);
+        Ok(())
+    }
+
+    fn set_filter_type(&self, node: &AudioNode, filter_type: FilterType) -> GameResult<()> {
+        let filter_type = match filter_type {
+            FilterType::LowPass => BiquadFilterType::Lowpass,
+            FilterType::HighPass => BiquadFilterType::Highpass,
+            FilterType::BandPass => BiquadFilterType::Bandpass,
+            FilterType::LowShelf => BiquadFilterType::Lowshelf,
+            FilterType::HighShelf => BiquadFilterType::Highshelf,
+            FilterType::Peaking => BiquadFilterType::Peaking,
+            FilterType::Notch => BiquadFilterType::Notch,
+            FilterType::AllPass => BiquadFilterType::Allpass,
+        };
+        cast::<BiquadFilterNode>(node)?.set_type(filter_type);
+        Ok(())
+    }
+
+    fn set_curve(&self, node: &AudioNode, curve: &[f32]) -> GameResult<()> {
+        cast::<WaveShaperNode>(node)?.set_curve_opt_f32_array(Some(&Float32Array::from(curve)));
+        Ok(())
+    }
+
+    fn set_oversample(&self, node: &AudioNode, oversample: Oversample) -> GameResult<()> {
+        let oversample = match oversample {
+            Oversample::None => OverSampleType::None,
+            Oversample::TwoTimes => OverSampleType::N2x,
+            Oversample::FourTimes => OverSampleType::N4x,
+        };
+        cast::<WaveShaperNode>(node)?.set_oversample(oversample);
+        Ok(())
+    }
+
+    fn set_buffer(&self, node: &AudioNode, buffer: &AudioBuffer) -> GameResult<()> {
+        if let Some(convolver) = node.dyn_ref::<ConvolverNode>() {
+            convolver.set_buffer(Some(buffer));
+        } else {
+            cast::<AudioBufferSourceNode>(node)?.set_buffer(Some(buffer));
+        }
+        Ok(())
+    }
+
+    fn set_loop(&self, node: &AudioNode, points: Option<(GameTime, GameTime)>) -> GameResult<()> {
+        let source = cast::<AudioBufferSourceNode>(node)?;
+        source.set_loop(true);
+        if let Some((start, end)) = points {
+            source.set_loop_start(start);
+            source.set_loop_end(end);
+        }
+        Ok(())
+    }
+
+    fn set_channel_count_mode(&self, node: &AudioNode, mode: ChannelCountMode) -> GameResult<()> {
+        node.set_channel_count_mode(match mode {
+            ChannelCountMode::Max => web_sys::ChannelCountMode::Max,
+            ChannelCountMode::ClampedMax => web_sys::ChannelCountMode::ClampedMax,
+            ChannelCountMode::Explicit => web_sys::ChannelCountMode::Explicit,
+        });
+        Ok(())
+    }
+
+    fn param_value(&self, param: &Param<AudioNode>) -> GameResult<f32> {
+        Ok(audio_param(param)?.value())
+    }
+
+    fn automate(&self, param: &Param<AudioNode>, automation: Automation) -> GameResult<()> {
+        let audio_param = audio_param(param)?;
+        match automation {
+            Automation::Value(value) => {
+                audio_param.set_value(value);
+                Ok(())
+            }
+            Automation::ValueAtTime(value, when) => audio_param
+                .set_value_at_time(value, when)
+                .map(|_| ())
+                .map_err(GameError::js("Could not set param value")),
+            Automation::LinearRamp(value, end) => audio_param
+                .linear_ramp_to_value_at_time(value, end)
+                .map(|_| ())
+                .map_err(GameError::js("Could not schedule linear ramp")),
+            Automation::ExponentialRamp(value, end) => audio_param
+                .exponential_ramp_to_value_at_time(value, end)
+                .map(|_| ())
+                .map_err(GameError::js("Could not schedule exponential ramp")),
+            Automation::Target {
+                value,
+                start,
+                time_constant,
+            } => audio_param
+                .set_target_at_time(value, start, time_constant)
+                .map(|_| ())
+                .map_err(GameError::js("Could not update param")),
+            Automation::Cancel(when) => audio_param
+                .cancel_scheduled_values(when)
+                .map(|_| ())
+                .map_err(GameError::js("Could not cancel scheduled values")),
+        }
+    }
+
+    fn start(&self, node: &AudioNode, when: GameTime) -> GameResult<()> {
+        cast::<AudioScheduledSourceNode>(node)?
+            .start_with_when(when)
+            .map_err(GameError::js("Could not start source"))
+    }
+
+    fn stop(&self, node: &AudioNode, when: Option<GameTime>) -> GameResult<()> {
+        let source = cast::<AudioScheduledSourceNode>(node)?;
+        match when {
+            Some(when) => source.stop_with_when(when),
+            None => source.stop(),
+        }
+        .map_err(GameError::js("Could not stop source"))
+    }
+
+    fn read_waveform(&self, node: &AudioNode, samples: &mut [f32]) -> GameResult<()> {
+        cast::<AnalyserNode>(node)?.get_float_time_domain_data(samples);
+        Ok(())
+    }
+}
+
+fn cast<T: JsCast>(node: &AudioNode) -> GameResult<&T> {
+    node.dyn_ref::<T>()
+        .ok_or(GameError::msg("Audio node is not of the expected type"))
+}
+
+fn audio_param(param: &Param<AudioNode>) -> GameResult<AudioParam> {
+    let node = &param.node;
+    let audio_param = match param.name {
+        ParamName::Gain => node.dyn_ref::<GainNode>().map(GainNode::gain).or_else(|| {
+            node.dyn_ref::<BiquadFilterNode>()
+                .map(BiquadFilterNode::gain)
+        }),
+        ParamName::Frequency => node
+            .dyn_ref::<OscillatorNode>()
+            .map(OscillatorNode::frequency)
+            .or_else(|| {
+                node.dyn_ref::<BiquadFilterNode>()
+                    .map(BiquadFilterNode::frequency)
+            }),
+        ParamName::Detune => node
+            .dyn_ref::<OscillatorNode>()
+            .map(OscillatorNode::detune)
+            .or_else(|| {
+                node.dyn_ref::<AudioBufferSourceNode>()
+                    .map(AudioBufferSourceNode::detune)
+            })
+            .or_else(|| {
+                node.dyn_ref::<BiquadFilterNode>()
+                    .map(BiquadFilterNode::detune)
+            }),
+        ParamName::Q => node.dyn_ref::<BiquadFilterNode>().map(BiquadFilterNode::q),
+        ParamName::Pan => node
+            .dyn_ref::<StereoPannerNode>()
+            .map(StereoPannerNode::pan),
+        ParamName::DelayTime => node.dyn_ref::<DelayNode>().map(DelayNode::delay_time),
+        ParamName::PlaybackRate => node
+            .dyn_ref::<AudioBufferSourceNode>()
+            .map(AudioBufferSourceNode::playback_rate),
+        ParamName::Threshold => compressor(node).map(DynamicsCompressorNode::threshold),
+        ParamName::Knee => compressor(node).map(DynamicsCompressorNode::knee),
+        ParamName::Ratio => compressor(node).map(DynamicsCompressorNode::ratio),
+        ParamName::Attack => compressor(node).map(DynamicsCompressorNode::attack),
+        ParamName::Release => compressor(node).map(DynamicsCompressorNode::release),
+        ParamName::Worklet(name) => {
+            let params = cast::<AudioWorkletNode>(node)?
+                .parameters()
+                .map_err(GameError::js("Could not read worklet params"))?;
+            params.get(name)
+        }
+    };
+    audio_param.ok_or(GameError::msg("Audio node has no such param"))
+}
+
+fn compressor(node: &AudioNode) -> Option<&DynamicsCompressorNode> {
+    node.dyn_ref::<DynamicsCompressorNode>()
+}
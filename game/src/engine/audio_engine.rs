@@ -1,29 +1,138 @@
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
 
 use crate::core::GameTime;
 use crate::engine::errors::GameError;
 use crate::engine::errors::GameResult;
 use crate::nodes::audio_effect::AudioEffect;
-use crate::nodes::audio_effect::DistortionCurve;
+use crate::nodes::audio_effect::BitcrusherParameters;
+use crate::nodes::audio_effect::CompressorParameters;
+use crate::nodes::audio_effect::DelayParameters;
 use crate::nodes::audio_effect::DistortionParameters;
 use crate::nodes::audio_effect::FilterParameters;
+use crate::nodes::audio_effect::ModulatedDelayParameters;
+use crate::nodes::audio_effect::PanParameters;
+use crate::nodes::audio_effect::PhaserParameters;
 use crate::nodes::audio_effect::ReverbParameters;
 use crate::nodes::audio_graph::AudioGraph;
+use crate::nodes::envelope::Envelope;
+use crate::nodes::lfo::Lfo;
+use crate::nodes::lfo::ModulationTarget;
+use crate::nodes::note_generator::MusicTime;
+use crate::nodes::note_generator::NoteDuration;
+use crate::nodes::note_generator::NoteEvent;
+use crate::nodes::oscillator::FmPreset;
+use crate::nodes::oscillator::NoiseColor;
+use crate::nodes::oscillator::Oscillator;
 use crate::nodes::oscillator::WaveShape;
-use web_sys::js_sys::Float32Array;
-use web_sys::AudioContext;
-use web_sys::AudioNode as WebAudioNode;
-use web_sys::BiquadFilterNode;
-use web_sys::ConvolverNode;
-use web_sys::GainNode;
-use web_sys::OscillatorNode;
-use web_sys::OscillatorType;
-use web_sys::OverSampleType;
-use web_sys::WaveShaperNode;
-
+use crate::nodes::oscillator::WavetablePreset;
+use crate::nodes::parameters::EffectMix;
+use crate::nodes::percussion::DrumLayer;
+use crate::nodes::percussion::DrumSource;
+use crate::nodes::percussion::DrumVoice;
+use crate::nodes::sampler::SampleAsset;
+use crate::nodes::sampler::SampleZone;
+use crate::nodes::voice::Glide;
+use crate::nodes::voice::MonoNote;
+use crate::nodes::voice::Unison;
+#[cfg(target_arch = "wasm32")]
+use miniquad::warn;
+
+use super::analyser::Analysis;
+use super::analyser::ANALYSER_SIZE;
+#[cfg(target_arch = "wasm32")]
+use super::audio_backend::web_audio::WebAudioBackend;
+use super::audio_backend::AudioBackend;
+use super::audio_backend::Automation;
+use super::audio_backend::ChannelCountMode;
+use super::audio_backend::NodeKind;
+use super::audio_backend::OscillatorType;
+use super::audio_backend::Oversample;
+use super::audio_backend::Param;
+use super::audio_backend::ParamName;
 use super::game_config::AudioConfig;
-use crate::core::random;
+use super::wav::DecodedAudio;
+use crate::core::Random;
+use crate::core::RandomStream;
+use crate::render::Samples;
+
+const CLIPPER_CURVE_SIZE: usize = 1024;
+const DISTORTION_CURVE_SIZE: usize = 44100;
+/// Points of the bitcrusher fallback curve, enough for the steps to stay sharp at 16 bits
+pub(crate) const BITCRUSHER_CURVE_SIZE: usize = 1 << 17;
+const BITCRUSHER_WORKLET_URL: &str = "resources/worklets/bitcrusher.js";
+const BITCRUSHER_PROCESSOR: &str = "bitcrusher";
+const BITCRUSHER_BITS: ParamName = ParamName::Worklet("bits");
+const BITCRUSHER_RATE: ParamName = ParamName::Worklet("rate");
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct VoiceStats {
+    /// Voices sounding or scheduled to
+    pub voices: usize,
+    /// Voices stopped early to make room for new notes since the game started
+    pub steals: u32,
+}
+
+/// Where the playing loop is, and which cards its sounding notes come from
+#[derive(Clone, Debug, Default)]
+pub struct Playhead {
+    /// Start of the current repetition of the loop
+    pub loop_start: GameTime,
+    /// Fraction of the loop played so far
+    pub position: f32,
+    /// Index of the card each sounding note comes from, with the fraction of the note
+    /// played so far
+    pub active_cards: Vec<(usize, f32)>,
+}
+
+/// Boundary a changed graph waits for before it takes over from the playing one
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwapQuantization {
+    Immediate,
+    Beat,
+    Bar,
+    Loop,
+}
+
+impl SwapQuantization {
+    pub const ALL: [SwapQuantization; 4] = [
+        SwapQuantization::Immediate,
+        SwapQuantization::Beat,
+        SwapQuantization::Bar,
+        SwapQuantization::Loop,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SwapQuantization::Immediate => "Immediate",
+            SwapQuantization::Beat => "Beat",
+            SwapQuantization::Bar => "Bar",
+            SwapQuantization::Loop => "Loop",
+        }
+    }
+
+    /// First boundary from `now` on, counted from the start of the playing loop
+    fn next_boundary(
+        &self,
+        now: GameTime,
+        origin: GameTime,
+        loop_seconds: GameTime,
+        bpm: u32,
+    ) -> GameTime {
+        let period = match self {
+            SwapQuantization::Immediate => return now,
+            SwapQuantization::Beat => MusicTime::from(NoteDuration::Quarter).to_seconds(bpm),
+            SwapQuantization::Bar => MusicTime::from(NoteDuration::Whole).to_seconds(bpm),
+            SwapQuantization::Loop => loop_seconds,
+        };
+        if period <= 0.0 || now <= origin {
+            return now.max(origin);
+        }
+        origin + ((now - origin) / period).ceil() * period
+    }
+}
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum AudioState {
@@ -31,45 +140,120 @@ pub enum AudioState {
     Playing,
 }
 
-pub struct AudioEngine {
-    audio_context: AudioContext,
-    master_gain: GainNode,
-    oscillators: Vec<RefCell<GameOscillator>>,
-    effects: Vec<RefCell<Box<dyn AudioEffectNode>>>,
+pub struct AudioEngine<B: AudioBackend> {
+    backend: B,
+    master_gain: B::Node,
+    /// Taps the mix after the master gain for the oscilloscope and spectrum
+    analyser: B::Node,
+    master_bus: GameMasterBus<B>,
+    voices: VoiceAllocator<B>,
+    /// Notes of every lane, turned into voices shortly before they play
+    sequences: Vec<GameSequence<B>>,
+    effects: Vec<RefCell<GameEffectSlot<B>>>,
+    lfos: Vec<RefCell<GameLfo<B>>>,
+    lanes: Vec<GameLane<B>>,
+    /// Effect chains of swapped out graphs, fading out
+    retired: Vec<RetiredChain<B>>,
+    /// Pan of the lanes, kept so lanes created later start at the same position
+    lane_pans: RefCell<Vec<f32>>,
+    samples: Samples,
+    /// Decoded samples copied into backend buffers, created the first time a lane plays them
+    sample_buffers: HashMap<SampleAsset, B::Buffer>,
     state: Cell<AudioState>,
+    /// Time constant of live param changes
+    param_smoothing: f64,
 }
 
-impl AudioEngine {
-    pub fn new() -> GameResult<AudioEngine> {
-        let audio_context =
-            AudioContext::new().map_err(GameError::js("Could not construct AudioContext"))?;
+#[cfg(target_arch = "wasm32")]
+impl AudioEngine<WebAudioBackend> {
+    /// Registers the processors of worklet effects, the effects fall back to built-in
+    /// nodes if a module can't be loaded
+    pub async fn load_worklets(&self) {
+        let result = self
+            .backend
+            .add_worklet_module(BITCRUSHER_WORKLET_URL, BITCRUSHER_PROCESSOR)
+            .await;
+        if let Err(e) = result {
+            warn!("Bitcrusher falls back to a wave shaper: {}", e.show());
+        }
+    }
+}
+
+impl<B: AudioBackend> AudioEngine<B> {
+    pub fn new(
+        backend: B,
+        samples: Samples,
+        audio_config: &AudioConfig,
+    ) -> GameResult<AudioEngine<B>> {
+        let master_gain = backend.create_node(NodeKind::Gain)?;
 
-        let master_gain = audio_context
-            .create_gain()
-            .map_err(GameError::js("Could not create master gain node"))?;
+        let analyser = backend.create_node(NodeKind::Analyser {
+            fft_size: ANALYSER_SIZE,
+        })?;
 
-        master_gain
-            .connect_with_audio_node(&audio_context.destination())
-            .map_err(GameError::js(
-                "Could not connect master gain to destination",
-            ))?;
+        let master_bus = GameMasterBus::new(&backend, audio_config)?;
+        backend.connect(&master_gain, &analyser)?;
+        backend.connect(&analyser, &master_bus.compressor)?;
 
-        master_gain.gain().set_value(1.0);
+        set_param(&backend, &master_gain, ParamName::Gain, 1.0)?;
 
         Ok(AudioEngine {
-            audio_context,
+            backend,
             master_gain,
-            oscillators: vec![],
+            analyser,
+            master_bus,
+            voices: VoiceAllocator::new(),
+            sequences: vec![],
             effects: vec![],
+            lfos: vec![],
+            lanes: vec![],
+            retired: vec![],
+            lane_pans: RefCell::new(vec![]),
+            samples,
+            sample_buffers: HashMap::new(),
             state: Cell::new(AudioState::NotPlaying),
+            param_smoothing: audio_config.param_smoothing,
         })
     }
 
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
     /// Set the volume (0.0 to 1.0)
-    pub fn set_volume(&self, volume: f32) {
+    pub fn set_volume(&self, volume: f32) -> GameResult<()> {
         let clamped_volume = volume.clamp(0.0, 1.0);
         let exponential_volume = clamped_volume.sqrt();
-        self.master_gain.gain().set_value(exponential_volume);
+        set_param(
+            &self.backend,
+            &self.master_gain,
+            ParamName::Gain,
+            exponential_volume,
+        )
+    }
+
+    /// Set the pan of a lane (-1.0 left to 1.0 right)
+    pub fn set_lane_pan(&self, lane: usize, pan: f32) -> GameResult<()> {
+        let pan = pan.clamp(-1.0, 1.0);
+        let mut lane_pans = self.lane_pans.borrow_mut();
+        if lane_pans.len() <= lane {
+            lane_pans.resize(lane + 1, 0.0);
+        }
+        if lane_pans[lane] == pan {
+            return Ok(());
+        }
+        lane_pans[lane] = pan;
+        if let Some(game_lane) = self.lanes.get(lane) {
+            smooth_param(
+                &self.backend,
+                &game_lane.panner,
+                ParamName::Pan,
+                pan,
+                self.backend.current_time(),
+                self.param_smoothing,
+            )?;
+        }
+        Ok(())
     }
 
     pub fn is_playing(&self) -> bool {
@@ -78,11 +262,43 @@ impl AudioEngine {
 
     pub fn stop_all(&mut self) -> GameResult<()> {
         self.state.set(AudioState::NotPlaying);
-        for osc in &self.oscillators {
-            osc.borrow_mut().stop_immediate()?;
+        self.voices.stop_all(&self.backend)?;
+        for sequence in &mut self.sequences {
+            sequence.stop(&self.backend)?;
+        }
+        self.sequences = vec![];
+        for lfo in &self.lfos {
+            lfo.borrow_mut().stop(&self.backend)?;
+        }
+        self.lfos = vec![];
+        for lane in &self.lanes {
+            lane.disconnect(&self.backend)?;
+        }
+        self.lanes = vec![];
+        for effect in &self.effects {
+            effect.borrow_mut().stop(&self.backend)?;
         }
-        self.oscillators = vec![];
         self.effects = vec![];
+        for chain in &mut self.retired {
+            chain.stop(&self.backend)?;
+        }
+        self.retired = vec![];
+        Ok(())
+    }
+
+    /// Moves the params and mixes of the playing effects to new values, the effects must be
+    /// in the same order as the ones the graph was interpreted with
+    pub fn update_effects(
+        &self,
+        bpm: u32,
+        audio_effects: &[AudioEffect],
+        mixes: &[EffectMix],
+    ) -> GameResult<()> {
+        for ((effect, audio_effect), mix) in self.effects.iter().zip(audio_effects).zip(mixes) {
+            let mut effect = effect.borrow_mut();
+            effect.update(&self.backend, audio_effect, bpm, self.param_smoothing)?;
+            effect.set_mix(&self.backend, mix, self.param_smoothing)?;
+        }
         Ok(())
     }
 
@@ -93,133 +309,549 @@ impl AudioEngine {
         audio_config: &AudioConfig,
     ) -> GameResult<()> {
         self.state.set(AudioState::Playing);
-        let oscillator = &audio_graph
-            .oscillator()
-            .ok_or(GameError::msg("Invalid graph: no oscillator found"))?;
-        let audio_effects = audio_graph.audio_effects();
+        let when = self.backend.current_time();
+        let destination = self.start_effect_chain(bpm, audio_graph, when)?;
+        self.start_sequence(bpm, audio_graph, audio_config, destination, when)
+    }
+
+    /// Lets `audio_graph` take over from the playing graph at the next boundary of
+    /// `quantization`. The old voices fade out as the new notes come in, and the effect
+    /// chain keeps playing if `keep_effects` is set
+    pub fn swap_graph(
+        &mut self,
+        bpm: u32,
+        audio_graph: &AudioGraph,
+        audio_config: &AudioConfig,
+        quantization: SwapQuantization,
+        keep_effects: bool,
+    ) -> GameResult<()> {
+        let Some(playing) = self.sequences.last() else {
+            return self.interpret_graph(bpm, audio_graph, audio_config);
+        };
+        let destination = playing.destination.clone();
+        let at = quantization.next_boundary(
+            self.backend.current_time(),
+            playing.origin,
+            playing.loop_seconds(),
+            bpm,
+        );
+
+        self.voices
+            .release_after(&self.backend, at, audio_config.swap_crossfade)?;
+        for sequence in &mut self.sequences {
+            sequence.end_at(&self.backend, at)?;
+        }
+
+        let destination = if keep_effects {
+            destination
+        } else {
+            self.retire_effect_chain(at, audio_config.swap_crossfade)?;
+            let destination = self.start_effect_chain(bpm, audio_graph, at)?;
+            if let Some(lane) = self.lanes.last() {
+                lane.fade_in(&self.backend, at, audio_config.swap_crossfade)?;
+            }
+            destination
+        };
+        self.start_sequence(bpm, audio_graph, audio_config, destination, at)
+    }
+
+    /// Fades the playing effect chain out at `at`, it's stopped once the fade is over
+    fn retire_effect_chain(&mut self, at: GameTime, fade: GameTime) -> GameResult<()> {
+        for lane in &self.lanes {
+            lane.fade_out(&self.backend, at, fade)?;
+        }
+        self.retired.push(RetiredChain {
+            lanes: std::mem::take(&mut self.lanes),
+            effects: std::mem::take(&mut self.effects),
+            lfos: std::mem::take(&mut self.lfos),
+            end: at + fade,
+        });
+        Ok(())
+    }
 
-        let when = self.audio_context.current_time();
+    /// Builds the effects, lane and LFOs of `audio_graph`, returning the node its voices
+    /// play into
+    fn start_effect_chain(
+        &mut self,
+        bpm: u32,
+        audio_graph: &AudioGraph,
+        when: GameTime,
+    ) -> GameResult<B::Node> {
+        let audio_effects = audio_graph.audio_effects();
+        let backend = &self.backend;
 
-        let mut effect_nodes: Vec<Box<dyn AudioEffectNode>> = vec![];
-        for effect in &audio_effects {
+        let mut effect_nodes: Vec<Box<dyn AudioEffectNode<B>>> = vec![];
+        for (index, effect) in audio_effects.iter().enumerate() {
             match effect {
                 AudioEffect::Filter(filter_params) => {
-                    let filter = GameFilter::new(&self.audio_context, filter_params)?;
+                    let filter = GameFilter::new(backend, filter_params)?;
                     effect_nodes.push(Box::new(filter));
                 }
                 AudioEffect::Distortion(distortion_params) => {
-                    let distortion = GameDistortion::new(&self.audio_context, distortion_params)?;
+                    let distortion = GameDistortion::new(backend, distortion_params)?;
                     effect_nodes.push(Box::new(distortion));
                 }
                 AudioEffect::Reverb(reverb_params) => {
-                    let reverb = GameReverb::new(&self.audio_context, reverb_params)?;
+                    let random =
+                        Random::from_stream(audio_graph.seed(), RandomStream::Reverb(index));
+                    let reverb = GameReverb::new(backend, reverb_params, random)?;
                     effect_nodes.push(Box::new(reverb));
                 }
+                AudioEffect::Delay(delay_params) => {
+                    let delay = GameDelay::new(backend, delay_params, bpm)?;
+                    effect_nodes.push(Box::new(delay));
+                }
+                AudioEffect::Chorus(params) | AudioEffect::Flanger(params) => {
+                    let modulated_delay = GameModulatedDelay::new(backend, params, bpm, when)?;
+                    effect_nodes.push(Box::new(modulated_delay));
+                }
+                AudioEffect::Phaser(phaser_params) => {
+                    let phaser = GamePhaser::new(backend, phaser_params, bpm, when)?;
+                    effect_nodes.push(Box::new(phaser));
+                }
+                AudioEffect::Compressor(compressor_params) => {
+                    let compressor = GameCompressor::new(backend, compressor_params)?;
+                    effect_nodes.push(Box::new(compressor));
+                }
+                AudioEffect::Pan(pan_params) => {
+                    let pan = GamePan::new(backend, pan_params, bpm, when)?;
+                    effect_nodes.push(Box::new(pan));
+                }
+                AudioEffect::Bitcrusher(bitcrusher_params) => {
+                    let bitcrusher = GameBitcrusher::new(backend, bitcrusher_params)?;
+                    effect_nodes.push(Box::new(bitcrusher));
+                }
             }
         }
 
-        let oscillator_destination: WebAudioNode = if effect_nodes.is_empty() {
-            self.master_gain.clone().into()
+        let effect_nodes = effect_nodes
+            .into_iter()
+            .zip(audio_graph.effect_mixes())
+            .map(|(effect, mix)| GameEffectSlot::new(backend, effect, &mix))
+            .collect::<GameResult<Vec<_>>>()?;
+
+        let lane_pan = self
+            .lane_pans
+            .borrow()
+            .get(self.lanes.len())
+            .copied()
+            .unwrap_or(0.0);
+        let lane = GameLane::new(backend, &self.master_gain, lane_pan)?;
+        let lane_output = lane.output.clone();
+
+        let oscillator_destination = if effect_nodes.is_empty() {
+            lane_output.clone()
         } else {
             for i in 1..effect_nodes.len() {
                 let prev_output = effect_nodes[i - 1].get_output_node();
                 let current_input = effect_nodes[i].get_input_node();
-
-                prev_output
-                    .as_ref()
-                    .connect_with_audio_node(current_input.as_ref())
-                    .map_err(GameError::js("Could not connect effects in chain"))?;
+                backend.connect(prev_output, current_input)?;
             }
 
             if let Some(last_effect) = effect_nodes.last() {
-                last_effect
-                    .get_output_node()
-                    .as_ref()
-                    .connect_with_audio_node(&self.master_gain)
-                    .map_err(GameError::js(
-                        "Could not connect final effect to master gain",
-                    ))?;
+                backend.connect(last_effect.get_output_node(), &lane_output)?;
             }
 
-            effect_nodes[0].get_input_node().as_ref().clone()
+            effect_nodes[0].get_input_node().clone()
         };
 
+        for (lfo, target) in audio_graph.modulations() {
+            let param = match target {
+                // NOTE: vibrato belongs to the voices, the sequence starts it
+                ModulationTarget::OscillatorDetune => continue,
+                ModulationTarget::FilterCutoff(index) => effect_nodes
+                    .get(index)
+                    .and_then(|effect| effect.modulated_param())
+                    .ok_or(GameError::msg("LFO target has no modulated param"))?,
+                ModulationTarget::OutputGain => Param::new(&lane_output, ParamName::Gain),
+            };
+            let range = lfo.modulation_range(target, backend.param_value(&param)?);
+            let game_lfo = GameLfo::new(backend, &lfo, bpm)?;
+            game_lfo.modulate(backend, &param, range)?;
+            game_lfo.start(backend, when)?;
+            self.lfos.push(RefCell::new(game_lfo));
+        }
+
         for effect in effect_nodes {
             self.effects.push(RefCell::new(effect));
         }
+        self.lanes.push(lane);
+        Ok(oscillator_destination)
+    }
+
+    /// Starts playing the notes of `audio_graph` into `destination`, looping from `when` on
+    fn start_sequence(
+        &mut self,
+        bpm: u32,
+        audio_graph: &AudioGraph,
+        audio_config: &AudioConfig,
+        destination: B::Node,
+        when: GameTime,
+    ) -> GameResult<()> {
+        let oscillator = &audio_graph
+            .oscillator()
+            .ok_or(GameError::msg("Invalid graph: no oscillator found"))?;
+        if let Oscillator::Sampler(instrument) = oscillator {
+            for zone in instrument.zones() {
+                self.load_sample_buffer(zone.asset)?;
+            }
+        }
+
+        let backend = &self.backend;
+        let mut vibrato_lfos = vec![];
+        for (lfo, target) in audio_graph.modulations() {
+            if target == ModulationTarget::OscillatorDetune {
+                let game_lfo = GameLfo::new(backend, &lfo, bpm)?;
+                game_lfo.start(backend, when)?;
+                vibrato_lfos.push(game_lfo);
+            }
+        }
+
+        let periodic_wave = match oscillator {
+            Oscillator::Wave(WaveShape::Wavetable(preset)) => {
+                Some(create_periodic_wave(backend, *preset)?)
+            }
+            _ => None,
+        };
+        let noise_buffer = match oscillator {
+            Oscillator::Noise(color) => {
+                Some(create_noise_buffer(backend, *color, audio_graph.seed())?)
+            }
+            Oscillator::Drum(_) => Some(create_noise_buffer(
+                backend,
+                NoiseColor::White,
+                audio_graph.seed(),
+            )?),
+            Oscillator::Wave(_) | Oscillator::Sampler(_) => None,
+        };
 
+        let envelope = audio_graph
+            .envelope()
+            .unwrap_or(Envelope::from_config(audio_config));
         let generator = audio_graph.process_note_generators();
 
-        let loop_length_seconds = generator.loop_length.to_seconds(bpm);
+        let mut sequence = GameSequence {
+            oscillator: *oscillator,
+            envelope,
+            unison: audio_graph.unison(),
+            glide: audio_graph.glide(),
+            periodic_wave,
+            noise_buffer,
+            destination,
+            vibrato_lfos,
+            notes: generator.notes,
+            loop_length: generator.loop_length,
+            bpm,
+            origin: when,
+            scheduled_until: when,
+            end: None,
+            crossfade: audio_config.swap_crossfade,
+            mono: None,
+        };
+        sequence.start_mono(backend)?;
+        self.sequences.push(sequence);
 
-        let loops_to_schedule =
-            (audio_config.max_schedule_ahead / loop_length_seconds).ceil() as i32;
+        Ok(())
+    }
 
-        let mut notes_repeated = vec![];
-        for i in 0..loops_to_schedule {
-            let shifted_notes: Vec<_> = generator
-                .notes
-                .iter()
-                .map(|n: &_| n.shifted(generator.loop_length * i as u32))
-                .collect();
-            for sn in shifted_notes {
-                notes_repeated.push(sn);
+    /// Turns the notes starting within `look_ahead` seconds into voices and lets go of
+    /// the voices that fell silent. Called every frame, so loops play until stopped
+    pub fn schedule_notes(
+        &mut self,
+        look_ahead: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        let now = self.backend.current_time();
+        self.voices.release_finished(&self.backend, now)?;
+        for sequence in &mut self.sequences {
+            if sequence.is_over(now) {
+                sequence.stop(&self.backend)?;
             }
         }
-
-        for note_event in notes_repeated {
-            let freq = note_event.note.to_frequancy();
-            let start = when + note_event.start.to_seconds(bpm);
-            let duration = note_event.duration.to_seconds(bpm);
-
-            let osc = GameOscillator::new(&self.audio_context, oscillator.wave_shape)?;
-            osc.play_with_destination(
-                &oscillator_destination,
-                freq,
-                start,
-                duration,
+        self.sequences.retain(|sequence| !sequence.is_over(now));
+        for chain in &mut self.retired {
+            if chain.end <= now {
+                chain.stop(&self.backend)?;
+            }
+        }
+        self.retired.retain(|chain| chain.end > now);
+
+        for sequence in &mut self.sequences {
+            let voices = sequence.schedule(
+                &self.backend,
+                now + look_ahead,
+                &self.sample_buffers,
+                &self.samples,
                 audio_config,
             )?;
-            self.oscillators.push(RefCell::new(osc));
+            for voice in voices {
+                self.voices.allocate(&self.backend, voice, audio_config)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Playhead of the graph heard right now, a graph waiting to take over doesn't
+    /// count until it does
+    pub fn playhead(&self) -> Option<Playhead> {
+        let now = self.backend.current_time();
+        self.sequences
+            .iter()
+            .rev()
+            .find(|sequence| sequence.origin <= now)
+            .map(|sequence| sequence.playhead(now))
+    }
+
+    /// Waveform and spectrum of the latest samples of the mix
+    pub fn analysis(&self) -> GameResult<Analysis> {
+        let mut waveform = vec![0.0; ANALYSER_SIZE];
+        self.backend.read_waveform(&self.analyser, &mut waveform)?;
+        Ok(Analysis::new(waveform, self.backend.sample_rate()))
+    }
+
+    pub fn voice_stats(&self) -> VoiceStats {
+        let mono_voices = self
+            .sequences
+            .iter()
+            .filter(|sequence| sequence.mono.is_some())
+            .count();
+        VoiceStats {
+            voices: self.voices.voices.len() + mono_voices,
+            steals: self.voices.steals,
         }
+    }
 
+    fn load_sample_buffer(&mut self, asset: SampleAsset) -> GameResult<()> {
+        if self.sample_buffers.contains_key(&asset) {
+            return Ok(());
+        }
+        let sample = self
+            .samples
+            .get(&asset)
+            .ok_or(GameError::msg("Sample asset is not loaded"))?;
+        let buffer = create_sample_buffer(&self.backend, sample)?;
+        self.sample_buffers.insert(asset, buffer);
         Ok(())
     }
 }
 
-pub struct GameOscillator {
-    osc: OscillatorNode,
-    gain: GainNode,
+fn modulate_detune<B: AudioBackend>(
+    backend: &B,
+    voice: &dyn GameVoice<B>,
+    vibrato_lfos: &[GameLfo<B>],
+) -> GameResult<()> {
+    for detune in voice.detune_params() {
+        for lfo in vibrato_lfos {
+            let range = lfo.lfo.modulation_range(
+                ModulationTarget::OscillatorDetune,
+                backend.param_value(&detune)?,
+            );
+            lfo.modulate(backend, &detune, range)?;
+        }
+    }
+    Ok(())
+}
+
+fn create_periodic_wave<B: AudioBackend>(
+    backend: &B,
+    preset: WavetablePreset,
+) -> GameResult<B::Wave> {
+    let imag = preset.harmonics();
+    let real = vec![0.0; imag.len()];
+    backend.create_periodic_wave(&real, &imag)
+}
+
+fn create_noise_buffer<B: AudioBackend>(
+    backend: &B,
+    color: NoiseColor,
+    seed: u64,
+) -> GameResult<B::Buffer> {
+    let sample_rate = backend.sample_rate();
+    let mut random = Random::from_stream(seed, RandomStream::Noise);
+    let noise = color.buffer(sample_rate, &mut random);
+    backend.create_buffer(sample_rate, &[noise])
+}
+
+fn create_sample_buffer<B: AudioBackend>(
+    backend: &B,
+    sample: &DecodedAudio,
+) -> GameResult<B::Buffer> {
+    backend.create_buffer(sample.sample_rate, &sample.channels)
+}
+
+fn create_compressor<B: AudioBackend>(
+    backend: &B,
+    params: &CompressorParameters,
+) -> GameResult<B::Node> {
+    let compressor = backend.create_node(NodeKind::DynamicsCompressor)?;
+    set_param(backend, &compressor, ParamName::Threshold, params.threshold)?;
+    set_param(backend, &compressor, ParamName::Knee, params.knee)?;
+    set_param(backend, &compressor, ParamName::Ratio, params.ratio)?;
+    set_param(backend, &compressor, ParamName::Attack, params.attack)?;
+    set_param(backend, &compressor, ParamName::Release, params.release)?;
+    Ok(compressor)
+}
+
+/// Sets a param right away, before anything is scheduled on it
+fn set_param<B: AudioBackend>(
+    backend: &B,
+    node: &B::Node,
+    name: ParamName,
+    value: f32,
+) -> GameResult<()> {
+    backend.automate(&Param::new(node, name), Automation::Value(value))
+}
+
+/// Moves a param to a new value without the click of an instant jump
+fn smooth_param<B: AudioBackend>(
+    backend: &B,
+    node: &B::Node,
+    name: ParamName,
+    value: f32,
+    when: GameTime,
+    smoothing: f64,
+) -> GameResult<()> {
+    backend.automate(
+        &Param::new(node, name),
+        Automation::Target {
+            value,
+            start: when,
+            time_constant: smoothing,
+        },
+    )
+}
+
+fn schedule_envelope<B: AudioBackend>(
+    backend: &B,
+    gain: &B::Node,
+    envelope: &Envelope,
+    start: GameTime,
+    duration: GameTime,
+    peak: f32,
+) -> GameResult<()> {
+    let gain = Param::new(gain, ParamName::Gain);
+    backend.automate(&gain, Automation::ValueAtTime(0.0, start))?;
+
+    for (offset, level) in envelope.breakpoints(duration).into_iter().skip(1) {
+        backend.automate(&gain, Automation::LinearRamp(level * peak, start + offset))?;
+    }
+    Ok(())
+}
+
+/// Envelope of a note held until further notice, it stays at the sustain level
+/// until a release is scheduled or the voice is stopped
+fn schedule_held_envelope<B: AudioBackend>(
+    backend: &B,
+    gain: &B::Node,
+    envelope: &Envelope,
+    start: GameTime,
+    peak: f32,
+) -> GameResult<()> {
+    let gain = Param::new(gain, ParamName::Gain);
+    backend.automate(&gain, Automation::ValueAtTime(0.0, start))?;
+
+    let mut breakpoints = envelope.breakpoints(envelope.attack + envelope.decay);
+    breakpoints.pop();
+    for (offset, level) in breakpoints.into_iter().skip(1) {
+        backend.automate(&gain, Automation::LinearRamp(level * peak, start + offset))?;
+    }
+    Ok(())
+}
+
+/// Drops whatever was scheduled on the gain from `when` on and fades it out over `fade`
+/// instead
+fn fade_out<B: AudioBackend>(
+    backend: &B,
+    gain: &B::Node,
+    when: GameTime,
+    fade: GameTime,
+) -> GameResult<()> {
+    let gain = Param::new(gain, ParamName::Gain);
+    backend.automate(&gain, Automation::Cancel(when))?;
+    backend.automate(
+        &gain,
+        Automation::Target {
+            value: 0.0,
+            start: when,
+            time_constant: fade / 5.0,
+        },
+    )
+}
+
+fn set_wave_shape<B: AudioBackend>(
+    backend: &B,
+    osc: &B::Node,
+    wave_shape: WaveShape,
+    periodic_wave: Option<&B::Wave>,
+) -> GameResult<()> {
+    let wave = match wave_shape {
+        WaveShape::Sine | WaveShape::Fm(_) => OscillatorType::Sine,
+        WaveShape::Square => OscillatorType::Square,
+        WaveShape::Sawtooth => OscillatorType::Sawtooth,
+        WaveShape::Triangle => OscillatorType::Triangle,
+        WaveShape::Wavetable(_) => {
+            let periodic_wave =
+                periodic_wave.ok_or(GameError::msg("Wavetable oscillator has no periodic wave"))?;
+            return backend.set_periodic_wave(osc, periodic_wave);
+        }
+    };
+    backend.set_oscillator_type(osc, wave)
+}
+
+pub struct GameOscillator<B: AudioBackend> {
+    osc: B::Node,
+    gain: B::Node,
     wave_shape: WaveShape,
+    periodic_wave: Option<B::Wave>,
+    modulator: Option<GameFmModulator<B>>,
+    envelope: Envelope,
     is_stopped: bool,
 }
 
-impl GameOscillator {
-    fn new(audio_context: &AudioContext, wave_shape: WaveShape) -> GameResult<GameOscillator> {
-        let osc = audio_context
-            .create_oscillator()
-            .map_err(GameError::js("Could not create oscillator"))?;
-        let gain = audio_context
-            .create_gain()
-            .map_err(GameError::js("Could not create gain node"))?;
+impl<B: AudioBackend> GameOscillator<B> {
+    fn new(
+        backend: &B,
+        wave_shape: WaveShape,
+        periodic_wave: Option<B::Wave>,
+        envelope: Envelope,
+    ) -> GameResult<GameOscillator<B>> {
+        let osc = backend.create_node(NodeKind::Oscillator)?;
+        let gain = backend.create_node(NodeKind::Gain)?;
+        let modulator = match wave_shape {
+            WaveShape::Fm(preset) => Some(GameFmModulator::new(backend, preset)?),
+            _ => None,
+        };
         Ok(GameOscillator {
             osc,
             gain,
             wave_shape,
+            periodic_wave,
+            modulator,
+            envelope,
             is_stopped: false,
         })
     }
 
+    fn apply_wave_shape(&self, backend: &B) -> GameResult<()> {
+        set_wave_shape(
+            backend,
+            &self.osc,
+            self.wave_shape,
+            self.periodic_wave.as_ref(),
+        )
+    }
+
     fn play(
         &self,
-        audio_context: &AudioContext,
+        backend: &B,
         frequency: f32,
         start: GameTime,
         duration: GameTime,
         audio_config: &AudioConfig,
     ) -> GameResult<()> {
         self.play_with_destination(
-            &audio_context.destination().into(),
+            backend,
+            &backend.destination(),
             frequency,
             start,
             duration,
@@ -229,336 +861,472 @@ impl GameOscillator {
 
     fn play_with_destination(
         &self,
-        destination: &WebAudioNode,
-        frequency: f32,
-        start: GameTime,
-        duration: GameTime,
-        audio_config: &AudioConfig,
-    ) -> GameResult<()> {
-        let wave = match self.wave_shape {
-            WaveShape::Sine => OscillatorType::Sine,
-            WaveShape::Square => OscillatorType::Square,
-        };
-        self.osc.set_type(wave);
-        self.osc.frequency().set_value(frequency);
-
-        self.osc
-            .connect_with_audio_node(&self.gain)
-            .map_err(GameError::js("Could not connect oscillator to gain"))?;
-        self.gain
-            .connect_with_audio_node(destination)
-            .map_err(GameError::js("Could not connect gain to destination"))?;
-
-        let start_time = start as f64;
-        let end_time = start_time + duration as f64;
-
-        let attack_time = audio_config.attack_time;
-        let release_time = audio_config.release_time;
-
-        self.gain
-            .gain()
-            .set_value_at_time(0.0, start_time)
-            .map_err(GameError::js("Could not set initial gain"))?;
-
-        self.gain
-            .gain()
-            .linear_ramp_to_value_at_time(audio_config.output_gain, start_time + attack_time)
-            .map_err(GameError::js("Could not schedule attack ramp"))?;
-
-        let release_start = end_time - release_time;
-        self.gain
-            .gain()
-            .set_value_at_time(audio_config.output_gain, release_start)
-            .map_err(GameError::js("Could not set release start gain"))?;
-        self.gain
-            .gain()
-            .linear_ramp_to_value_at_time(0.0, end_time)
-            .map_err(GameError::js("Could not schedule release ramp"))?;
-
-        self.osc
-            .start_with_when(start_time)
-            .map_err(GameError::js("Could not start audio"))?;
-        self.osc
-            .stop_with_when(end_time)
-            .map_err(GameError::js("Couldn't schedule stop"))?;
-        Ok(())
-    }
-
-    fn play_looping(
-        &self,
-        destination: &WebAudioNode,
+        backend: &B,
+        destination: &B::Node,
         frequency: f32,
         start: GameTime,
         duration: GameTime,
-        loop_period: GameTime,
         audio_config: &AudioConfig,
     ) -> GameResult<()> {
-        let wave = match self.wave_shape {
-            WaveShape::Sine => OscillatorType::Sine,
-            WaveShape::Square => OscillatorType::Square,
-        };
-        self.osc.set_type(wave);
-        self.osc.frequency().set_value(frequency);
-
-        self.osc
-            .connect_with_audio_node(&self.gain)
-            .map_err(GameError::js("Could not connect oscillator to gain"))?;
-        self.gain
-            .connect_with_audio_node(destination)
-            .map_err(GameError::js("Could not connect gain to destination"))?;
-
-        let start_time = start as f64;
-        let note_duration = duration as f64;
-        let loop_period_secs = loop_period as f64;
-
-        let attack_time = audio_config.attack_time;
-        let release_time = audio_config.release_time;
-
-        self.osc
-            .start_with_when(start_time)
-            .map_err(GameError::js("Could not start audio"))?;
-
-        let loops_to_schedule = (audio_config.max_schedule_ahead / loop_period_secs).ceil() as i32;
-
-        for i in 0..loops_to_schedule {
-            let loop_start = start_time + (loop_period_secs * i as f64);
-            let note_start = loop_start;
-            let note_end = note_start + note_duration;
+        self.apply_wave_shape(backend)?;
+        set_param(backend, &self.osc, ParamName::Frequency, frequency)?;
+        if let Some(modulator) = &self.modulator {
+            modulator.connect_to_carrier(backend, &self.osc, frequency, start)?;
+        }
 
-            self.gain
-                .gain()
-                .set_value_at_time(0.0, note_start)
-                .map_err(GameError::js("Could not set initial gain"))?;
+        backend.connect(&self.osc, &self.gain)?;
+        backend.connect(&self.gain, destination)?;
 
-            self.gain
-                .gain()
-                .linear_ramp_to_value_at_time(audio_config.output_gain, note_start + attack_time)
-                .map_err(GameError::js("Could not schedule attack ramp"))?;
+        self.schedule_envelope(backend, start, duration, audio_config)?;
 
-            let release_start = note_end - release_time;
-            self.gain
-                .gain()
-                .set_value_at_time(audio_config.output_gain, release_start)
-                .map_err(GameError::js("Could not set release start gain"))?;
-            self.gain
-                .gain()
-                .linear_ramp_to_value_at_time(0.0, note_end)
-                .map_err(GameError::js("Could not schedule release ramp"))?;
+        backend.start(&self.osc, start)?;
+        backend.stop(&self.osc, Some(start + self.envelope.tail_end(duration)))?;
+        if let Some(modulator) = &self.modulator {
+            modulator.stop_at(backend, start + self.envelope.tail_end(duration))?;
         }
-
         Ok(())
     }
 
-    fn stop_immediate(&mut self) -> GameResult<()> {
-        self.osc
-            .stop()
-            .map_err(GameError::js("Could not stop oscillator"))?;
-        self.is_stopped = true;
-        Ok(())
+    fn schedule_envelope(
+        &self,
+        backend: &B,
+        start: GameTime,
+        duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        schedule_envelope(
+            backend,
+            &self.gain,
+            &self.envelope,
+            start,
+            duration,
+            audio_config.output_gain,
+        )
     }
 
-    fn stop_at(&mut self, when: GameTime) -> GameResult<()> {
-        self.osc
-            .stop_with_when(when as f64)
-            .map_err(GameError::js("Could not schedule stop"))?;
+    fn stop_at(&mut self, backend: &B, when: GameTime) -> GameResult<()> {
+        backend.stop(&self.osc, Some(when))?;
+        if let Some(modulator) = &self.modulator {
+            modulator.stop_at(backend, when)?;
+        }
         self.is_stopped = true;
         Ok(())
     }
 }
 
-pub struct GameFilter {
-    filter: BiquadFilterNode,
-    parameters: FilterParameters,
+/// Several detuned oscillators per note, each panned to its own place in the stereo field
+pub struct GameUnison<B: AudioBackend> {
+    voices: Vec<GameOscillator<B>>,
+    panners: Vec<B::Node>,
+    output: B::Node,
 }
 
-impl GameFilter {
-    fn new(audio_context: &AudioContext, params: &FilterParameters) -> GameResult<GameFilter> {
-        let filter = audio_context
-            .create_biquad_filter()
-            .map_err(GameError::js("Could not create biquad filter"))?;
-
-        filter.set_type(params.filter_type.to_biquad_type());
-        filter.frequency().set_value(params.frequency);
-        filter.q().set_value(params.q);
+impl<B: AudioBackend> GameUnison<B> {
+    fn new(
+        backend: &B,
+        wave_shape: WaveShape,
+        periodic_wave: Option<B::Wave>,
+        envelope: Envelope,
+        unison: &Unison,
+    ) -> GameResult<GameUnison<B>> {
+        let output = backend.create_node(NodeKind::Gain)?;
+        set_param(backend, &output, ParamName::Gain, unison.voice_gain())?;
+
+        let mut voices = vec![];
+        let mut panners = vec![];
+        for (detune, pan) in unison.voice_offsets() {
+            let voice = GameOscillator::new(backend, wave_shape, periodic_wave.clone(), envelope)?;
+            set_param(backend, &voice.osc, ParamName::Detune, detune)?;
+
+            let panner = backend.create_node(NodeKind::StereoPanner)?;
+            set_param(backend, &panner, ParamName::Pan, pan)?;
+            backend.connect(&panner, &output)?;
+
+            voices.push(voice);
+            panners.push(panner);
+        }
 
-        Ok(GameFilter {
-            filter,
-            parameters: params.clone(),
+        Ok(GameUnison {
+            voices,
+            panners,
+            output,
         })
     }
+}
 
-    fn get_node(&self) -> &BiquadFilterNode {
-        &self.filter
+/// Continuous noise played through the lane envelope
+pub struct GameNoise<B: AudioBackend> {
+    source: B::Node,
+    gain: B::Node,
+    envelope: Envelope,
+}
+
+impl<B: AudioBackend> GameNoise<B> {
+    fn new(backend: &B, buffer: &B::Buffer, envelope: Envelope) -> GameResult<GameNoise<B>> {
+        let source = backend.create_node(NodeKind::BufferSource)?;
+        let gain = backend.create_node(NodeKind::Gain)?;
+        backend.set_buffer(&source, buffer)?;
+        backend.set_loop(&source, None)?;
+        Ok(GameNoise {
+            source,
+            gain,
+            envelope,
+        })
     }
 }
 
-pub struct GameDistortion {
-    input_gain: GainNode,
-    wave_shaper: WaveShaperNode,
-    output_gain: GainNode,
-    parameters: DistortionParameters,
+/// Sample repitched to the note through its playback rate
+pub struct GameSampler<B: AudioBackend> {
+    source: B::Node,
+    gain: B::Node,
+    envelope: Envelope,
 }
 
-impl GameDistortion {
+impl<B: AudioBackend> GameSampler<B> {
     fn new(
-        audio_context: &AudioContext,
-        params: &DistortionParameters,
-    ) -> GameResult<GameDistortion> {
-        let input_gain = audio_context.create_gain().map_err(GameError::js(
-            "Could not create input gain node for distortion",
-        ))?;
-
-        let wave_shaper = audio_context
-            .create_wave_shaper()
-            .map_err(GameError::js("Could not create wave shaper"))?;
+        backend: &B,
+        buffer: &B::Buffer,
+        sample_rate: f32,
+        zone: &SampleZone,
+        playback_rate: f32,
+        envelope: Envelope,
+    ) -> GameResult<GameSampler<B>> {
+        let source = backend.create_node(NodeKind::BufferSource)?;
+        let gain = backend.create_node(NodeKind::Gain)?;
+        backend.set_buffer(&source, buffer)?;
+        set_param(backend, &source, ParamName::PlaybackRate, playback_rate)?;
+        if let Some((loop_start, loop_end)) = zone.loop_points {
+            let sample_rate = sample_rate as f64;
+            backend.set_loop(
+                &source,
+                Some((
+                    loop_start as f64 / sample_rate,
+                    loop_end as f64 / sample_rate,
+                )),
+            )?;
+        }
+        Ok(GameSampler {
+            source,
+            gain,
+            envelope,
+        })
+    }
+}
 
-        let output_gain = audio_context.create_gain().map_err(GameError::js(
-            "Could not create output gain node for distortion",
-        ))?;
+/// Drum hit, each layer owns its source, optional filter and envelope gain
+pub struct GameDrum<B: AudioBackend> {
+    layers: Vec<GameDrumLayer<B>>,
+}
 
-        input_gain.gain().set_value(1.0 + params.amount * 10.0);
+struct GameDrumLayer<B: AudioBackend> {
+    source: B::Node,
+    /// Frequency of tone layers, swept down at the start of the hit
+    frequency: Option<Param<B::Node>>,
+    filter: Option<B::Node>,
+    gain: B::Node,
+    layer: DrumLayer,
+}
 
-        let samples = 44100;
-        let curve: Vec<f32> = (0..samples)
-            .map(|i| {
-                let x = (i as f32 / (samples - 1) as f32) * 2.0 - 1.0;
-
-                match params.curve_type {
-                    DistortionCurve::SoftClip => x.tanh() * (1.0 + params.amount * 0.5),
-                    DistortionCurve::HardClip => {
-                        let k = params.amount * 100.0;
-                        let deg = std::f32::consts::PI / 180.0;
-
-                        if x.abs() < 0.001 {
-                            x
-                        } else {
-                            let distorted =
-                                ((3.0 + k) * x * 20.0 * deg) / (std::f32::consts::PI + k * x.abs());
-
-                            distorted.max(-1.0).min(1.0)
-                        }
-                    }
+impl<B: AudioBackend> GameDrum<B> {
+    fn new(backend: &B, voice: DrumVoice, noise_buffer: &B::Buffer) -> GameResult<GameDrum<B>> {
+        let mut layers = vec![];
+        for layer in voice.layers() {
+            let (source, frequency) = match layer.source {
+                DrumSource::Tone { shape, .. } => {
+                    let osc = backend.create_node(NodeKind::Oscillator)?;
+                    set_wave_shape(backend, &osc, shape, None)?;
+                    let frequency = Param::new(&osc, ParamName::Frequency);
+                    (osc, Some(frequency))
                 }
-            })
-            .collect();
-
-        wave_shaper.set_curve_opt_f32_array(Some(&Float32Array::from(curve.as_slice())));
-
-        wave_shaper.set_oversample(OverSampleType::N2x);
+                DrumSource::Noise => {
+                    let source = backend.create_node(NodeKind::BufferSource)?;
+                    backend.set_buffer(&source, noise_buffer)?;
+                    backend.set_loop(&source, None)?;
+                    (source, None)
+                }
+            };
+            let filter = match &layer.filter {
+                Some(params) => Some(GameFilter::new(backend, params)?.filter),
+                None => None,
+            };
+            let gain = backend.create_node(NodeKind::Gain)?;
+            layers.push(GameDrumLayer {
+                source,
+                frequency,
+                filter,
+                gain,
+                layer,
+            });
+        }
+        Ok(GameDrum { layers })
+    }
+}
 
-        let compensation_gain = match params.curve_type {
-            DistortionCurve::SoftClip => 1.0 / (1.0 + params.amount * 0.5),
-            DistortionCurve::HardClip => 0.3 / (1.0 + params.amount),
-        };
-        output_gain.gain().set_value(compensation_gain);
+/// Modulator operator of an FM voice, its output scaled by the modulation index
+/// drives the carrier frequency
+pub struct GameFmModulator<B: AudioBackend> {
+    osc: B::Node,
+    index: B::Node,
+    preset: FmPreset,
+}
 
-        input_gain
-            .connect_with_audio_node(&wave_shaper)
-            .map_err(GameError::js("Could not connect input gain to wave shaper"))?;
+impl<B: AudioBackend> GameFmModulator<B> {
+    fn new(backend: &B, preset: FmPreset) -> GameResult<GameFmModulator<B>> {
+        let osc = backend.create_node(NodeKind::Oscillator)?;
+        let index = backend.create_node(NodeKind::Gain)?;
+        backend.set_oscillator_type(&osc, OscillatorType::Sine)?;
+        backend.connect(&osc, &index)?;
+        Ok(GameFmModulator { osc, index, preset })
+    }
 
-        wave_shaper
-            .connect_with_audio_node(&output_gain)
-            .map_err(GameError::js(
-                "Could not connect wave shaper to output gain",
-            ))?;
+    fn connect_to_carrier(
+        &self,
+        backend: &B,
+        carrier: &B::Node,
+        frequency: f32,
+        start: GameTime,
+    ) -> GameResult<()> {
+        let (modulator_frequency, deviation) = self.preset.modulator(frequency);
+        set_param(
+            backend,
+            &self.osc,
+            ParamName::Frequency,
+            modulator_frequency,
+        )?;
+        set_param(backend, &self.index, ParamName::Gain, deviation)?;
+        backend.connect_param(&self.index, &Param::new(carrier, ParamName::Frequency))?;
+        backend.start(&self.osc, start)
+    }
 
-        Ok(GameDistortion {
-            input_gain,
-            wave_shaper,
-            output_gain,
-            parameters: params.clone(),
-        })
+    /// Keeps the modulator frequency and index in step with a carrier sliding `from` `to`
+    fn glide(
+        &self,
+        backend: &B,
+        from: f32,
+        to: f32,
+        start: GameTime,
+        end: GameTime,
+    ) -> GameResult<()> {
+        let (from_frequency, from_deviation) = self.preset.modulator(from);
+        let (to_frequency, to_deviation) = self.preset.modulator(to);
+        for (param, from, to) in [
+            (
+                Param::new(&self.osc, ParamName::Frequency),
+                from_frequency,
+                to_frequency,
+            ),
+            (
+                Param::new(&self.index, ParamName::Gain),
+                from_deviation,
+                to_deviation,
+            ),
+        ] {
+            backend.automate(&param, Automation::ValueAtTime(from, start))?;
+            backend.automate(&param, Automation::ExponentialRamp(to, end))?;
+        }
+        Ok(())
     }
 
-    fn get_input_node(&self) -> &GainNode {
-        &self.input_gain
+    fn stop(&self, backend: &B) -> GameResult<()> {
+        backend.stop(&self.osc, None)
     }
 
-    fn get_output_node(&self) -> &GainNode {
-        &self.output_gain
+    fn stop_at(&self, backend: &B, when: GameTime) -> GameResult<()> {
+        backend.stop(&self.osc, Some(when))
     }
 }
 
-pub struct GameReverb {
-    input_node: GainNode,
-    output_node: GainNode,
-    dry_gain: GainNode,
-    wet_gain: GainNode,
-    convolver: ConvolverNode,
-    parameters: ReverbParameters,
+/// Oscillator running for the whole playback, its output scaled by `depth` is added
+/// to the params it modulates
+pub struct GameLfo<B: AudioBackend> {
+    osc: B::Node,
+    depth: B::Node,
+    lfo: Lfo,
 }
 
-impl GameReverb {
-    fn new(audio_context: &AudioContext, params: &ReverbParameters) -> GameResult<GameReverb> {
-        let input_node = audio_context
-            .create_gain()
-            .map_err(GameError::js("Could not create input gain for reverb"))?;
-        let output_node = audio_context
-            .create_gain()
-            .map_err(GameError::js("Could not create output gain for reverb"))?;
-        let dry_gain = audio_context
-            .create_gain()
-            .map_err(GameError::js("Could not create dry gain for reverb"))?;
-        let wet_gain = audio_context
-            .create_gain()
-            .map_err(GameError::js("Could not create wet gain for reverb"))?;
-        let convolver = audio_context
-            .create_convolver()
-            .map_err(GameError::js("Could not create convolver node"))?;
-
-        let sample_rate = audio_context.sample_rate();
-        let length = (sample_rate * params.decay_time) as usize;
-        let mut impulse_data = vec![0.0f32; length];
-
-        let pre_delay_samples = (sample_rate * 0.02) as usize;
-        let decay_exponent = 2.0;
+impl<B: AudioBackend> GameLfo<B> {
+    fn new(backend: &B, lfo: &Lfo, bpm: u32) -> GameResult<GameLfo<B>> {
+        let osc = backend.create_node(NodeKind::Oscillator)?;
+        let depth = backend.create_node(NodeKind::Gain)?;
 
-        for i in 0..length {
-            let mut sample_value = 0.0f32;
+        let periodic_wave = match lfo.shape {
+            WaveShape::Wavetable(preset) => Some(create_periodic_wave(backend, preset)?),
+            _ => None,
+        };
+        set_wave_shape(backend, &osc, lfo.shape, periodic_wave.as_ref())?;
+        set_param(backend, &osc, ParamName::Frequency, lfo.frequency(bpm))?;
 
-            if i >= pre_delay_samples {
-                let normalized_time =
-                    (i - pre_delay_samples) as f32 / (length - pre_delay_samples) as f32;
-                let decay = (1.0 - normalized_time).powf(decay_exponent);
-                sample_value = (random() * 2.0 - 1.0) * decay;
-            }
+        backend.connect(&osc, &depth)?;
 
-            impulse_data[i] = sample_value;
-        }
+        Ok(GameLfo {
+            osc,
+            depth,
+            lfo: *lfo,
+        })
+    }
 
-        let impulse_buffer = audio_context
-            .create_buffer(1, length as u32, sample_rate)
-            .map_err(GameError::js("Could not create audio buffer for impulse"))?;
+    fn modulate(
+        &self,
+        backend: &B,
+        param: &Param<B::Node>,
+        (base, amplitude): (f32, f32),
+    ) -> GameResult<()> {
+        backend.automate(param, Automation::Value(base))?;
+        set_param(backend, &self.depth, ParamName::Gain, amplitude)?;
+        backend.connect_param(&self.depth, param)
+    }
 
-        impulse_buffer
-            .copy_to_channel(&impulse_data, 0)
-            .map_err(GameError::js("Could not copy impulse data to buffer"))?;
+    fn start(&self, backend: &B, when: GameTime) -> GameResult<()> {
+        backend.start(&self.osc, when)
+    }
 
-        convolver.set_buffer(Some(&impulse_buffer));
+    fn set_frequency(
+        &self,
+        backend: &B,
+        frequency: f32,
+        when: GameTime,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        smooth_param(
+            backend,
+            &self.osc,
+            ParamName::Frequency,
+            frequency,
+            when,
+            smoothing,
+        )
+    }
+
+    fn set_amplitude(
+        &self,
+        backend: &B,
+        amplitude: f32,
+        when: GameTime,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        smooth_param(
+            backend,
+            &self.depth,
+            ParamName::Gain,
+            amplitude,
+            when,
+            smoothing,
+        )
+    }
+
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        backend.stop(&self.osc, None)?;
+        backend.disconnect(&self.depth)
+    }
+}
+
+pub struct GameFilter<B: AudioBackend> {
+    filter: B::Node,
+    parameters: FilterParameters,
+}
+
+impl<B: AudioBackend> GameFilter<B> {
+    fn new(backend: &B, params: &FilterParameters) -> GameResult<GameFilter<B>> {
+        let filter = backend.create_node(NodeKind::BiquadFilter)?;
+
+        backend.set_filter_type(&filter, params.filter_type)?;
+        set_param(backend, &filter, ParamName::Frequency, params.frequency)?;
+        set_param(backend, &filter, ParamName::Q, params.q)?;
+        set_param(backend, &filter, ParamName::Gain, params.gain)?;
+
+        Ok(GameFilter {
+            filter,
+            parameters: params.clone(),
+        })
+    }
+
+    fn get_node(&self) -> &B::Node {
+        &self.filter
+    }
+}
+
+pub struct GameDistortion<B: AudioBackend> {
+    input_gain: B::Node,
+    wave_shaper: B::Node,
+    output_gain: B::Node,
+    parameters: DistortionParameters,
+}
+
+impl<B: AudioBackend> GameDistortion<B> {
+    fn new(backend: &B, params: &DistortionParameters) -> GameResult<GameDistortion<B>> {
+        let input_gain = backend.create_node(NodeKind::Gain)?;
+        let wave_shaper = backend.create_node(NodeKind::WaveShaper)?;
+        let output_gain = backend.create_node(NodeKind::Gain)?;
+
+        set_param(backend, &input_gain, ParamName::Gain, params.input_gain())?;
+
+        let curve = params.curve(DISTORTION_CURVE_SIZE);
+        backend.set_curve(&wave_shaper, &curve)?;
+
+        backend.set_oversample(&wave_shaper, Oversample::TwoTimes)?;
+
+        set_param(
+            backend,
+            &output_gain,
+            ParamName::Gain,
+            params.compensation_gain(),
+        )?;
+
+        backend.connect(&input_gain, &wave_shaper)?;
+        backend.connect(&wave_shaper, &output_gain)?;
+
+        Ok(GameDistortion {
+            input_gain,
+            wave_shaper,
+            output_gain,
+            parameters: params.clone(),
+        })
+    }
+
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_gain
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_gain
+    }
+}
+
+pub struct GameReverb<B: AudioBackend> {
+    input_node: B::Node,
+    output_node: B::Node,
+    dry_gain: B::Node,
+    wet_gain: B::Node,
+    convolver: B::Node,
+    parameters: ReverbParameters,
+    /// Regenerating the impulse response starts over from the same numbers
+    random: Random,
+}
+
+impl<B: AudioBackend> GameReverb<B> {
+    fn new(backend: &B, params: &ReverbParameters, random: Random) -> GameResult<GameReverb<B>> {
+        let input_node = backend.create_node(NodeKind::Gain)?;
+        let output_node = backend.create_node(NodeKind::Gain)?;
+        let dry_gain = backend.create_node(NodeKind::Gain)?;
+        let wet_gain = backend.create_node(NodeKind::Gain)?;
+        let convolver = backend.create_node(NodeKind::Convolver)?;
+
+        let impulse_buffer = create_impulse_buffer(backend, params, random.clone())?;
+        backend.set_buffer(&convolver, &impulse_buffer)?;
 
         // Set gain levels
-        dry_gain.gain().set_value(params.dry_level);
-        wet_gain.gain().set_value(params.wet_level);
+        set_param(backend, &dry_gain, ParamName::Gain, params.dry_level)?;
+        set_param(backend, &wet_gain, ParamName::Gain, params.wet_level)?;
 
         // Connect nodes in parallel
         // Dry path: input -> dry_gain -> output
-        input_node
-            .connect_with_audio_node(&dry_gain)
-            .map_err(GameError::js("Could not connect input to dry gain"))?;
-        dry_gain
-            .connect_with_audio_node(&output_node)
-            .map_err(GameError::js("Could not connect dry gain to output"))?;
+        backend.connect(&input_node, &dry_gain)?;
+        backend.connect(&dry_gain, &output_node)?;
 
         // Wet path: input -> convolver -> wet_gain -> output
-        input_node
-            .connect_with_audio_node(&convolver)
-            .map_err(GameError::js("Could not connect input to convolver"))?;
-        convolver
-            .connect_with_audio_node(&wet_gain)
-            .map_err(GameError::js("Could not connect convolver to wet gain"))?;
-        wet_gain
-            .connect_with_audio_node(&output_node)
-            .map_err(GameError::js("Could not connect wet gain to output"))?;
+        backend.connect(&input_node, &convolver)?;
+        backend.connect(&convolver, &wet_gain)?;
+        backend.connect(&wet_gain, &output_node)?;
 
         Ok(GameReverb {
             input_node,
@@ -567,41 +1335,2287 @@ impl GameReverb {
             wet_gain,
             convolver,
             parameters: params.clone(),
+            random,
         })
     }
 }
 
-trait AudioEffectNode {
-    fn get_input_node(&self) -> &dyn AsRef<WebAudioNode>;
-    fn get_output_node(&self) -> &dyn AsRef<WebAudioNode>;
+fn create_impulse_buffer<B: AudioBackend>(
+    backend: &B,
+    params: &ReverbParameters,
+    mut random: Random,
+) -> GameResult<B::Buffer> {
+    let sample_rate = backend.sample_rate();
+    let impulse_data = params.impulse_response(sample_rate, &mut random);
+    backend.create_buffer(sample_rate, &[impulse_data])
 }
 
-impl AudioEffectNode for GameFilter {
-    fn get_input_node(&self) -> &dyn AsRef<WebAudioNode> {
-        &self.filter
+/// Echo built from delay lines in a feedback loop, every repeat goes through a damping
+/// lowpass. Ping-pong delays alternate the echoes between a left and a right delay line
+pub struct GameDelay<B: AudioBackend> {
+    input_node: B::Node,
+    output_node: B::Node,
+    dry_gain: B::Node,
+    wet_gain: B::Node,
+    delays: Vec<B::Node>,
+    panners: Vec<B::Node>,
+    damping: B::Node,
+    feedback: B::Node,
+    max_delay_time: f32,
+    parameters: DelayParameters,
+}
+
+impl<B: AudioBackend> GameDelay<B> {
+    fn new(backend: &B, params: &DelayParameters, bpm: u32) -> GameResult<GameDelay<B>> {
+        let input_node = backend.create_node(NodeKind::Gain)?;
+        let output_node = backend.create_node(NodeKind::Gain)?;
+        let dry_gain = backend.create_node(NodeKind::Gain)?;
+        let wet_gain = backend.create_node(NodeKind::Gain)?;
+        let damping = GameFilter::new(backend, &params.damping_filter())?.filter;
+        let feedback = backend.create_node(NodeKind::Gain)?;
+
+        // NOTE: the delay lines are sized for the slowest tempo so that a tempo or
+        // division change never needs a new line
+        let max_delay_time = params.max_delay_seconds(bpm);
+        let delay_time = params.delay_seconds(bpm).min(max_delay_time);
+        let channels = if params.ping_pong { 2 } else { 1 };
+        let mut delays = vec![];
+        for _ in 0..channels {
+            let delay = backend.create_node(NodeKind::Delay {
+                max_delay_time: max_delay_time as f64,
+            })?;
+            set_param(backend, &delay, ParamName::DelayTime, delay_time)?;
+            delays.push(delay);
+        }
+
+        set_param(backend, &dry_gain, ParamName::Gain, params.dry_level)?;
+        set_param(backend, &wet_gain, ParamName::Gain, params.wet_level)?;
+        set_param(backend, &feedback, ParamName::Gain, params.feedback_gain())?;
+
+        // Dry path: input -> dry_gain -> output
+        backend.connect(&input_node, &dry_gain)?;
+        backend.connect(&dry_gain, &output_node)?;
+
+        // Feedback loop: input -> delays in series -> damping -> feedback -> first delay
+        backend.connect(&input_node, &delays[0])?;
+        for pair in delays.windows(2) {
+            backend.connect(&pair[0], &pair[1])?;
+        }
+        backend.connect(&delays[delays.len() - 1], &damping)?;
+        backend.connect(&damping, &feedback)?;
+        backend.connect(&feedback, &delays[0])?;
+
+        // Wet path: delay -> wet_gain -> output, ping-pong delays are panned hard left and right
+        let mut panners = vec![];
+        if params.ping_pong {
+            for (delay, pan) in delays.iter().zip([-1.0, 1.0]) {
+                let panner = backend.create_node(NodeKind::StereoPanner)?;
+                set_param(backend, &panner, ParamName::Pan, pan)?;
+                backend.connect(delay, &panner)?;
+                backend.connect(&panner, &wet_gain)?;
+                panners.push(panner);
+            }
+        } else {
+            backend.connect(&delays[0], &wet_gain)?;
+        }
+        backend.connect(&wet_gain, &output_node)?;
+
+        Ok(GameDelay {
+            input_node,
+            output_node,
+            dry_gain,
+            wet_gain,
+            delays,
+            panners,
+            damping,
+            feedback,
+            max_delay_time,
+            parameters: params.clone(),
+        })
     }
+}
 
-    fn get_output_node(&self) -> &dyn AsRef<WebAudioNode> {
-        &self.filter
+/// Chorus or flanger: delay lines whose delay time is swept by their own LFO
+pub struct GameModulatedDelay<B: AudioBackend> {
+    input_node: B::Node,
+    output_node: B::Node,
+    dry_gain: B::Node,
+    wet_gain: B::Node,
+    delays: Vec<B::Node>,
+    feedbacks: Vec<B::Node>,
+    panners: Vec<B::Node>,
+    lfos: Vec<GameLfo<B>>,
+    parameters: ModulatedDelayParameters,
+}
+
+impl<B: AudioBackend> GameModulatedDelay<B> {
+    fn new(
+        backend: &B,
+        params: &ModulatedDelayParameters,
+        bpm: u32,
+        when: GameTime,
+    ) -> GameResult<GameModulatedDelay<B>> {
+        let input_node = backend.create_node(NodeKind::Gain)?;
+        let output_node = backend.create_node(NodeKind::Gain)?;
+        let dry_gain = backend.create_node(NodeKind::Gain)?;
+        let wet_gain = backend.create_node(NodeKind::Gain)?;
+
+        set_param(backend, &dry_gain, ParamName::Gain, params.dry_level)?;
+        set_param(backend, &wet_gain, ParamName::Gain, params.wet_gain())?;
+
+        backend.connect(&input_node, &dry_gain)?;
+        backend.connect(&dry_gain, &output_node)?;
+        backend.connect(&wet_gain, &output_node)?;
+
+        let mut delays = vec![];
+        let mut feedbacks = vec![];
+        let mut panners = vec![];
+        let mut lfos = vec![];
+        for (lfo, pan) in params.voice_lfos().iter().zip(params.voice_pans()) {
+            let delay = backend.create_node(NodeKind::Delay {
+                max_delay_time: params.max_delay() as f64,
+            })?;
+            let feedback = backend.create_node(NodeKind::Gain)?;
+            let panner = backend.create_node(NodeKind::StereoPanner)?;
+            set_param(backend, &feedback, ParamName::Gain, params.feedback_gain())?;
+            set_param(backend, &panner, ParamName::Pan, pan)?;
+
+            // input -> delay -> panner -> wet_gain, delay -> feedback -> delay
+            backend.connect(&input_node, &delay)?;
+            backend.connect(&delay, &feedback)?;
+            backend.connect(&feedback, &delay)?;
+            backend.connect(&delay, &panner)?;
+            backend.connect(&panner, &wet_gain)?;
+
+            let game_lfo = GameLfo::new(backend, lfo, bpm)?;
+            game_lfo.modulate(
+                backend,
+                &Param::new(&delay, ParamName::DelayTime),
+                (params.delay, params.depth),
+            )?;
+            game_lfo.start(backend, when)?;
+
+            delays.push(delay);
+            feedbacks.push(feedback);
+            panners.push(panner);
+            lfos.push(game_lfo);
+        }
+
+        Ok(GameModulatedDelay {
+            input_node,
+            output_node,
+            dry_gain,
+            wet_gain,
+            delays,
+            feedbacks,
+            panners,
+            lfos,
+            parameters: params.clone(),
+        })
     }
 }
 
-impl AudioEffectNode for GameDistortion {
-    fn get_input_node(&self) -> &dyn AsRef<WebAudioNode> {
-        &self.input_gain
+/// Chain of all-pass filters swept by a single LFO, mixed with the dry signal
+pub struct GamePhaser<B: AudioBackend> {
+    input_node: B::Node,
+    output_node: B::Node,
+    dry_gain: B::Node,
+    wet_gain: B::Node,
+    stages: Vec<B::Node>,
+    lfo: GameLfo<B>,
+    parameters: PhaserParameters,
+}
+
+impl<B: AudioBackend> GamePhaser<B> {
+    fn new(
+        backend: &B,
+        params: &PhaserParameters,
+        bpm: u32,
+        when: GameTime,
+    ) -> GameResult<GamePhaser<B>> {
+        let input_node = backend.create_node(NodeKind::Gain)?;
+        let output_node = backend.create_node(NodeKind::Gain)?;
+        let dry_gain = backend.create_node(NodeKind::Gain)?;
+        let wet_gain = backend.create_node(NodeKind::Gain)?;
+
+        set_param(backend, &dry_gain, ParamName::Gain, params.dry_level)?;
+        set_param(backend, &wet_gain, ParamName::Gain, params.wet_level)?;
+
+        let lfo = GameLfo::new(backend, &params.lfo(), bpm)?;
+        let mut stages = vec![];
+        for _ in 0..params.stages.max(1) {
+            let stage = GameFilter::new(backend, &params.stage_filter(params.frequency))?;
+            lfo.modulate(
+                backend,
+                &Param::new(&stage.filter, ParamName::Frequency),
+                params.sweep(),
+            )?;
+            stages.push(stage.filter);
+        }
+
+        // Dry path: input -> dry_gain -> output
+        backend.connect(&input_node, &dry_gain)?;
+        backend.connect(&dry_gain, &output_node)?;
+
+        // Wet path: input -> all-pass stages -> wet_gain -> output
+        backend.connect(&input_node, &stages[0])?;
+        for pair in stages.windows(2) {
+            backend.connect(&pair[0], &pair[1])?;
+        }
+        backend.connect(&stages[stages.len() - 1], &wet_gain)?;
+        backend.connect(&wet_gain, &output_node)?;
+
+        lfo.start(backend, when)?;
+
+        Ok(GamePhaser {
+            input_node,
+            output_node,
+            dry_gain,
+            wet_gain,
+            stages,
+            lfo,
+            parameters: params.clone(),
+        })
     }
+}
 
-    fn get_output_node(&self) -> &dyn AsRef<WebAudioNode> {
-        &self.output_gain
+/// Places the signal in the stereo field, with an LFO sweeping it for auto-pan
+pub struct GamePan<B: AudioBackend> {
+    panner: B::Node,
+    lfo: GameLfo<B>,
+    parameters: PanParameters,
+}
+
+impl<B: AudioBackend> GamePan<B> {
+    fn new(
+        backend: &B,
+        params: &PanParameters,
+        bpm: u32,
+        when: GameTime,
+    ) -> GameResult<GamePan<B>> {
+        let panner = backend.create_node(NodeKind::StereoPanner)?;
+
+        // NOTE: the LFO runs even for a fixed pan, so the depth can be raised while playing
+        let lfo = GameLfo::new(backend, &params.lfo(), bpm)?;
+        lfo.modulate(
+            backend,
+            &Param::new(&panner, ParamName::Pan),
+            params.sweep(),
+        )?;
+        lfo.start(backend, when)?;
+
+        Ok(GamePan {
+            panner,
+            lfo,
+            parameters: *params,
+        })
     }
 }
 
-impl AudioEffectNode for GameReverb {
-    fn get_input_node(&self) -> &dyn AsRef<WebAudioNode> {
-        &self.input_node
+/// Lo-fi effect running in an `AudioWorkletNode`. Without the worklet a wave shaper
+/// staircase reduces the bit depth, but the sample rate stays untouched
+pub struct GameBitcrusher<B: AudioBackend> {
+    node: BitcrusherNode<B>,
+    parameters: BitcrusherParameters,
+}
+
+enum BitcrusherNode<B: AudioBackend> {
+    Worklet(B::Node),
+    Staircase(B::Node),
+}
+
+impl<B: AudioBackend> GameBitcrusher<B> {
+    fn new(backend: &B, params: &BitcrusherParameters) -> GameResult<GameBitcrusher<B>> {
+        let node = if backend.has_processor(BITCRUSHER_PROCESSOR) {
+            let worklet = backend.create_node(NodeKind::Worklet(BITCRUSHER_PROCESSOR))?;
+            set_param(backend, &worklet, BITCRUSHER_BITS, params.bits)?;
+            set_param(backend, &worklet, BITCRUSHER_RATE, params.sample_rate)?;
+            BitcrusherNode::Worklet(worklet)
+        } else {
+            let wave_shaper = backend.create_node(NodeKind::WaveShaper)?;
+            set_staircase(backend, &wave_shaper, params)?;
+            BitcrusherNode::Staircase(wave_shaper)
+        };
+
+        Ok(GameBitcrusher {
+            node,
+            parameters: *params,
+        })
     }
+}
 
-    fn get_output_node(&self) -> &dyn AsRef<WebAudioNode> {
-        &self.output_node
+fn set_staircase<B: AudioBackend>(
+    backend: &B,
+    wave_shaper: &B::Node,
+    params: &BitcrusherParameters,
+) -> GameResult<()> {
+    let curve = params.curve(BITCRUSHER_CURVE_SIZE);
+    backend.set_curve(wave_shaper, &curve)
+}
+
+/// Output of a lane, a separate gain so tremolo doesn't affect other lanes or the volume
+/// setting, panned before reaching the master gain
+struct GameLane<B: AudioBackend> {
+    output: B::Node,
+    panner: B::Node,
+    /// Fades the lane out when another graph takes over
+    fader: B::Node,
+}
+
+impl<B: AudioBackend> GameLane<B> {
+    fn new(backend: &B, master_gain: &B::Node, pan: f32) -> GameResult<GameLane<B>> {
+        let output = backend.create_node(NodeKind::Gain)?;
+        let panner = backend.create_node(NodeKind::StereoPanner)?;
+        // NOTE: mono lanes are upmixed first, so a centered lane keeps its level instead of
+        // being split between the channels at -3dB
+        backend.set_channel_count_mode(&panner, ChannelCountMode::Explicit)?;
+        set_param(backend, &panner, ParamName::Pan, pan)?;
+
+        let fader = backend.create_node(NodeKind::Gain)?;
+
+        backend.connect(&output, &panner)?;
+        backend.connect(&panner, &fader)?;
+        backend.connect(&fader, master_gain)?;
+
+        Ok(GameLane {
+            output,
+            panner,
+            fader,
+        })
+    }
+
+    fn fade_out(&self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        fade_out(backend, &self.fader, when, fade)
+    }
+
+    /// Brings the lane in as the one it takes over from fades out
+    fn fade_in(&self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        let gain = Param::new(&self.fader, ParamName::Gain);
+        backend.automate(&gain, Automation::ValueAtTime(0.0, when))?;
+        backend.automate(&gain, Automation::LinearRamp(1.0, when + fade))
+    }
+
+    fn disconnect(&self, backend: &B) -> GameResult<()> {
+        backend.disconnect(&self.output)?;
+        backend.disconnect(&self.panner)?;
+        backend.disconnect(&self.fader)
+    }
+}
+
+/// Effect chain of a graph that was swapped out, kept until it has faded out
+struct RetiredChain<B: AudioBackend> {
+    lanes: Vec<GameLane<B>>,
+    effects: Vec<RefCell<GameEffectSlot<B>>>,
+    lfos: Vec<RefCell<GameLfo<B>>>,
+    end: GameTime,
+}
+
+impl<B: AudioBackend> RetiredChain<B> {
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        for lfo in &self.lfos {
+            lfo.borrow_mut().stop(backend)?;
+        }
+        for lane in &self.lanes {
+            lane.disconnect(backend)?;
+        }
+        for effect in &self.effects {
+            effect.borrow_mut().stop(backend)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct GameCompressor<B: AudioBackend> {
+    compressor: B::Node,
+    parameters: CompressorParameters,
+}
+
+impl<B: AudioBackend> GameCompressor<B> {
+    fn new(backend: &B, params: &CompressorParameters) -> GameResult<GameCompressor<B>> {
+        Ok(GameCompressor {
+            compressor: create_compressor(backend, params)?,
+            parameters: *params,
+        })
+    }
+}
+
+/// Dynamics shared by all lanes between the master gain and the destination:
+/// compressor -> limiter -> clipper. The limiter catches what the compressor lets
+/// through, the clipper cuts the peaks the limiter's attack is too slow for
+pub struct GameMasterBus<B: AudioBackend> {
+    compressor: B::Node,
+    limiter: B::Node,
+    clipper: B::Node,
+}
+
+impl<B: AudioBackend> GameMasterBus<B> {
+    fn new(backend: &B, audio_config: &AudioConfig) -> GameResult<GameMasterBus<B>> {
+        let compressor = create_compressor(backend, &audio_config.master_compressor)?;
+        let limiter = create_compressor(
+            backend,
+            &CompressorParameters::limiter(audio_config.limiter_ceiling),
+        )?;
+        let clipper = backend.create_node(NodeKind::WaveShaper)?;
+
+        let ceiling = 10f32.powf(audio_config.limiter_ceiling / 20.0);
+        let curve: Vec<f32> = (0..CLIPPER_CURVE_SIZE)
+            .map(|i| {
+                let x = i as f32 / (CLIPPER_CURVE_SIZE - 1) as f32 * 2.0 - 1.0;
+                x.clamp(-ceiling, ceiling)
+            })
+            .collect();
+        backend.set_curve(&clipper, &curve)?;
+
+        backend.connect(&compressor, &limiter)?;
+        backend.connect(&limiter, &clipper)?;
+        backend.connect(&clipper, &backend.destination())?;
+
+        Ok(GameMasterBus {
+            compressor,
+            limiter,
+            clipper,
+        })
+    }
+}
+
+/// Voice of a single note, kept until it has fallen silent
+struct ScheduledVoice<B: AudioBackend> {
+    voice: Box<dyn GameVoice<B>>,
+    start: GameTime,
+    end: GameTime,
+}
+
+impl<B: AudioBackend> ScheduledVoice<B> {
+    /// Cuts the note short at `when`, a note that hasn't started by then never plays
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        if when <= self.start {
+            self.voice.stop_immediate(backend)?;
+            self.end = self.start;
+        } else if when < self.end {
+            self.voice.release_at(backend, when, fade)?;
+            self.end = when + fade;
+        }
+        Ok(())
+    }
+}
+
+/// Voices of every lane, at most `max_polyphony` at once. A note past the limit steals
+/// the voice of the oldest note, which fades out as the new note starts
+struct VoiceAllocator<B: AudioBackend> {
+    voices: Vec<ScheduledVoice<B>>,
+    /// Stolen voices still fading out, they no longer count towards the polyphony
+    stolen: Vec<ScheduledVoice<B>>,
+    steals: u32,
+}
+
+impl<B: AudioBackend> VoiceAllocator<B> {
+    fn new() -> Self {
+        Self {
+            voices: vec![],
+            stolen: vec![],
+            steals: 0,
+        }
+    }
+
+    /// Disconnects and lets go of the voices that fell silent before `now`
+    fn release_finished(&mut self, backend: &B, now: GameTime) -> GameResult<()> {
+        for voice in self.voices.iter().chain(&self.stolen) {
+            if voice.end <= now {
+                voice.voice.disconnect(backend)?;
+            }
+        }
+        self.voices.retain(|voice| voice.end > now);
+        self.stolen.retain(|voice| voice.end > now);
+        Ok(())
+    }
+
+    fn allocate(
+        &mut self,
+        backend: &B,
+        voice: ScheduledVoice<B>,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        while self.voices.len() >= audio_config.max_polyphony {
+            let Some(oldest) = self
+                .voices
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.start.total_cmp(&b.start))
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            let mut stolen = self.voices.remove(oldest);
+            stolen.release_at(backend, voice.start, audio_config.steal_fade)?;
+            self.stolen.push(stolen);
+            self.steals += 1;
+        }
+        self.voices.push(voice);
+        Ok(())
+    }
+
+    /// Fades out the voices still sounding at `when`
+    fn release_after(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        for voice in &mut self.voices {
+            voice.release_at(backend, when, fade)?;
+        }
+        Ok(())
+    }
+
+    fn stop_all(&mut self, backend: &B) -> GameResult<()> {
+        for voice in self.voices.iter_mut().chain(&mut self.stolen) {
+            voice.voice.stop_immediate(backend)?;
+        }
+        self.voices = vec![];
+        self.stolen = vec![];
+        Ok(())
+    }
+}
+
+/// Looping notes of a lane. Only the notes starting within the look-ahead window are
+/// turned into voices, so a loop plays for as long as it's scheduled
+struct GameSequence<B: AudioBackend> {
+    oscillator: Oscillator,
+    envelope: Envelope,
+    unison: Option<Unison>,
+    glide: Option<Glide>,
+    periodic_wave: Option<B::Wave>,
+    noise_buffer: Option<B::Buffer>,
+    destination: B::Node,
+    vibrato_lfos: Vec<GameLfo<B>>,
+    notes: Vec<NoteEvent>,
+    loop_length: MusicTime,
+    bpm: u32,
+    /// Start of the first loop
+    origin: GameTime,
+    /// End of the window the notes were scheduled for so far
+    scheduled_until: GameTime,
+    /// Time another sequence takes over at
+    end: Option<GameTime>,
+    /// Fade out of the voices once another sequence took over
+    crossfade: GameTime,
+    /// Single voice playing every note when the lane glides
+    mono: Option<Box<dyn GameMonoVoice<B>>>,
+}
+
+impl<B: AudioBackend> GameSequence<B> {
+    /// Starts the voice of a gliding lane, the notes are scheduled on it as they come.
+    /// Only oscillators with a pitch to slide play mono, the other sound sources keep
+    /// a voice per note
+    fn start_mono(&mut self, backend: &B) -> GameResult<()> {
+        let (Some(_), Oscillator::Wave(wave_shape)) = (&self.glide, self.oscillator) else {
+            return Ok(());
+        };
+        let Some(first) = Glide::mono_notes(&self.notes, self.bpm).first().copied() else {
+            return Ok(());
+        };
+        let voice: Box<dyn GameMonoVoice<B>> = match &self.unison {
+            Some(unison) => Box::new(GameUnison::new(
+                backend,
+                wave_shape,
+                self.periodic_wave.clone(),
+                self.envelope,
+                unison,
+            )?),
+            None => Box::new(GameOscillator::new(
+                backend,
+                wave_shape,
+                self.periodic_wave.clone(),
+                self.envelope,
+            )?),
+        };
+        voice.start_mono(
+            backend,
+            &self.destination,
+            first.frequency,
+            self.origin + first.start,
+        )?;
+        modulate_detune(backend, voice.as_ref(), &self.vibrato_lfos)?;
+        self.mono = Some(voice);
+        Ok(())
+    }
+
+    /// Schedules the notes starting before `until` that weren't scheduled yet. Notes
+    /// whose start has already passed are skipped rather than played late
+    fn schedule(
+        &mut self,
+        backend: &B,
+        until: GameTime,
+        sample_buffers: &HashMap<SampleAsset, B::Buffer>,
+        samples: &Samples,
+        audio_config: &AudioConfig,
+    ) -> GameResult<Vec<ScheduledVoice<B>>> {
+        let from = self.scheduled_until.max(backend.current_time());
+        let until = until.min(self.end.unwrap_or(GameTime::INFINITY));
+        if until <= from {
+            return Ok(vec![]);
+        }
+        self.scheduled_until = until;
+        let loops = self.loops_between(from, until);
+
+        if let (Some(glide), Some(mono)) = (&self.glide, &self.mono) {
+            // NOTE: a loop of context on each side, slides and phrases carry over
+            // from one loop into the next
+            let context = loops.start.saturating_sub(1)..loops.end + 1;
+            let notes: Vec<MonoNote> = Glide::mono_notes(&self.notes_in(context), self.bpm)
+                .into_iter()
+                .map(|note| MonoNote {
+                    start: self.origin + note.start,
+                    ..note
+                })
+                .collect();
+            mono.schedule_mono(
+                backend,
+                &notes,
+                from..until,
+                glide,
+                self.is_looping(),
+                audio_config,
+            )?;
+            // NOTE: phrases reach past the end, the fade out has to come after them
+            if let (Some(end), Some(mono)) = (self.end, &mut self.mono) {
+                mono.release_at(backend, end, self.crossfade)?;
+            }
+            return Ok(vec![]);
+        }
+
+        let mut voices = vec![];
+        for note_event in self.notes_in(loops) {
+            let start = self.origin + note_event.start.to_seconds(self.bpm);
+            if start < from || start >= until {
+                continue;
+            }
+            let duration = note_event.duration.to_seconds(self.bpm);
+            let voice = self.create_voice(backend, &note_event, sample_buffers, samples)?;
+            modulate_detune(backend, voice.as_ref(), &self.vibrato_lfos)?;
+            voice.play(
+                backend,
+                &self.destination,
+                note_event.note.to_frequancy(),
+                start,
+                duration,
+                audio_config,
+            )?;
+            let mut voice = ScheduledVoice {
+                end: start + voice.tail_end(duration),
+                voice,
+                start,
+            };
+            if let Some(end) = self.end {
+                voice.release_at(backend, end, self.crossfade)?;
+            }
+            voices.push(voice);
+        }
+        Ok(voices)
+    }
+
+    fn create_voice(
+        &self,
+        backend: &B,
+        note_event: &NoteEvent,
+        sample_buffers: &HashMap<SampleAsset, B::Buffer>,
+        samples: &Samples,
+    ) -> GameResult<Box<dyn GameVoice<B>>> {
+        let voice: Box<dyn GameVoice<B>> = match self.oscillator {
+            Oscillator::Wave(wave_shape) => match &self.unison {
+                Some(unison) => Box::new(GameUnison::new(
+                    backend,
+                    wave_shape,
+                    self.periodic_wave.clone(),
+                    self.envelope,
+                    unison,
+                )?),
+                None => Box::new(GameOscillator::new(
+                    backend,
+                    wave_shape,
+                    self.periodic_wave.clone(),
+                    self.envelope,
+                )?),
+            },
+            Oscillator::Noise(_) => {
+                let noise_buffer = self
+                    .noise_buffer
+                    .as_ref()
+                    .ok_or(GameError::msg("Noise voice has no noise buffer"))?;
+                Box::new(GameNoise::new(backend, noise_buffer, self.envelope)?)
+            }
+            Oscillator::Drum(drum_voice) => {
+                let noise_buffer = self
+                    .noise_buffer
+                    .as_ref()
+                    .ok_or(GameError::msg("Drum voice has no noise buffer"))?;
+                let drum_voice =
+                    drum_voice.unwrap_or(DrumVoice::from_note_name(note_event.note.note_name));
+                Box::new(GameDrum::new(backend, drum_voice, noise_buffer)?)
+            }
+            Oscillator::Sampler(instrument) => {
+                let zone = instrument
+                    .zone_for(&note_event.note)
+                    .ok_or(GameError::msg("Sampler has no zone for note"))?;
+                let buffer = sample_buffers
+                    .get(&zone.asset)
+                    .ok_or(GameError::msg("Sample buffer was not loaded"))?;
+                let sample_rate = samples
+                    .get(&zone.asset)
+                    .ok_or(GameError::msg("Sample asset is not loaded"))?
+                    .sample_rate;
+                Box::new(GameSampler::new(
+                    backend,
+                    buffer,
+                    sample_rate,
+                    &zone,
+                    zone.playback_rate(&note_event.note),
+                    self.envelope,
+                )?)
+            }
+        };
+        Ok(voice)
+    }
+
+    /// Stops scheduling notes from `at` on and fades out the mono voice there
+    fn end_at(&mut self, backend: &B, at: GameTime) -> GameResult<()> {
+        let end = self.end.map_or(at, |end| end.min(at));
+        self.end = Some(end);
+        if let Some(mono) = &mut self.mono {
+            mono.release_at(backend, end, self.crossfade)?;
+        }
+        Ok(())
+    }
+
+    fn playhead(&self, now: GameTime) -> Playhead {
+        let elapsed = now - self.origin;
+        let loop_seconds = self.loop_seconds();
+        let loops = if self.is_looping() {
+            (elapsed / loop_seconds).floor()
+        } else {
+            0.0
+        };
+        let offset = elapsed - loops * loop_seconds;
+
+        let mut active_cards = vec![];
+        for note in &self.notes {
+            let start = note.start.to_seconds(self.bpm);
+            let duration = note.duration.to_seconds(self.bpm);
+            // NOTE: a note of the previous loop may still be sounding
+            let previous = (loops > 0.0).then_some(offset + loop_seconds);
+            let played = [Some(offset), previous]
+                .into_iter()
+                .flatten()
+                .find(|time| *time >= start && *time < start + duration);
+            if let Some(time) = played {
+                active_cards.push((note.card, ((time - start) / duration) as f32));
+            }
+        }
+
+        Playhead {
+            loop_start: self.origin + loops * loop_seconds,
+            position: if self.is_looping() {
+                (offset / loop_seconds) as f32
+            } else {
+                0.0
+            },
+            active_cards,
+        }
+    }
+
+    /// Whether the sequence ended and has faded out by `now`
+    fn is_over(&self, now: GameTime) -> bool {
+        self.end.is_some_and(|end| end + self.crossfade <= now)
+    }
+
+    fn is_looping(&self) -> bool {
+        self.loop_length > MusicTime::ZERO
+    }
+
+    fn loop_seconds(&self) -> GameTime {
+        self.loop_length.to_seconds(self.bpm)
+    }
+
+    /// Loops with notes starting between `from` and `until`. A sequence without
+    /// length plays its notes once
+    fn loops_between(&self, from: GameTime, until: GameTime) -> Range<u32> {
+        if !self.is_looping() {
+            return 0..1;
+        }
+        let loop_seconds = self.loop_seconds();
+        let start = ((from - self.origin) / loop_seconds).floor().max(0.0) as u32;
+        let end = ((until - self.origin) / loop_seconds).ceil().max(0.0) as u32;
+        start..end
+    }
+
+    /// Notes of `loops`, from the start of the first loop
+    fn notes_in(&self, loops: Range<u32>) -> Vec<NoteEvent> {
+        let loops = if self.is_looping() { loops } else { 0..1 };
+        loops
+            .flat_map(|i| {
+                self.notes
+                    .iter()
+                    .map(move |note| note.shifted(self.loop_length * i))
+            })
+            .collect()
+    }
+
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        if let Some(mono) = &mut self.mono {
+            mono.stop_immediate(backend)?;
+        }
+        for lfo in &mut self.vibrato_lfos {
+            lfo.stop(backend)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything scheduled for a single note, owning all of its nodes
+trait GameVoice<B: AudioBackend> {
+    fn play(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        frequency: f32,
+        start: GameTime,
+        duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()>;
+
+    fn stop_immediate(&mut self, backend: &B) -> GameResult<()>;
+
+    /// Fades the voice out over `fade` from `when` on, cutting the note short
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()>;
+
+    /// Time from the note start until the voice is silent
+    fn tail_end(&self, duration: GameTime) -> GameTime;
+
+    /// Detaches the nodes that outlive the stopped sources, called once the voice is silent
+    fn disconnect(&self, _backend: &B) -> GameResult<()> {
+        Ok(())
+    }
+
+    /// Params vibrato LFOs modulate
+    fn detune_params(&self) -> Vec<Param<B::Node>> {
+        vec![]
+    }
+}
+
+impl<B: AudioBackend> GameVoice<B> for GameOscillator<B> {
+    fn play(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        frequency: f32,
+        start: GameTime,
+        duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        self.play_with_destination(
+            backend,
+            destination,
+            frequency,
+            start,
+            duration,
+            audio_config,
+        )
+    }
+
+    fn stop_immediate(&mut self, backend: &B) -> GameResult<()> {
+        backend.stop(&self.osc, None)?;
+        if let Some(modulator) = &self.modulator {
+            modulator.stop(backend)?;
+        }
+        self.is_stopped = true;
+        Ok(())
+    }
+
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        fade_out(backend, &self.gain, when, fade)?;
+        self.stop_at(backend, when + fade)
+    }
+
+    fn tail_end(&self, duration: GameTime) -> GameTime {
+        self.envelope.tail_end(duration)
+    }
+
+    fn detune_params(&self) -> Vec<Param<B::Node>> {
+        vec![Param::new(&self.osc, ParamName::Detune)]
+    }
+}
+
+impl<B: AudioBackend> GameVoice<B> for GameUnison<B> {
+    fn play(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        frequency: f32,
+        start: GameTime,
+        duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        backend.connect(&self.output, destination)?;
+        for (voice, panner) in self.voices.iter().zip(&self.panners) {
+            voice.play_with_destination(
+                backend,
+                panner,
+                frequency,
+                start,
+                duration,
+                audio_config,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn stop_immediate(&mut self, backend: &B) -> GameResult<()> {
+        for voice in &mut self.voices {
+            voice.stop_immediate(backend)?;
+        }
+        backend.disconnect(&self.output)
+    }
+
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        for voice in &mut self.voices {
+            voice.release_at(backend, when, fade)?;
+        }
+        Ok(())
+    }
+
+    fn tail_end(&self, duration: GameTime) -> GameTime {
+        self.voices
+            .iter()
+            .map(|voice| voice.envelope.tail_end(duration))
+            .fold(0.0, GameTime::max)
+    }
+
+    // NOTE: the oscillators stop on their own at the end of the fade, the shared output
+    // would stay connected to the lane without this
+    fn disconnect(&self, backend: &B) -> GameResult<()> {
+        backend.disconnect(&self.output)
+    }
+
+    fn detune_params(&self) -> Vec<Param<B::Node>> {
+        self.voices
+            .iter()
+            .flat_map(|voice| voice.detune_params())
+            .collect()
+    }
+}
+
+impl<B: AudioBackend> GameVoice<B> for GameNoise<B> {
+    fn play(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        _frequency: f32,
+        start: GameTime,
+        duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        backend.connect(&self.source, &self.gain)?;
+        backend.connect(&self.gain, destination)?;
+
+        schedule_envelope(
+            backend,
+            &self.gain,
+            &self.envelope,
+            start,
+            duration,
+            audio_config.output_gain,
+        )?;
+
+        backend.start(&self.source, start)?;
+        backend.stop(&self.source, Some(start + self.envelope.tail_end(duration)))
+    }
+
+    fn stop_immediate(&mut self, backend: &B) -> GameResult<()> {
+        backend.stop(&self.source, None)
+    }
+
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        fade_out(backend, &self.gain, when, fade)?;
+        backend.stop(&self.source, Some(when + fade))
+    }
+
+    fn tail_end(&self, duration: GameTime) -> GameTime {
+        self.envelope.tail_end(duration)
+    }
+}
+
+impl<B: AudioBackend> GameVoice<B> for GameSampler<B> {
+    /// The pitch is baked into the playback rate, so `frequency` is unused
+    fn play(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        _frequency: f32,
+        start: GameTime,
+        duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        backend.connect(&self.source, &self.gain)?;
+        backend.connect(&self.gain, destination)?;
+
+        schedule_envelope(
+            backend,
+            &self.gain,
+            &self.envelope,
+            start,
+            duration,
+            audio_config.output_gain,
+        )?;
+
+        backend.start(&self.source, start)?;
+        backend.stop(&self.source, Some(start + self.envelope.tail_end(duration)))
+    }
+
+    fn stop_immediate(&mut self, backend: &B) -> GameResult<()> {
+        backend.stop(&self.source, None)
+    }
+
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        fade_out(backend, &self.gain, when, fade)?;
+        backend.stop(&self.source, Some(when + fade))
+    }
+
+    fn tail_end(&self, duration: GameTime) -> GameTime {
+        self.envelope.tail_end(duration)
+    }
+
+    fn detune_params(&self) -> Vec<Param<B::Node>> {
+        vec![Param::new(&self.source, ParamName::Detune)]
+    }
+}
+
+impl<B: AudioBackend> GameVoice<B> for GameDrum<B> {
+    /// Drums ignore the note frequency and length, every layer plays its own one-shot envelope
+    fn play(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        _frequency: f32,
+        start: GameTime,
+        _duration: GameTime,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        for drum_layer in &self.layers {
+            let layer = &drum_layer.layer;
+            let layer_start = start + layer.offset;
+
+            match &drum_layer.filter {
+                Some(filter) => {
+                    backend.connect(&drum_layer.source, filter)?;
+                    backend.connect(filter, &drum_layer.gain)?;
+                }
+                None => backend.connect(&drum_layer.source, &drum_layer.gain)?,
+            }
+            backend.connect(&drum_layer.gain, destination)?;
+
+            if let (Some(frequency), DrumSource::Tone { sweep_time, .. }) =
+                (&drum_layer.frequency, layer.source)
+            {
+                backend.automate(
+                    frequency,
+                    Automation::ValueAtTime(layer.frequency_at(0.0), layer_start),
+                )?;
+                backend.automate(
+                    frequency,
+                    Automation::ExponentialRamp(
+                        layer.frequency_at(sweep_time),
+                        layer_start + sweep_time,
+                    ),
+                )?;
+            }
+
+            schedule_envelope(
+                backend,
+                &drum_layer.gain,
+                &layer.envelope,
+                layer_start,
+                layer.duration(),
+                layer.level * audio_config.output_gain,
+            )?;
+
+            backend.start(&drum_layer.source, layer_start)?;
+            backend.stop(&drum_layer.source, Some(start + layer.end()))?;
+        }
+        Ok(())
+    }
+
+    fn stop_immediate(&mut self, backend: &B) -> GameResult<()> {
+        for drum_layer in &self.layers {
+            backend.stop(&drum_layer.source, None)?;
+        }
+        Ok(())
+    }
+
+    fn release_at(&mut self, backend: &B, when: GameTime, fade: GameTime) -> GameResult<()> {
+        for drum_layer in &self.layers {
+            fade_out(backend, &drum_layer.gain, when, fade)?;
+            backend.stop(&drum_layer.source, Some(when + fade))?;
+        }
+        Ok(())
+    }
+
+    fn tail_end(&self, _duration: GameTime) -> GameTime {
+        self.layers
+            .iter()
+            .map(|drum_layer| drum_layer.layer.end())
+            .fold(0.0, GameTime::max)
+    }
+}
+
+/// Voice playing every note of a gliding lane, sliding its pitch into each note and
+/// retriggering the envelope only at the start of each phrase
+trait GameMonoVoice<B: AudioBackend>: GameVoice<B> {
+    fn start_mono(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        frequency: f32,
+        start: GameTime,
+    ) -> GameResult<()>;
+
+    /// Schedules the slides and phrases of `notes` starting within `range`, the notes
+    /// around it only tell where they come from and how long they last. Without an
+    /// end in sight the last phrase of a loop is held
+    fn schedule_mono(
+        &self,
+        backend: &B,
+        notes: &[MonoNote],
+        range: Range<GameTime>,
+        glide: &Glide,
+        is_looping: bool,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()>;
+}
+
+impl<B: AudioBackend> GameMonoVoice<B> for GameOscillator<B> {
+    fn start_mono(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        frequency: f32,
+        start: GameTime,
+    ) -> GameResult<()> {
+        self.apply_wave_shape(backend)?;
+        set_param(backend, &self.osc, ParamName::Frequency, frequency)?;
+        set_param(backend, &self.gain, ParamName::Gain, 0.0)?;
+        if let Some(modulator) = &self.modulator {
+            modulator.connect_to_carrier(backend, &self.osc, frequency, start)?;
+        }
+
+        backend.connect(&self.osc, &self.gain)?;
+        backend.connect(&self.gain, destination)?;
+        backend.start(&self.osc, start)
+    }
+
+    fn schedule_mono(
+        &self,
+        backend: &B,
+        notes: &[MonoNote],
+        range: Range<GameTime>,
+        glide: &Glide,
+        is_looping: bool,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        let frequency = Param::new(&self.osc, ParamName::Frequency);
+        for (from, to, start, end) in glide.ramps(notes) {
+            if !range.contains(&start) {
+                continue;
+            }
+            backend.automate(&frequency, Automation::ValueAtTime(from, start))?;
+            backend.automate(&frequency, Automation::ExponentialRamp(to, end))?;
+            if let Some(modulator) = &self.modulator {
+                modulator.glide(backend, from, to, start, end)?;
+            }
+        }
+
+        let phrases = Glide::phrases(notes, self.envelope.release);
+        for (i, (start, duration)) in phrases.iter().enumerate() {
+            if !range.contains(start) {
+                continue;
+            }
+            // NOTE: the notes reach a loop past the range, a phrase running into
+            // their end spans a whole loop and so goes on forever
+            if is_looping && i + 1 == phrases.len() {
+                schedule_held_envelope(
+                    backend,
+                    &self.gain,
+                    &self.envelope,
+                    *start,
+                    audio_config.output_gain,
+                )?;
+            } else {
+                self.schedule_envelope(backend, *start, *duration, audio_config)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> GameMonoVoice<B> for GameUnison<B> {
+    fn start_mono(
+        &self,
+        backend: &B,
+        destination: &B::Node,
+        frequency: f32,
+        start: GameTime,
+    ) -> GameResult<()> {
+        backend.connect(&self.output, destination)?;
+        for (voice, panner) in self.voices.iter().zip(&self.panners) {
+            voice.start_mono(backend, panner, frequency, start)?;
+        }
+        Ok(())
+    }
+
+    fn schedule_mono(
+        &self,
+        backend: &B,
+        notes: &[MonoNote],
+        range: Range<GameTime>,
+        glide: &Glide,
+        is_looping: bool,
+        audio_config: &AudioConfig,
+    ) -> GameResult<()> {
+        for voice in &self.voices {
+            voice.schedule_mono(
+                backend,
+                notes,
+                range.clone(),
+                glide,
+                is_looping,
+                audio_config,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Effect crossfaded with its own input, which lets a card be bypassed or mixed in
+/// while it plays
+pub struct GameEffectSlot<B: AudioBackend> {
+    input_node: B::Node,
+    output_node: B::Node,
+    dry_gain: B::Node,
+    wet_gain: B::Node,
+    effect: Box<dyn AudioEffectNode<B>>,
+}
+
+impl<B: AudioBackend> GameEffectSlot<B> {
+    fn new(
+        backend: &B,
+        effect: Box<dyn AudioEffectNode<B>>,
+        mix: &EffectMix,
+    ) -> GameResult<GameEffectSlot<B>> {
+        let input_node = backend.create_node(NodeKind::Gain)?;
+        let output_node = backend.create_node(NodeKind::Gain)?;
+        let dry_gain = backend.create_node(NodeKind::Gain)?;
+        let wet_gain = backend.create_node(NodeKind::Gain)?;
+
+        let (dry_level, wet_level) = mix.gains();
+        set_param(backend, &dry_gain, ParamName::Gain, dry_level)?;
+        set_param(backend, &wet_gain, ParamName::Gain, wet_level)?;
+
+        backend.connect(&input_node, &dry_gain)?;
+        backend.connect(&input_node, effect.get_input_node())?;
+        backend.connect(effect.get_output_node(), &wet_gain)?;
+        backend.connect(&dry_gain, &output_node)?;
+        backend.connect(&wet_gain, &output_node)?;
+
+        Ok(GameEffectSlot {
+            input_node,
+            output_node,
+            dry_gain,
+            wet_gain,
+            effect,
+        })
+    }
+
+    /// Crossfades to the new mix, short enough that toggling a bypass is heard at once
+    fn set_mix(&self, backend: &B, mix: &EffectMix, smoothing: f64) -> GameResult<()> {
+        let when = backend.current_time();
+        let (dry_level, wet_level) = mix.gains();
+        smooth_param(
+            backend,
+            &self.dry_gain,
+            ParamName::Gain,
+            dry_level,
+            when,
+            smoothing,
+        )?;
+        smooth_param(
+            backend,
+            &self.wet_gain,
+            ParamName::Gain,
+            wet_level,
+            when,
+            smoothing,
+        )
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameEffectSlot<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_node
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_node
+    }
+
+    fn modulated_param(&self) -> Option<Param<B::Node>> {
+        self.effect.modulated_param()
+    }
+
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        self.effect.stop(backend)
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        self.effect.update(backend, effect, bpm, smoothing)
+    }
+}
+
+trait AudioEffectNode<B: AudioBackend> {
+    fn get_input_node(&self) -> &B::Node;
+    fn get_output_node(&self) -> &B::Node;
+
+    /// Param an LFO placed in front of the effect modulates
+    fn modulated_param(&self) -> Option<Param<B::Node>> {
+        None
+    }
+
+    /// Stops sources the effect runs on its own, like internal LFOs
+    fn stop(&mut self, _backend: &B) -> GameResult<()> {
+        Ok(())
+    }
+
+    /// Follows new parameters of the effect while it plays
+    fn update(
+        &mut self,
+        _backend: &B,
+        _effect: &AudioEffect,
+        _bpm: u32,
+        _smoothing: f64,
+    ) -> GameResult<()> {
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameFilter<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.filter
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.filter
+    }
+
+    fn modulated_param(&self) -> Option<Param<B::Node>> {
+        Some(Param::new(&self.filter, ParamName::Frequency))
+    }
+
+    // NOTE: an LFO in front of the filter keeps the sweep range of the old cutoff
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        _bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Filter(params) = effect {
+            let when = backend.current_time();
+            let filter = &self.filter;
+            smooth_param(
+                backend,
+                filter,
+                ParamName::Frequency,
+                params.frequency,
+                when,
+                smoothing,
+            )?;
+            smooth_param(backend, filter, ParamName::Q, params.q, when, smoothing)?;
+            smooth_param(
+                backend,
+                filter,
+                ParamName::Gain,
+                params.gain,
+                when,
+                smoothing,
+            )?;
+            self.parameters = params.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameDistortion<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_gain
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_gain
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        _bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Distortion(params) = effect {
+            let when = backend.current_time();
+            // NOTE: the curve is large, it's only rebuilt when the knob actually moved
+            if params.amount != self.parameters.amount
+                || params.curve_type != self.parameters.curve_type
+            {
+                let curve = params.curve(DISTORTION_CURVE_SIZE);
+                backend.set_curve(&self.wave_shaper, &curve)?;
+            }
+            smooth_param(
+                backend,
+                &self.input_gain,
+                ParamName::Gain,
+                params.input_gain(),
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.output_gain,
+                ParamName::Gain,
+                params.compensation_gain(),
+                when,
+                smoothing,
+            )?;
+            self.parameters = params.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameReverb<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_node
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_node
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        _bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Reverb(params) = effect {
+            let when = backend.current_time();
+            if params.decay_time != self.parameters.decay_time {
+                let impulse_buffer = create_impulse_buffer(backend, params, self.random.clone())?;
+                backend.set_buffer(&self.convolver, &impulse_buffer)?;
+            }
+            smooth_param(
+                backend,
+                &self.dry_gain,
+                ParamName::Gain,
+                params.dry_level,
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.wet_gain,
+                ParamName::Gain,
+                params.wet_level,
+                when,
+                smoothing,
+            )?;
+            self.parameters = params.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameDelay<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_node
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_node
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Delay(params) = effect {
+            let when = backend.current_time();
+            let delay_time = params.delay_seconds(bpm).min(self.max_delay_time);
+            for delay in &self.delays {
+                smooth_param(
+                    backend,
+                    delay,
+                    ParamName::DelayTime,
+                    delay_time,
+                    when,
+                    smoothing,
+                )?;
+            }
+            smooth_param(
+                backend,
+                &self.feedback,
+                ParamName::Gain,
+                params.feedback_gain(),
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.damping,
+                ParamName::Frequency,
+                params.damping,
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.dry_gain,
+                ParamName::Gain,
+                params.dry_level,
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.wet_gain,
+                ParamName::Gain,
+                params.wet_level,
+                when,
+                smoothing,
+            )?;
+            self.parameters = params.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameModulatedDelay<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_node
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_node
+    }
+
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        for lfo in &mut self.lfos {
+            lfo.stop(backend)?;
+        }
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Chorus(params) | AudioEffect::Flanger(params) = effect {
+            let when = backend.current_time();
+            for (game_lfo, lfo) in self.lfos.iter().zip(params.voice_lfos()) {
+                game_lfo.set_frequency(backend, lfo.frequency(bpm), when, smoothing)?;
+            }
+            for feedback in &self.feedbacks {
+                smooth_param(
+                    backend,
+                    feedback,
+                    ParamName::Gain,
+                    params.feedback_gain(),
+                    when,
+                    smoothing,
+                )?;
+            }
+            smooth_param(
+                backend,
+                &self.dry_gain,
+                ParamName::Gain,
+                params.dry_level,
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.wet_gain,
+                ParamName::Gain,
+                params.wet_gain(),
+                when,
+                smoothing,
+            )?;
+            self.parameters = params.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GamePhaser<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.input_node
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.output_node
+    }
+
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        self.lfo.stop(backend)
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Phaser(params) = effect {
+            let when = backend.current_time();
+            let (base, amplitude) = params.sweep();
+            for stage in &self.stages {
+                smooth_param(backend, stage, ParamName::Frequency, base, when, smoothing)?;
+            }
+            self.lfo
+                .set_amplitude(backend, amplitude, when, smoothing)?;
+            self.lfo
+                .set_frequency(backend, params.lfo().frequency(bpm), when, smoothing)?;
+            smooth_param(
+                backend,
+                &self.dry_gain,
+                ParamName::Gain,
+                params.dry_level,
+                when,
+                smoothing,
+            )?;
+            smooth_param(
+                backend,
+                &self.wet_gain,
+                ParamName::Gain,
+                params.wet_level,
+                when,
+                smoothing,
+            )?;
+            self.parameters = params.clone();
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameCompressor<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.compressor
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.compressor
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        _bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Compressor(params) = effect {
+            let when = backend.current_time();
+            let compressor = &self.compressor;
+            for (name, value) in [
+                (ParamName::Threshold, params.threshold),
+                (ParamName::Knee, params.knee),
+                (ParamName::Ratio, params.ratio),
+                (ParamName::Attack, params.attack),
+                (ParamName::Release, params.release),
+            ] {
+                smooth_param(backend, compressor, name, value, when, smoothing)?;
+            }
+            self.parameters = *params;
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GamePan<B> {
+    fn get_input_node(&self) -> &B::Node {
+        &self.panner
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        &self.panner
+    }
+
+    fn stop(&mut self, backend: &B) -> GameResult<()> {
+        self.lfo.stop(backend)
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        _bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Pan(params) = effect {
+            let when = backend.current_time();
+            let (position, amplitude) = params.sweep();
+            smooth_param(
+                backend,
+                &self.panner,
+                ParamName::Pan,
+                position,
+                when,
+                smoothing,
+            )?;
+            self.lfo
+                .set_amplitude(backend, amplitude, when, smoothing)?;
+            self.parameters = *params;
+        }
+        Ok(())
+    }
+}
+
+impl<B: AudioBackend> AudioEffectNode<B> for GameBitcrusher<B> {
+    fn get_input_node(&self) -> &B::Node {
+        match &self.node {
+            BitcrusherNode::Worklet(worklet) => worklet,
+            BitcrusherNode::Staircase(wave_shaper) => wave_shaper,
+        }
+    }
+
+    fn get_output_node(&self) -> &B::Node {
+        self.get_input_node()
+    }
+
+    fn update(
+        &mut self,
+        backend: &B,
+        effect: &AudioEffect,
+        _bpm: u32,
+        smoothing: f64,
+    ) -> GameResult<()> {
+        if let AudioEffect::Bitcrusher(params) = effect {
+            match &self.node {
+                BitcrusherNode::Worklet(worklet) => {
+                    let when = backend.current_time();
+                    smooth_param(
+                        backend,
+                        worklet,
+                        BITCRUSHER_BITS,
+                        params.bits,
+                        when,
+                        smoothing,
+                    )?;
+                    smooth_param(
+                        backend,
+                        worklet,
+                        BITCRUSHER_RATE,
+                        params.sample_rate,
+                        when,
+                        smoothing,
+                    )?;
+                }
+                BitcrusherNode::Staircase(wave_shaper) => {
+                    set_staircase(backend, wave_shaper, params)?
+                }
+            }
+            self.parameters = *params;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::audio_backend::recording::BackendEvent;
+    use crate::engine::audio_backend::recording::NodeId;
+    use crate::engine::audio_backend::recording::RecordingBackend;
+    use crate::engine::test_fixtures::audio_config;
+    use crate::engine::test_fixtures::graph;
+    use crate::nodes::audio_effect::AutoPanRate;
+    use crate::nodes::audio_effect::DelayDivision;
+    use crate::nodes::audio_effect::FilterType;
+    use crate::nodes::lfo::LfoPreset;
+    use crate::nodes::note_generator::Note;
+    use crate::nodes::note_generator::NoteGenerator;
+    use crate::nodes::note_generator::NoteName;
+    use crate::nodes::voice::UnisonVoices;
+    use crate::render::widgets::card_widget::CardType;
+
+    const SAMPLE_RATE: f32 = 44100.0;
+    const BPM: u32 = 120;
+
+    /// A3 quarter notes on a sine, through a lowpass filter
+    fn filtered_sine() -> AudioGraph {
+        graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Filter(FilterType::LowPass),
+        ])
+    }
+
+    fn engine(audio_config: &AudioConfig) -> AudioEngine<RecordingBackend> {
+        AudioEngine::new(
+            RecordingBackend::new(SAMPLE_RATE),
+            Samples::new(),
+            audio_config,
+        )
+        .expect("Engine should start")
+    }
+
+    fn successors(backend: &RecordingBackend, node: NodeId) -> Vec<NodeId> {
+        let mut successors: Vec<NodeId> = backend
+            .connections()
+            .into_iter()
+            .filter(|(from, _)| *from == node)
+            .map(|(_, to)| to)
+            .collect();
+        successors.dedup();
+        successors
+    }
+
+    /// Every way the output of `node` reaches the destination, the node included
+    fn paths_to_destination(backend: &RecordingBackend, node: NodeId) -> Vec<Vec<NodeId>> {
+        if node == RecordingBackend::DESTINATION {
+            return vec![vec![node]];
+        }
+        successors(backend, node)
+            .into_iter()
+            .flat_map(|next| paths_to_destination(backend, next))
+            .map(|path| [vec![node], path].concat())
+            .collect()
+    }
+
+    fn kinds(backend: &RecordingBackend, path: &[NodeId]) -> Vec<Option<NodeKind>> {
+        path.iter().map(|node| backend.node_kind(*node)).collect()
+    }
+
+    /// Gain node every note of the voice is enveloped on, right after its oscillator
+    fn voice_gains(backend: &RecordingBackend) -> Vec<NodeId> {
+        backend
+            .nodes_of(NodeKind::Oscillator)
+            .into_iter()
+            .flat_map(|osc| successors(backend, osc))
+            .collect()
+    }
+
+    fn gain_automations(backend: &RecordingBackend, node: NodeId) -> Vec<Automation> {
+        backend.automations(&Param::new(&node, ParamName::Gain))
+    }
+
+    /// Start times of the oscillators started since the events were last cleared
+    fn oscillator_starts(backend: &RecordingBackend) -> Vec<GameTime> {
+        backend
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                BackendEvent::Start(node, when)
+                    if backend.node_kind(node) == Some(NodeKind::Oscillator) =>
+                {
+                    Some(when)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn voices_play_through_the_effect_slot_lane_and_master_bus() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+
+        engine
+            .interpret_graph(BPM, &filtered_sine(), &audio_config)
+            .expect("Graph should play");
+        engine
+            .schedule_notes(0.1, &audio_config)
+            .expect("Notes should be scheduled");
+
+        let backend = engine.backend();
+        let osc = backend.nodes_of(NodeKind::Oscillator)[0];
+        let paths = paths_to_destination(backend, osc);
+        // NOTE: the slot sends the voice both through the filter and around it
+        assert_eq!(paths.len(), 2);
+        let filter = backend.nodes_of(NodeKind::BiquadFilter)[0];
+        let wet_path = paths
+            .iter()
+            .find(|path| path.contains(&filter))
+            .expect("Voice should reach the filter");
+        assert_eq!(
+            kinds(backend, wet_path),
+            vec![
+                Some(NodeKind::Oscillator),
+                // Voice gain, then the slot input
+                Some(NodeKind::Gain),
+                Some(NodeKind::Gain),
+                Some(NodeKind::BiquadFilter),
+                // Wet gain, then the slot output
+                Some(NodeKind::Gain),
+                Some(NodeKind::Gain),
+                // Lane output, panner and fader
+                Some(NodeKind::Gain),
+                Some(NodeKind::StereoPanner),
+                Some(NodeKind::Gain),
+                // Master gain, analyser, compressor, limiter and clipper
+                Some(NodeKind::Gain),
+                Some(NodeKind::Analyser {
+                    fft_size: ANALYSER_SIZE
+                }),
+                Some(NodeKind::DynamicsCompressor),
+                Some(NodeKind::DynamicsCompressor),
+                Some(NodeKind::WaveShaper),
+                None,
+            ]
+        );
+        assert_eq!(wet_path[9], engine.master_gain);
+        assert_eq!(wet_path[10], engine.analyser);
+        assert_eq!(wet_path[11], engine.master_bus.compressor);
+        assert_eq!(wet_path[12], engine.master_bus.limiter);
+        assert_eq!(wet_path[13], engine.master_bus.clipper);
+    }
+
+    #[test]
+    fn first_note_gain_follows_the_envelope() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+
+        engine
+            .interpret_graph(BPM, &filtered_sine(), &audio_config)
+            .expect("Graph should play");
+        engine
+            .schedule_notes(0.1, &audio_config)
+            .expect("Notes should be scheduled");
+
+        let backend = engine.backend();
+        let gains = voice_gains(backend);
+        assert_eq!(gains.len(), 1);
+        let peak = audio_config.output_gain;
+        // A quarter note at 120 BPM lasts half a second
+        assert_eq!(
+            gain_automations(backend, gains[0]),
+            vec![
+                Automation::ValueAtTime(0.0, 0.0),
+                Automation::LinearRamp(peak, 0.001),
+                Automation::LinearRamp(peak, 0.001),
+                Automation::LinearRamp(peak, 0.5),
+                Automation::LinearRamp(0.0, 0.5 + 0.002),
+            ]
+        );
+    }
+
+    #[test]
+    fn delay_lines_fit_a_quarter_at_the_slowest_tempo_and_follow_the_bpm() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+        let graph = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Delay(DelayDivision::EighthTriplet),
+        ]);
+
+        engine
+            .interpret_graph(BPM, &graph, &audio_config)
+            .expect("Graph should play");
+        engine.backend().set_current_time(1.0);
+        engine
+            .update_effects(60, &graph.audio_effects(), &graph.effect_mixes())
+            .expect("Effects should update");
+
+        let backend = engine.backend();
+        // A quarter at 40 BPM, the slowest tempo
+        let delay = backend.nodes_of(NodeKind::Delay {
+            max_delay_time: 1.5,
+        })[0];
+        let triplet_at = |bpm| DelayDivision::EighthTriplet.time().to_seconds(bpm) as f32;
+        assert_eq!(
+            backend.automations(&Param::new(&delay, ParamName::DelayTime)),
+            vec![
+                Automation::Value(triplet_at(BPM)),
+                Automation::Target {
+                    value: triplet_at(60),
+                    start: 1.0,
+                    time_constant: audio_config.param_smoothing,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn delay_lines_fit_the_echo_below_the_slowest_tempo() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+        let graph = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Delay(DelayDivision::Quarter),
+        ]);
+
+        engine
+            .interpret_graph(20, &graph, &audio_config)
+            .expect("Graph should play");
+
+        let backend = engine.backend();
+        // A quarter at 20 BPM
+        let delay = backend.nodes_of(NodeKind::Delay {
+            max_delay_time: 3.0,
+        })[0];
+        assert_eq!(
+            backend.automations(&Param::new(&delay, ParamName::DelayTime)),
+            vec![Automation::Value(3.0)]
+        );
+    }
+
+    #[test]
+    fn distortion_curve_is_rebuilt_only_when_the_amount_changes() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+        let graph = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Distortion,
+        ]);
+        engine
+            .interpret_graph(BPM, &graph, &audio_config)
+            .expect("Graph should play");
+        let mut effects = graph.audio_effects();
+        let mixes = graph.effect_mixes();
+
+        engine.backend().clear_events();
+        engine
+            .update_effects(BPM, &effects, &mixes)
+            .expect("Effects should update");
+        let AudioEffect::Distortion(params) = &mut effects[0] else {
+            panic!("Graph should have a distortion");
+        };
+        params.amount /= 2.0;
+        engine
+            .update_effects(BPM, &effects, &mixes)
+            .expect("Effects should update");
+
+        let curves = engine
+            .backend()
+            .events()
+            .into_iter()
+            .filter(|event| matches!(event, BackendEvent::SetCurve(..)))
+            .count();
+        assert_eq!(curves, 1);
+    }
+
+    #[test]
+    fn vibrato_lfo_is_only_built_by_the_sequence() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+        let graph = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Lfo(LfoPreset::SlowSine),
+            CardType::Oscillator(WaveShape::Sine),
+        ]);
+
+        engine
+            .interpret_graph(BPM, &graph, &audio_config)
+            .expect("Graph should play");
+
+        assert_eq!(engine.backend().nodes_of(NodeKind::Oscillator).len(), 1);
+        assert_eq!(oscillator_starts(engine.backend()), vec![0.0]);
+    }
+
+    #[test]
+    fn notes_are_scheduled_once_within_the_look_ahead_and_wrap_around_the_loop() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+        let origin = 0.3;
+        let look_ahead = 0.1;
+        engine.backend().set_current_time(origin);
+        engine
+            .interpret_graph(BPM, &filtered_sine(), &audio_config)
+            .expect("Graph should play");
+
+        let mut scheduled = vec![];
+        for frame in 0..75 {
+            let now = origin + frame as GameTime / 60.0;
+            let backend = engine.backend();
+            backend.set_current_time(now);
+            backend.clear_events();
+            engine
+                .schedule_notes(look_ahead, &audio_config)
+                .expect("Notes should be scheduled");
+            for start in oscillator_starts(engine.backend()) {
+                assert!(
+                    now <= start && start < now + look_ahead,
+                    "note at {start} scheduled at {now}"
+                );
+                scheduled.push(start);
+            }
+        }
+
+        // One note per half second loop, the last frame looks ahead to 1.633
+        let expected = [origin, origin + 0.5, origin + 1.0];
+        assert_eq!(scheduled.len(), expected.len());
+        for (start, expected) in scheduled.iter().zip(expected) {
+            assert!((start - expected).abs() < 1e-9, "{start} != {expected}");
+        }
+    }
+
+    #[test]
+    fn a_note_past_the_polyphony_steals_the_oldest_voice() {
+        let audio_config = AudioConfig {
+            max_polyphony: 2,
+            ..audio_config()
+        };
+        let mut engine = engine(&audio_config);
+        // Half notes an eighth apart, so all three overlap
+        let eighth: MusicTime = NoteDuration::Eighth.into();
+        let notes = [NoteName::C, NoteName::E, NoteName::G]
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                NoteEvent::new(
+                    Note::new(3, name),
+                    eighth * i as u32,
+                    NoteDuration::Half.into(),
+                )
+            })
+            .collect();
+        let graph = AudioGraph::new(
+            vec![NoteGenerator::new(NoteDuration::Whole.into(), notes)],
+            Oscillator::Wave(WaveShape::Sine),
+            vec![],
+        );
+
+        engine
+            .interpret_graph(BPM, &graph, &audio_config)
+            .expect("Graph should play");
+        engine
+            .schedule_notes(0.6, &audio_config)
+            .expect("Notes should be scheduled");
+
+        let backend = engine.backend();
+        let third_start = eighth.to_seconds(BPM) * 2.0;
+        let first = voice_gains(backend)[0];
+        let automations = gain_automations(backend, first);
+        assert_eq!(
+            automations[automations.len() - 2..],
+            [
+                Automation::Cancel(third_start),
+                Automation::Target {
+                    value: 0.0,
+                    start: third_start,
+                    time_constant: audio_config.steal_fade / 5.0,
+                },
+            ]
+        );
+        for gain in &voice_gains(backend)[1..] {
+            assert!(!gain_automations(backend, *gain).contains(&Automation::Cancel(third_start)));
+        }
+        let stats = engine.voice_stats();
+        assert_eq!(stats.steals, 1);
+        assert_eq!(stats.voices, 2);
+    }
+
+    #[test]
+    fn unison_output_is_disconnected_once_the_note_is_silent() {
+        let audio_config = audio_config();
+        let mut engine = engine(&audio_config);
+        let graph = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Unison(UnisonVoices::Two),
+        ]);
+        engine
+            .interpret_graph(BPM, &graph, &audio_config)
+            .expect("Graph should play");
+        engine
+            .schedule_notes(0.1, &audio_config)
+            .expect("Notes should be scheduled");
+        let backend = engine.backend();
+        let panner = successors(backend, voice_gains(backend)[0])[0];
+        let output = successors(backend, panner)[0];
+        let disconnected = |backend: &RecordingBackend| {
+            backend.events().contains(&BackendEvent::Disconnect(output))
+        };
+
+        // The note lasts until 0.502, its release included
+        backend.set_current_time(0.4);
+        engine
+            .schedule_notes(0.1, &audio_config)
+            .expect("Notes should be scheduled");
+        assert!(!disconnected(engine.backend()));
+        engine.backend().set_current_time(0.6);
+        engine
+            .schedule_notes(0.1, &audio_config)
+            .expect("Notes should be scheduled");
+        assert!(disconnected(engine.backend()));
+    }
+
+    #[test]
+    fn swapped_in_lane_fades_in_and_starts_its_lfos_at_the_boundary() {
+        let audio_config = audio_config();
+        let playing = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Chorus,
+        ]);
+        let next = graph(vec![
+            CardType::NoteGenerator(Some(NoteName::C)),
+            CardType::Oscillator(WaveShape::Sine),
+            CardType::Phaser,
+            CardType::AutoPan(AutoPanRate::Bar),
+        ]);
+        // A beat and a loop both last half a second, a bar two seconds
+        for (quantization, at) in [
+            (SwapQuantization::Beat, 0.5),
+            (SwapQuantization::Bar, 2.0),
+            (SwapQuantization::Loop, 0.5),
+        ] {
+            let mut engine = engine(&audio_config);
+            engine
+                .interpret_graph(BPM, &playing, &audio_config)
+                .expect("Graph should play");
+            engine.backend().set_current_time(0.3);
+            engine.backend().clear_events();
+
+            engine
+                .swap_graph(BPM, &next, &audio_config, quantization, false)
+                .expect("Graph should swap");
+
+            let backend = engine.backend();
+            let fader = engine.lanes.last().expect("Swap should add a lane").fader;
+            assert_eq!(
+                gain_automations(backend, fader),
+                vec![
+                    Automation::ValueAtTime(0.0, at),
+                    Automation::LinearRamp(1.0, at + audio_config.swap_crossfade),
+                ],
+                "{quantization:?}"
+            );
+            let lfo_starts = oscillator_starts(backend);
+            assert!(!lfo_starts.is_empty());
+            assert!(
+                lfo_starts.iter().all(|start| *start == at),
+                "{quantization:?} LFOs start at {lfo_starts:?}"
+            );
+        }
     }
 }
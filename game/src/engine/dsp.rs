@@ -0,0 +1,231 @@
+use std::f32::consts::PI;
+
+use crate::nodes::audio_effect::CompressorParameters;
+use crate::nodes::audio_effect::FilterParameters;
+use crate::nodes::audio_effect::FilterType;
+
+/// Mirrors `StereoPannerNode` on a single frame: a mono input is spread with equal power,
+/// a stereo input moves the opposite channel over to the side the pan points to
+pub fn pan_frame(frame: &[f32], pan: f32) -> [f32; 2] {
+    let pan = pan.clamp(-1.0, 1.0);
+    match frame {
+        [mono] => {
+            let x = (pan + 1.0) / 2.0 * PI / 2.0;
+            [mono * x.cos(), mono * x.sin()]
+        }
+        [left, right, ..] if pan <= 0.0 => {
+            let x = (pan + 1.0) * PI / 2.0;
+            [left + right * x.cos(), right * x.sin()]
+        }
+        [left, right, ..] => {
+            let x = pan * PI / 2.0;
+            [left * x.cos(), right + left * x.sin()]
+        }
+        [] => [0.0, 0.0],
+    }
+}
+
+/// Gain a `ConvolverNode` applies to a normalized impulse response, calibrated so responses
+/// of any power and sample rate sound about equally loud
+pub fn convolver_normalization(impulse: &[f32], sample_rate: f32) -> f32 {
+    const GAIN_CALIBRATION: f32 = 0.00125;
+    const GAIN_CALIBRATION_SAMPLE_RATE: f32 = 44100.0;
+    const MIN_POWER: f32 = 0.000125;
+
+    let power = (impulse.iter().map(|x| x * x).sum::<f32>() / impulse.len() as f32).sqrt();
+    let power = if power.is_finite() {
+        power.max(MIN_POWER)
+    } else {
+        MIN_POWER
+    };
+    GAIN_CALIBRATION_SAMPLE_RATE / sample_rate * GAIN_CALIBRATION / power
+}
+
+pub fn read_cycle(cycle: &[f32], phase: f32) -> f32 {
+    let position = phase * cycle.len() as f32;
+    let index = position as usize % cycle.len();
+    let next = (index + 1) % cycle.len();
+    let fraction = position.fract();
+    cycle[index] * (1.0 - fraction) + cycle[next] * fraction
+}
+
+/// Reads `curve` the way `WaveShaperNode` does, the curve needs at least two points
+pub fn shape_sample(curve: &[f32], x: f32) -> f32 {
+    let x = x.clamp(-1.0, 1.0);
+    let position = (curve.len() - 1) as f32 * (x + 1.0) / 2.0;
+    let index = (position as usize).min(curve.len() - 2);
+    let fraction = position - index as f32;
+    curve[index] * (1.0 - fraction) + curve[index + 1] * fraction
+}
+
+/// Approximates `DynamicsCompressorNode`: the level in dB follows the signal peaks
+/// with the attack and release times and is mapped through the compressor curve.
+/// Like Web Audio the loudest channel sets the gain of all of them
+// NOTE: unlike Web Audio there is no look-ahead, so the output isn't delayed
+pub struct Compressor {
+    level: f32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self { level: -100.0 }
+    }
+}
+
+impl Compressor {
+    /// Keeps the level between calls, so a signal can be compressed block by block
+    pub fn process(
+        &mut self,
+        params: &CompressorParameters,
+        channels: &mut [Vec<f32>],
+        sample_rate: f32,
+    ) {
+        let attack = (-1.0 / (params.attack.max(0.0001) * sample_rate)).exp();
+        let release = (-1.0 / (params.release.max(0.0001) * sample_rate)).exp();
+        let makeup_gain = params.makeup_gain();
+
+        for i in 0..channels.first().map_or(0, Vec::len) {
+            let peak = channels
+                .iter()
+                .map(|samples| samples[i].abs())
+                .fold(0.0, f32::max);
+            let input_level = 20.0 * peak.max(0.00001).log10();
+            let coefficient = if input_level > self.level {
+                attack
+            } else {
+                release
+            };
+            self.level = coefficient * self.level + (1.0 - coefficient) * input_level;
+            let reduction = params.output_level(self.level) - self.level;
+            let gain = 10f32.powf(reduction / 20.0) * makeup_gain;
+            for samples in channels.iter_mut() {
+                samples[i] *= gain;
+            }
+        }
+    }
+}
+
+/// Biquad filter using the coefficient formulas from the Web Audio specification
+#[derive(Default)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    pub fn new(params: &FilterParameters, sample_rate: f32) -> Self {
+        let mut biquad = Self::default();
+        biquad.set_coefficients(params, sample_rate);
+        biquad
+    }
+
+    /// Keeps the filter state, so coefficients can change while processing
+    pub fn set_coefficients(&mut self, params: &FilterParameters, sample_rate: f32) {
+        let w0 = 2.0 * PI * params.frequency / sample_rate;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha_q = sin_w0 / (2.0 * params.q);
+        // NOTE: Web Audio interprets Q of lowpass and highpass filters in dB
+        let alpha_q_db = sin_w0 / (2.0 * 10f32.powf(params.q / 20.0));
+        let a = 10f32.powf(params.gain / 40.0);
+        let sqrt_a = a.sqrt();
+        // Shelves have a fixed slope of 1
+        let alpha_s = sin_w0 / 2.0 * 2f32.sqrt();
+
+        let (b0, b1, b2, a0, a1, a2) = match params.filter_type {
+            FilterType::LowPass => (
+                (1.0 - cos_w0) / 2.0,
+                1.0 - cos_w0,
+                (1.0 - cos_w0) / 2.0,
+                1.0 + alpha_q_db,
+                -2.0 * cos_w0,
+                1.0 - alpha_q_db,
+            ),
+            FilterType::HighPass => (
+                (1.0 + cos_w0) / 2.0,
+                -(1.0 + cos_w0),
+                (1.0 + cos_w0) / 2.0,
+                1.0 + alpha_q_db,
+                -2.0 * cos_w0,
+                1.0 - alpha_q_db,
+            ),
+            FilterType::Notch => (
+                1.0,
+                -2.0 * cos_w0,
+                1.0,
+                1.0 + alpha_q,
+                -2.0 * cos_w0,
+                1.0 - alpha_q,
+            ),
+            FilterType::AllPass => (
+                1.0 - alpha_q,
+                -2.0 * cos_w0,
+                1.0 + alpha_q,
+                1.0 + alpha_q,
+                -2.0 * cos_w0,
+                1.0 - alpha_q,
+            ),
+            FilterType::BandPass => (
+                alpha_q,
+                0.0,
+                -alpha_q,
+                1.0 + alpha_q,
+                -2.0 * cos_w0,
+                1.0 - alpha_q,
+            ),
+            FilterType::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * alpha_s * sqrt_a),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * alpha_s * sqrt_a),
+                (a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * alpha_s * sqrt_a,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                (a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * alpha_s * sqrt_a,
+            ),
+            FilterType::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 + 2.0 * alpha_s * sqrt_a),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 - 2.0 * alpha_s * sqrt_a),
+                (a + 1.0) - (a - 1.0) * cos_w0 + 2.0 * alpha_s * sqrt_a,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                (a + 1.0) - (a - 1.0) * cos_w0 - 2.0 * alpha_s * sqrt_a,
+            ),
+            FilterType::Peaking => (
+                1.0 + alpha_q * a,
+                -2.0 * cos_w0,
+                1.0 - alpha_q * a,
+                1.0 + alpha_q / a,
+                -2.0 * cos_w0,
+                1.0 - alpha_q / a,
+            ),
+        };
+
+        self.b0 = b0 / a0;
+        self.b1 = b1 / a0;
+        self.b2 = b2 / a0;
+        self.a1 = a1 / a0;
+        self.a2 = a2 / a0;
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+
+    pub fn process_sample(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsValue;
 
 #[allow(dead_code)]
@@ -17,6 +18,7 @@ impl GameError {
     pub fn msg(message: &'static str) -> GameError {
         GameError::new(message, None)
     }
+    #[cfg(target_arch = "wasm32")]
     pub fn js(message: &'static str) -> impl Fn(JsValue) -> GameError {
         move |payload: JsValue| -> GameError {
             GameError {
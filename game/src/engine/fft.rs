@@ -0,0 +1,230 @@
+use std::f32::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// In-place radix-2 FFT, the length of `values` must be a power of two
+pub fn fft(values: &mut [Complex]) {
+    transform(values, false);
+}
+
+/// Inverse of `fft`, scaled so `ifft(fft(x)) == x`
+pub fn ifft(values: &mut [Complex]) {
+    transform(values, true);
+    let scale = 1.0 / values.len() as f32;
+    for value in values.iter_mut() {
+        value.re *= scale;
+        value.im *= scale;
+    }
+}
+
+fn transform(values: &mut [Complex], inverse: bool) {
+    let n = values.len();
+    assert!(n.is_power_of_two(), "FFT length must be a power of two");
+
+    // Bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let twiddles: Vec<Complex> = (0..n / 2)
+        .map(|k| {
+            let angle = sign * 2.0 * PI * k as f32 / n as f32;
+            Complex::new(angle.cos(), angle.sin())
+        })
+        .collect();
+
+    let mut len = 2;
+    while len <= n {
+        let stride = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let twiddle = twiddles[k * stride];
+                let even = values[start + k];
+                let odd = values[start + k + len / 2] * twiddle;
+                values[start + k] = even + odd;
+                values[start + k + len / 2] = even - odd;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Hann window of `len` samples, tapers a block to zero at both ends so its spectrum
+/// doesn't leak into every bin
+pub fn hann_window(len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / len as f32).cos())
+        .collect()
+}
+
+/// Linear convolution of `signal` with `kernel`, truncated to the length of `signal`
+pub fn convolve(signal: &[f32], kernel: &[f32]) -> Vec<f32> {
+    if signal.is_empty() || kernel.is_empty() {
+        return vec![0.0; signal.len()];
+    }
+    let n = (signal.len() + kernel.len() - 1).next_power_of_two();
+    let padded = |samples: &[f32]| -> Vec<Complex> {
+        let mut values: Vec<Complex> = samples.iter().map(|&x| Complex::new(x, 0.0)).collect();
+        values.resize(n, Complex::default());
+        values
+    };
+
+    let mut signal_spectrum = padded(signal);
+    let mut kernel_spectrum = padded(kernel);
+    fft(&mut signal_spectrum);
+    fft(&mut kernel_spectrum);
+    for (s, k) in signal_spectrum.iter_mut().zip(&kernel_spectrum) {
+        *s = *s * *k;
+    }
+    ifft(&mut signal_spectrum);
+
+    signal_spectrum
+        .into_iter()
+        .take(signal.len())
+        .map(|value| value.re)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Random;
+
+    fn noise(len: usize, seed: u64) -> Vec<f32> {
+        let mut random = Random::new(seed);
+        (0..len).map(|_| random.next_f32() * 2.0 - 1.0).collect()
+    }
+
+    /// Textbook O(n * m) convolution, truncated like `convolve`
+    fn direct_convolution(signal: &[f32], kernel: &[f32]) -> Vec<f32> {
+        (0..signal.len())
+            .map(|i| {
+                kernel
+                    .iter()
+                    .enumerate()
+                    .take(i + 1)
+                    .map(|(j, k)| signal[i - j] * k)
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sine_peaks_in_its_bin_only() {
+        let n = 256;
+        let bin = 16;
+        let mut values: Vec<Complex> = (0..n)
+            .map(|i| Complex::new((2.0 * PI * (bin * i) as f32 / n as f32).sin(), 0.0))
+            .collect();
+
+        fft(&mut values);
+
+        for (k, value) in values.iter().enumerate() {
+            let magnitude = value.magnitude();
+            if k == bin || k == n - bin {
+                assert!(
+                    (magnitude - n as f32 / 2.0).abs() < 1e-3,
+                    "bin {k}: {magnitude}"
+                );
+            } else {
+                assert!(magnitude < 1e-3, "bin {k}: {magnitude}");
+            }
+        }
+    }
+
+    #[test]
+    fn ifft_undoes_fft() {
+        let signal = noise(64, 1);
+        let mut values: Vec<Complex> = signal.iter().map(|&x| Complex::new(x, 0.0)).collect();
+
+        fft(&mut values);
+        ifft(&mut values);
+
+        for (value, expected) in values.iter().zip(&signal) {
+            assert!((value.re - expected).abs() < 1e-5);
+            assert!(value.im.abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn convolve_matches_direct_convolution() {
+        let signal = noise(300, 2);
+        let kernel = noise(37, 3);
+
+        let fast = convolve(&signal, &kernel);
+
+        let direct = direct_convolution(&signal, &kernel);
+        assert_eq!(fast.len(), signal.len());
+        for (i, (fast, direct)) in fast.iter().zip(&direct).enumerate() {
+            assert!(
+                (fast - direct).abs() < 1e-4,
+                "sample {i}: {fast} != {direct}"
+            );
+        }
+    }
+
+    #[test]
+    fn convolving_with_an_impulse_delays_the_signal() {
+        let signal = noise(100, 4);
+        let kernel = [0.0, 0.0, 0.0, 1.0];
+
+        let delayed = convolve(&signal, &kernel);
+
+        assert!(delayed[..3].iter().all(|x| x.abs() < 1e-5));
+        for (delayed, original) in delayed[3..].iter().zip(&signal) {
+            assert!((delayed - original).abs() < 1e-5);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use macroquad::color::Color;
 use macroquad::math::Vec2;
 
+use crate::nodes::audio_effect::CompressorParameters;
 use crate::render::widgets::card_widget::CardType;
 
 #[derive(Clone)]
@@ -8,10 +9,13 @@ pub struct GameConfig {
     pub card_height: f32,
     pub card_aspect_ratio: f32, // width / height
     pub bpm: u32,
+    /// How far ahead of the audio clock notes are turned into voices
     pub look_ahead_secs: f64,
     pub initial_deck: Vec<CardType>,
     pub graph_widget: GraphWidgetConfig,
     pub cards_widget: CardsRowWidgetConfig,
+    pub analyser_widget: AnalyserWidgetConfig,
+    pub card_parameters_widget: CardParametersWidgetConfig,
     pub debug_hud: Option<DebugHudConfig>,
     pub audio: AudioConfig,
     pub card_colors: CardColorConfig,
@@ -22,7 +26,10 @@ pub struct CardColorConfig {
     pub note_generator: Color,
     pub note_effect: Color,
     pub oscillator: Color,
+    pub envelope: Color,
     pub audio_effect: Color,
+    pub lfo: Color,
+    pub voice: Color,
 }
 
 #[derive(Clone)]
@@ -37,6 +44,18 @@ pub struct CardsRowWidgetConfig {
     pub size: Vec2,
 }
 
+#[derive(Clone)]
+pub struct AnalyserWidgetConfig {
+    pub location: Vec2,
+    pub size: Vec2,
+}
+
+#[derive(Clone)]
+pub struct CardParametersWidgetConfig {
+    pub location: Vec2,
+    pub size: Vec2,
+}
+
 #[derive(Clone)]
 pub struct DebugHudConfig {
     pub buffer_size: usize,
@@ -46,6 +65,17 @@ pub struct DebugHudConfig {
 pub struct AudioConfig {
     pub attack_time: f64,
     pub release_time: f64,
-    pub max_schedule_ahead: f64,
     pub output_gain: f32,
+    /// Time constant of live param changes, short enough to feel instant without clicks
+    pub param_smoothing: f64,
+    /// Most voices playing at once, the oldest note is stolen to make room for a new one
+    pub max_polyphony: usize,
+    /// Fade out of a stolen voice before its sources stop
+    pub steal_fade: f64,
+    /// Fade out of the voices and effects of a graph another one took over from
+    pub swap_crossfade: f64,
+    /// Compressor on the mix of all lanes, before the limiter
+    pub master_compressor: CompressorParameters,
+    /// Level in dB the limiter at the end of the master bus never lets the output exceed
+    pub limiter_ceiling: f32,
 }
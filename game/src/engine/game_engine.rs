@@ -19,7 +19,10 @@ use miniquad::MouseButton;
 
 use crate::debug::hud::DebugHud;
 use crate::nodes::audio_graph::AudioGraph;
+use crate::render::hover::Hover;
+use crate::render::widgets::analyser_widget::AnalyserWidget;
 use crate::render::widgets::audio_graph_widget::AudioGraphWidget;
+use crate::render::widgets::card_parameters_widget::CardParametersWidget;
 use crate::render::widgets::cards_row_widget::CardsRowWidget;
 use crate::render::widgets::error_popup_widget::ErrorPopupWidget;
 use crate::render::widgets::piece_library_widget::PieceLibraryWidget;
@@ -28,7 +31,9 @@ use crate::render::Render;
 use crate::render::RenderCtx;
 use crate::render::{drag_manager::DragManager, draggable_card_buffer::DraggableCardBuffer};
 
+use super::audio_backend::PlatformBackend;
 use super::audio_engine::AudioEngine;
+use super::audio_engine::SwapQuantization;
 use super::errors::GameResult;
 use super::game_config::GameConfig;
 use super::game_settings::GameSettings;
@@ -37,15 +42,21 @@ use super::game_state::GameState;
 use super::scheduler::Scheduler;
 use super::ton_wallet::{PieceData, TonWallet};
 
+/// Mouse travel below which a press and release on a card selects it instead of dragging
+const CLICK_DISTANCE: f32 = 0.01;
+
 pub struct GameEngine {
     state: RefCell<GameState>,
     render_ctx: RenderCtx,
     config: GameConfig,
-    audio_engine: RefCell<AudioEngine>,
+    audio_engine: RefCell<AudioEngine<PlatformBackend>>,
     drag_manager: DragManager,
     audio_scheduler: Scheduler,
     audio_graph_widget: AudioGraphWidget,
     cards_row_widget: CardsRowWidget,
+    card_parameters_widget: CardParametersWidget,
+    analyser_widget: AnalyserWidget,
+    press_position: Option<Vec2>,
     debug_hud: Option<RefCell<DebugHud>>,
     settings_widget: SettingsWidget,
     piece_library_widget: PieceLibraryWidget,
@@ -54,7 +65,7 @@ pub struct GameEngine {
 }
 
 impl GameEngine {
-    pub fn new(render_ctx: RenderCtx, config: GameConfig) -> GameResult<Self> {
+    pub async fn new(render_ctx: RenderCtx, config: GameConfig) -> GameResult<Self> {
         let settings = GameSettings::default();
         let state = GameState::new(config.initial_deck.clone());
 
@@ -77,12 +88,24 @@ impl GameEngine {
             state.card_deck.clone(),
             config.card_colors.clone(),
         );
+        let analyser_widget =
+            AnalyserWidget::new(config.analyser_widget.location, config.analyser_widget.size);
+        let card_parameters_widget = CardParametersWidget::new(
+            config.card_parameters_widget.location,
+            config.card_parameters_widget.size,
+        );
         let debug_hud = config
             .debug_hud
             .clone()
             .map(|ref h| RefCell::new(DebugHud::new(h.buffer_size)));
 
-        let audio_engine = AudioEngine::new()?;
+        #[cfg(target_arch = "wasm32")]
+        let backend = PlatformBackend::new()?;
+        #[cfg(not(target_arch = "wasm32"))]
+        let backend = PlatformBackend::from_env()?;
+        let audio_engine = AudioEngine::new(backend, render_ctx.samples().clone(), &config.audio)?;
+        #[cfg(target_arch = "wasm32")]
+        audio_engine.load_worklets().await;
         let settings_widget = SettingsWidget::from_settings(settings);
         let piece_library_widget = PieceLibraryWidget::new();
         let error_popup_widget = ErrorPopupWidget::new();
@@ -96,6 +119,9 @@ impl GameEngine {
             audio_scheduler: Scheduler::new(),
             audio_graph_widget,
             cards_row_widget,
+            card_parameters_widget,
+            analyser_widget,
+            press_position: None,
             debug_hud,
             settings_widget,
             piece_library_widget,
@@ -105,9 +131,16 @@ impl GameEngine {
     }
 
     pub async fn update(&mut self) -> GameResult<()> {
-        self.update_state();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.audio_engine.borrow().backend().render_realtime()?;
+        self.update_state()?;
         self.handle_input().await?;
         self.process_events()?;
+        self.audio_engine
+            .borrow_mut()
+            .schedule_notes(self.config.look_ahead_secs, &self.config.audio)?;
+        self.audio_graph_widget
+            .set_playhead(self.audio_engine.borrow().playhead());
         self.render()?;
 
         Ok(())
@@ -120,9 +153,18 @@ impl GameEngine {
         self.cards_row_widget.render(render_ctx)?;
         self.audio_graph_widget.render(render_ctx)?;
         self.drag_manager.render(render_ctx)?;
+        self.card_parameters_widget.render(render_ctx)?;
+
+        if self.settings_widget.settings.borrow().show_analyser {
+            self.analyser_widget
+                .set_analysis(self.audio_engine.borrow().analysis()?);
+            self.analyser_widget.render(render_ctx)?;
+        }
 
         if let Some(debug_hud) = &self.debug_hud {
-            debug_hud.borrow().render(render_ctx)?;
+            let debug_hud = debug_hud.borrow();
+            debug_hud.set_voice_stats(self.audio_engine.borrow().voice_stats());
+            debug_hud.render(render_ctx)?;
         }
 
         self.settings_widget.render(render_ctx)?;
@@ -150,7 +192,7 @@ impl GameEngine {
         Ok(())
     }
 
-    fn update_state(&mut self) {
+    fn update_state(&mut self) -> GameResult<()> {
         let screen_w = screen_width();
         let screen_h = screen_height();
         self.render_ctx.screen_size = vec2(screen_w, screen_h);
@@ -178,7 +220,9 @@ impl GameEngine {
         settings.is_connected = ton_wallet.is_connected();
 
         let vol = settings.volume;
-        self.audio_engine.borrow().set_volume(vol);
+        self.audio_engine.borrow().set_volume(vol)?;
+        // NOTE: the graph is the only lane for now
+        self.audio_engine.borrow().set_lane_pan(0, settings.pan)
     }
 
     async fn handle_input(&mut self) -> GameResult<()> {
@@ -217,7 +261,7 @@ impl GameEngine {
             let wallet = self.ton_wallet.borrow();
             if let Some(cards) = wallet.get_piece_cards(&address) {
                 // Load the new cards into the audio graph widget
-                self.audio_graph_widget.set_cards(cards.clone());
+                self.audio_graph_widget.set_piece_cards(cards);
 
                 // Update game state to track the remix source
                 self.state.borrow_mut().remixed_from_address = Some(address);
@@ -246,10 +290,30 @@ impl GameEngine {
             self.audio_scheduler.schedule(GameEvent::UpdateGraph, None);
         }
 
+        if let Some((i, parameters, mix)) = self.card_parameters_widget.handle_change() {
+            self.audio_graph_widget
+                .set_card_parameters(i, parameters, mix);
+            self.audio_scheduler
+                .schedule(GameEvent::UpdateParameters, None);
+        }
+
+        // NOTE: the sliders of the parameters widget take the mouse while it's over them
+        let over_parameters = self.card_parameters_widget.is_hovered_over(mouse_pos);
+
+        // Right click bypasses an effect, the playing graph follows without restarting
+        if is_mouse_button_pressed(MouseButton::Right)
+            && !over_parameters
+            && self.audio_graph_widget.toggle_bypass_at(mouse_pos)
+        {
+            self.audio_scheduler
+                .schedule(GameEvent::UpdateParameters, None);
+        }
+
         let mut buffers: Vec<&mut dyn DraggableCardBuffer> =
             vec![&mut self.cards_row_widget, &mut self.audio_graph_widget];
 
-        if is_mouse_button_pressed(MouseButton::Left) {
+        if is_mouse_button_pressed(MouseButton::Left) && !over_parameters {
+            self.press_position = Some(mouse_pos);
             self.drag_manager
                 .handle_mouse_press(mouse_pos, &mut buffers);
         }
@@ -259,9 +323,18 @@ impl GameEngine {
         }
 
         if is_mouse_button_released(MouseButton::Left) {
-            self.drag_manager.handle_mouse_release(&mut buffers);
-            self.audio_scheduler.schedule(GameEvent::UpdateGraph, None)
+            if let Some(press_position) = self.press_position.take() {
+                self.drag_manager.handle_mouse_release(&mut buffers);
+                if press_position.distance(mouse_pos) < CLICK_DISTANCE {
+                    self.audio_graph_widget.select_card_at(mouse_pos);
+                } else {
+                    self.audio_graph_widget.deselect();
+                }
+                self.audio_scheduler.schedule(GameEvent::UpdateGraph, None)
+            }
         }
+        self.card_parameters_widget
+            .show(self.audio_graph_widget.selected_card());
 
         let is_playing = self.audio_engine.borrow().is_playing();
         let is_different = self.state.borrow().current_graph != self.state.borrow().playing_graph;
@@ -276,6 +349,16 @@ impl GameEngine {
                 .schedule(GameEvent::StopAudioGraph, None);
         }
 
+        // M mutes and S solos the card under the mouse, the playing graph follows
+        let toggled_playback = !any_window_opened
+            && ((is_key_pressed(KeyCode::M) && self.audio_graph_widget.toggle_mute_at(mouse_pos))
+                || (is_key_pressed(KeyCode::S)
+                    && self.audio_graph_widget.toggle_solo_at(mouse_pos)));
+        if toggled_playback {
+            self.audio_scheduler
+                .schedule(GameEvent::UpdateParameters, None);
+        }
+
         if self.settings_widget.handle_create_piece() {
             let state = self.state.borrow();
             if state.current_graph.is_none() {
@@ -283,17 +366,12 @@ impl GameEngine {
                     "Invalid audio graph. A valid graph needs at least one note generator and one oscillator.".to_string(),
                 );
             } else {
-                let cards_to_save = self
-                    .audio_graph_widget
-                    .cards()
-                    .iter()
-                    .map(|card| card.borrow().card_type())
-                    .collect::<Vec<_>>();
+                let piece_cards = self.audio_graph_widget.piece_cards();
 
                 let piece_name = self.settings_widget.settings.borrow().piece_name.clone();
 
                 let piece_metadata = PieceData {
-                    version: 1,
+                    version: 3,
                     name: if piece_name.is_empty() {
                         "Untitled Piece".to_string()
                     } else {
@@ -301,7 +379,9 @@ impl GameEngine {
                     },
                     created_at: get_time() as u64,
                     bpm: self.config.bpm,
-                    cards: cards_to_save,
+                    cards: piece_cards.iter().map(|card| card.card_type).collect(),
+                    parameters: piece_cards.iter().map(|card| card.parameters).collect(),
+                    mixes: piece_cards.iter().map(|card| card.mix).collect(),
                 };
 
                 let piece_data_str = TonWallet::serialize_piece_data(&piece_metadata);
@@ -344,12 +424,7 @@ impl GameEngine {
                     )?;
 
                     // Get the current cards from the audio graph widget
-                    let current_cards = self
-                        .audio_graph_widget
-                        .cards()
-                        .iter()
-                        .map(|card| card.borrow().card_type())
-                        .collect();
+                    let current_cards = self.audio_graph_widget.piece_cards();
 
                     let mut state = self.state.borrow_mut();
                     state.playing_graph = Some(audio_graph.clone());
@@ -365,15 +440,71 @@ impl GameEngine {
                 Ok(vec![])
             }
             GameEvent::UpdateGraph => {
-                let card_types = self
-                    .audio_graph_widget
-                    .cards()
-                    .iter()
-                    .map(|card| card.borrow().card_type())
-                    .collect();
-                self.state.borrow_mut().current_graph = AudioGraph::from_cards(card_types).clone();
+                self.state.borrow_mut().current_graph = self.edited_graph();
+                self.swap_playing_graph()?;
+                Ok(vec![])
+            }
+            GameEvent::UpdateParameters => {
+                let current_graph = self.edited_graph();
+                let mut state = self.state.borrow_mut();
+                state.current_graph = current_graph.clone();
+
+                // Only effect parameters changed, the playing nodes follow them without
+                // restarting the piece
+                let is_playing = self.audio_engine.borrow().is_playing();
+                if let (Some(current), Some(playing)) = (&current_graph, &state.playing_graph) {
+                    if is_playing && current.has_same_layout(playing) {
+                        self.audio_engine.borrow().update_effects(
+                            self.config.bpm,
+                            &current.audio_effects(),
+                            &current.effect_mixes(),
+                        )?;
+                        state.playing_graph = current_graph;
+                        state.playing_cards = Some(self.audio_graph_widget.piece_cards());
+                    }
+                }
+                drop(state);
+                self.swap_playing_graph()?;
                 Ok(vec![])
             }
         }
     }
+
+    /// Graph of the cards on the board, played with their mutes and solos
+    fn edited_graph(&self) -> Option<AudioGraph> {
+        let piece_cards = self.audio_graph_widget.piece_cards();
+        AudioGraph::from_cards(piece_cards)
+            .map(|graph| graph.with_playback(self.audio_graph_widget.card_playback()))
+    }
+
+    /// Hands playback over to the edited graph at the next boundary set in the settings,
+    /// an invalid graph leaves the playing one as it is
+    fn swap_playing_graph(&self) -> GameResult<()> {
+        if !self.audio_engine.borrow().is_playing() {
+            return Ok(());
+        }
+        let mut state = self.state.borrow_mut();
+        let (Some(current), Some(playing)) = (&state.current_graph, &state.playing_graph) else {
+            return Ok(());
+        };
+        if current == playing {
+            return Ok(());
+        }
+        // NOTE: mutes and solos are heard right away, only edited cards wait for the boundary
+        let quantization = if current.has_same_cards(playing) {
+            SwapQuantization::Immediate
+        } else {
+            self.settings_widget.settings.borrow().swap_quantization
+        };
+        self.audio_engine.borrow_mut().swap_graph(
+            self.config.bpm,
+            current,
+            &self.config.audio,
+            quantization,
+            current.has_same_effect_chain(playing),
+        )?;
+        state.playing_graph = state.current_graph.clone();
+        state.playing_cards = Some(self.audio_graph_widget.piece_cards());
+        Ok(())
+    }
 }
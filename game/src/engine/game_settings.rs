@@ -1,5 +1,12 @@
+use crate::engine::audio_engine::SwapQuantization;
+
 pub struct GameSettings {
     pub volume: f32,
+    pub pan: f32,
+    /// Boundary an edited graph waits for before it takes over while playing
+    pub swap_quantization: SwapQuantization,
+    /// Shows the oscilloscope and spectrum of the mix
+    pub show_analyser: bool,
     pub vault_address: Option<String>,
     pub registry_address: Option<String>,
     pub wallet_address: Option<String>,
@@ -11,6 +18,9 @@ impl Default for GameSettings {
     fn default() -> Self {
         Self {
             volume: 1.0,
+            pan: 0.0,
+            swap_quantization: SwapQuantization::Bar,
+            show_analyser: false,
             vault_address: None,
             registry_address: None,
             wallet_address: None,
@@ -1,6 +1,7 @@
 use crate::engine::errors::GameResult;
 use crate::nodes::audio_graph::AudioGraph;
 use crate::render::widgets::card_widget::CardType;
+use crate::render::widgets::card_widget::PieceCard;
 use crate::render::Render;
 use crate::render::RenderCtx;
 
@@ -8,7 +9,7 @@ pub struct GameState {
     pub current_graph: Option<AudioGraph>,
     pub playing_graph: Option<AudioGraph>,
     pub card_deck: Vec<CardType>,
-    pub playing_cards: Option<Vec<CardType>>,
+    pub playing_cards: Option<Vec<PieceCard>>,
     pub remixed_from_address: Option<String>,
 }
 
@@ -34,4 +35,5 @@ pub enum GameEvent {
     InterpretGraph,
     StopAudioGraph,
     UpdateGraph,
+    UpdateParameters,
 }
@@ -1,9 +1,16 @@
+pub mod analyser;
+pub mod audio_backend;
 pub mod audio_engine;
 pub mod contract_info;
+pub mod dsp;
 pub mod errors;
+pub mod fft;
 pub mod game_config;
 pub mod game_engine;
 pub mod game_settings;
 pub mod game_state;
 pub mod scheduler;
+#[cfg(test)]
+pub mod test_fixtures;
 pub mod ton_wallet;
+pub mod wav;
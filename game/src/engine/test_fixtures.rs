@@ -0,0 +1,25 @@
+use crate::engine::game_config::AudioConfig;
+use crate::nodes::audio_effect::CompressorPreset;
+use crate::nodes::audio_graph::AudioGraph;
+use crate::render::widgets::card_widget::CardType;
+use crate::render::widgets::card_widget::PieceCard;
+
+/// Short envelopes so notes reach their sustain within a few samples
+pub fn audio_config() -> AudioConfig {
+    AudioConfig {
+        attack_time: 0.001,
+        release_time: 0.002,
+        output_gain: 0.8,
+        param_smoothing: 0.02,
+        max_polyphony: 48,
+        steal_fade: 0.01,
+        swap_crossfade: 0.05,
+        master_compressor: CompressorPreset::Gentle.parameters(),
+        limiter_ceiling: -1.0,
+    }
+}
+
+pub fn graph(cards: Vec<CardType>) -> AudioGraph {
+    let cards = cards.into_iter().map(PieceCard::from).collect();
+    AudioGraph::from_cards(cards).expect("Graph should be valid")
+}
@@ -1,14 +1,23 @@
 use base64;
 use base64::prelude::*;
 use bincode;
+#[cfg(target_arch = "wasm32")]
 use miniquad::info;
 use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::JsFuture;
+#[cfg(target_arch = "wasm32")]
 use web_sys::js_sys::{self, Promise};
 
-use crate::engine::contract_info::{ContractInfo, FeeParams, SecurityParams};
+use crate::engine::contract_info::ContractInfo;
+#[cfg(target_arch = "wasm32")]
+use crate::engine::contract_info::{FeeParams, SecurityParams};
+use crate::nodes::parameters::CardParameters;
+use crate::nodes::parameters::EffectMix;
 use crate::render::widgets::card_widget::CardType;
+use crate::render::widgets::card_widget::PieceCard;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PieceData {
@@ -17,8 +26,76 @@ pub struct PieceData {
     pub created_at: u64,
     pub bpm: u32,
     pub cards: Vec<CardType>,
+    /// Parameters tuned on each card, pieces saved before version 2 have none
+    pub parameters: Vec<Option<CardParameters>>,
+    /// Bypass and wet/dry mix of each card, pieces saved before version 3 have none
+    pub mixes: Vec<EffectMix>,
 }
 
+/// Layout of pieces saved before cards had parameters
+#[derive(Deserialize)]
+struct PieceDataV1 {
+    version: u16,
+    name: String,
+    created_at: u64,
+    bpm: u32,
+    cards: Vec<CardType>,
+}
+
+/// Layout of pieces saved before effect cards had a mix
+#[derive(Deserialize)]
+struct PieceDataV2 {
+    version: u16,
+    name: String,
+    created_at: u64,
+    bpm: u32,
+    cards: Vec<CardType>,
+    parameters: Vec<Option<CardParameters>>,
+}
+
+impl PieceData {
+    pub fn piece_cards(&self) -> Vec<PieceCard> {
+        self.cards
+            .iter()
+            .enumerate()
+            .map(|(i, card_type)| PieceCard {
+                card_type: *card_type,
+                parameters: self.parameters.get(i).copied().flatten(),
+                mix: self.mixes.get(i).copied().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+impl From<PieceDataV1> for PieceData {
+    fn from(data: PieceDataV1) -> Self {
+        PieceData {
+            version: data.version,
+            name: data.name,
+            created_at: data.created_at,
+            bpm: data.bpm,
+            cards: data.cards,
+            parameters: vec![],
+            mixes: vec![],
+        }
+    }
+}
+
+impl From<PieceDataV2> for PieceData {
+    fn from(data: PieceDataV2) -> Self {
+        PieceData {
+            version: data.version,
+            name: data.name,
+            created_at: data.created_at,
+            bpm: data.bpm,
+            cards: data.cards,
+            parameters: data.parameters,
+            mixes: vec![],
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = ["window", "tonBridge"])]
@@ -64,6 +141,7 @@ extern "C" {
     fn clearPendingPieceData();
 }
 
+#[cfg(target_arch = "wasm32")]
 fn parse_contract_info(js_value: &JsValue) -> ContractInfo {
     if js_value.is_undefined() || js_value.is_null() {
         return ContractInfo::default();
@@ -205,6 +283,7 @@ pub struct TonWallet {
 }
 
 impl TonWallet {
+    #[cfg(target_arch = "wasm32")]
     pub fn new() -> Self {
         let connected = isWalletConnected();
         let user_address = if connected { getUserAddress() } else { None };
@@ -228,6 +307,7 @@ impl TonWallet {
         }
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn update(&mut self) {
         self.connected = isWalletConnected();
         self.user_address = if self.connected {
@@ -280,6 +360,7 @@ impl TonWallet {
         }
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn get_piece_addresses(&self) -> Vec<String> {
         if !self.connected {
             return Vec::new();
@@ -300,14 +381,17 @@ impl TonWallet {
         }
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn get_piece_data(&self) -> JsValue {
         getPieceData()
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub async fn refresh_vault_address(&mut self) -> Result<JsValue, JsValue> {
         JsFuture::from(refreshVaultAddress()).await
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn save_audio_graph(&self, data: &str) -> Promise {
         if !self.connected {
             return Promise::resolve(&JsValue::from_bool(false));
@@ -315,6 +399,7 @@ impl TonWallet {
         saveAudioGraph(data)
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn load_audio_graph(&self, address: &str) -> Promise {
         if !self.connected {
             return Promise::resolve(&JsValue::null());
@@ -330,6 +415,7 @@ impl TonWallet {
         &self.transaction_state
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn set_pending_piece_data(&mut self, piece_raw_data: &str, remixed_from: Option<&str>) {
         if !self.connected {
             return;
@@ -349,21 +435,31 @@ impl TonWallet {
             if let Ok(piece_data) = bincode::deserialize::<PieceData>(&bytes) {
                 return Some(piece_data);
             }
+            // NOTE: old pieces end right after the fields they had, so they fail to read
+            // as the current layout
+            if let Ok(piece_data) = bincode::deserialize::<PieceDataV2>(&bytes) {
+                return Some(piece_data.into());
+            }
+            if let Ok(piece_data) = bincode::deserialize::<PieceDataV1>(&bytes) {
+                return Some(piece_data.into());
+            }
         }
         None
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub fn clear_pending_piece_data(&self) {
         clearPendingPieceData();
     }
 
-    pub fn get_piece_cards(&self, piece_address: &str) -> Option<Vec<CardType>> {
+    pub fn get_piece_cards(&self, piece_address: &str) -> Option<Vec<PieceCard>> {
         self.contract_info
             .piece_data_structs
             .get(piece_address)
-            .map(|data| data.cards.clone())
+            .map(|data| data.piece_cards())
     }
 
+    #[cfg(target_arch = "wasm32")]
     pub async fn create_new_piece(
         &self,
         piece_raw_data: &str,
@@ -382,3 +478,30 @@ impl TonWallet {
         result
     }
 }
+
+/// Native builds have no TON bridge, the wallet stays disconnected and pieces are only
+/// played locally
+#[cfg(not(target_arch = "wasm32"))]
+impl TonWallet {
+    pub fn new() -> Self {
+        Self {
+            connected: false,
+            user_address: None,
+            user_vault_address: None,
+            registry_address: None,
+            transaction_state: TransactionState::Idle,
+            transaction_data: None,
+            contract_info: ContractInfo::default(),
+        }
+    }
+
+    pub fn update(&mut self) {}
+
+    pub fn get_piece_addresses(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn set_pending_piece_data(&mut self, _piece_raw_data: &str, _remixed_from: Option<&str>) {}
+
+    pub fn clear_pending_piece_data(&self) {}
+}
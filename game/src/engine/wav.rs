@@ -0,0 +1,287 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Seek;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::SeekFrom;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+
+use crate::engine::errors::GameError;
+use crate::engine::errors::GameResult;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Audio decoded into one buffer of samples in [-1, 1] per channel
+pub struct DecodedAudio {
+    pub sample_rate: f32,
+    pub channels: Vec<Vec<f32>>,
+}
+
+impl DecodedAudio {
+    pub fn frames(&self) -> usize {
+        self.channels.first().map_or(0, |channel| channel.len())
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.frames() as f32 / self.sample_rate
+    }
+
+    /// Average of all channels at `frame`
+    pub fn mono_at(&self, frame: usize) -> f32 {
+        let sum: f32 = self.channels.iter().map(|channel| channel[frame]).sum();
+        sum / self.channels.len() as f32
+    }
+}
+
+struct Format {
+    format: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+/// Decodes RIFF WAVE files with integer PCM (8, 16, 24, 32 bit) or 32 bit float samples
+pub fn decode_wav(bytes: &[u8]) -> GameResult<DecodedAudio> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(GameError::msg("Not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut position = 12;
+    while position + 8 <= bytes.len() {
+        let id = &bytes[position..position + 4];
+        let size = read_u32(bytes, position + 4) as usize;
+        let body_start = position + 8;
+        let body = body_start
+            .checked_add(size)
+            .and_then(|body_end| bytes.get(body_start..body_end))
+            .ok_or(GameError::msg("Truncated WAV chunk"))?;
+        match id {
+            b"fmt " => format = Some(parse_format(body)?),
+            b"data" => data = Some(body),
+            _ => (),
+        }
+        // Chunks are padded to an even size
+        position = body_start + body.len() + size % 2;
+    }
+
+    let format = format.ok_or(GameError::msg("WAV file has no fmt chunk"))?;
+    let data = data.ok_or(GameError::msg("WAV file has no data chunk"))?;
+    let bytes_per_sample = (format.bits_per_sample / 8) as usize;
+    if format.channels == 0 || bytes_per_sample == 0 {
+        return Err(GameError::msg("Unsupported WAV sample format"));
+    }
+    let frame_size = bytes_per_sample * format.channels as usize;
+    let frames = data.len() / frame_size;
+    let mut channels = vec![Vec::with_capacity(frames); format.channels as usize];
+
+    for frame in data.chunks_exact(frame_size) {
+        for (channel, sample) in channels
+            .iter_mut()
+            .zip(frame.chunks_exact(bytes_per_sample))
+        {
+            channel.push(decode_sample(&format, sample)?);
+        }
+    }
+
+    Ok(DecodedAudio {
+        sample_rate: format.sample_rate as f32,
+        channels,
+    })
+}
+
+/// Streams 32 bit float samples to a WAV file. The chunk sizes are patched after every
+/// write, so the file stays readable if the game is killed while recording
+#[cfg(not(target_arch = "wasm32"))]
+pub struct WavWriter {
+    file: File,
+    channels: u16,
+    data_size: u32,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WavWriter {
+    const HEADER_SIZE: u32 = 44;
+
+    pub fn create(path: &str, sample_rate: u32, channels: u16) -> GameResult<Self> {
+        let file = File::create(path).map_err(io_error("Failed to create WAV file"))?;
+        let mut writer = Self {
+            file,
+            channels,
+            data_size: 0,
+        };
+        let block_align = channels * 4;
+        let mut header = Vec::with_capacity(Self::HEADER_SIZE as usize);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&(Self::HEADER_SIZE - 8).to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes());
+        header.extend_from_slice(&FORMAT_FLOAT.to_le_bytes());
+        header.extend_from_slice(&channels.to_le_bytes());
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&32u16.to_le_bytes());
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&0u32.to_le_bytes());
+        writer.write_bytes(&header)?;
+        Ok(writer)
+    }
+
+    /// Appends the frames of one buffer per channel, missing channels are written silent
+    pub fn write(&mut self, channels: &[Vec<f32>]) -> GameResult<()> {
+        let frames = channels.first().map_or(0, Vec::len);
+        let mut data = Vec::with_capacity(frames * self.channels as usize * 4);
+        for frame in 0..frames {
+            for channel in 0..self.channels as usize {
+                let sample = channels.get(channel).map_or(0.0, |samples| samples[frame]);
+                data.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+        self.write_bytes(&data)?;
+        self.data_size += data.len() as u32;
+
+        self.file
+            .seek(SeekFrom::Start(4))
+            .map_err(io_error("Failed to write WAV file"))?;
+        self.write_bytes(&(Self::HEADER_SIZE - 8 + self.data_size).to_le_bytes())?;
+        self.file
+            .seek(SeekFrom::Start(40))
+            .map_err(io_error("Failed to write WAV file"))?;
+        self.write_bytes(&self.data_size.to_le_bytes())?;
+        self.file
+            .seek(SeekFrom::End(0))
+            .map_err(io_error("Failed to write WAV file"))?;
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> GameResult<()> {
+        self.file
+            .write_all(bytes)
+            .map_err(io_error("Failed to write WAV file"))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn io_error(message: &'static str) -> impl Fn(std::io::Error) -> GameError {
+    move |error| GameError::new(message, Some(error.to_string()))
+}
+
+fn parse_format(body: &[u8]) -> GameResult<Format> {
+    if body.len() < 16 {
+        return Err(GameError::msg("WAV fmt chunk is too short"));
+    }
+    let mut format = read_u16(body, 0);
+    // Extensible files store the actual format in the first bytes of the sub format GUID
+    if format == FORMAT_EXTENSIBLE && body.len() >= 26 {
+        format = read_u16(body, 24);
+    }
+    Ok(Format {
+        format,
+        channels: read_u16(body, 2),
+        sample_rate: read_u32(body, 4),
+        bits_per_sample: read_u16(body, 14),
+    })
+}
+
+fn decode_sample(format: &Format, bytes: &[u8]) -> GameResult<f32> {
+    let sample = match (format.format, format.bits_per_sample) {
+        (FORMAT_PCM, 8) => (bytes[0] as f32 - 128.0) / 128.0,
+        (FORMAT_PCM, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+        (FORMAT_PCM, 24) => {
+            // Shift into the top of an i32 so the sign is extended
+            let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+            value as f32 / 8388608.0
+        }
+        (FORMAT_PCM, 32) => {
+            i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2147483648.0
+        }
+        (FORMAT_FLOAT, 32) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        _ => return Err(GameError::msg("Unsupported WAV sample format")),
+    };
+    Ok(sample)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// WAV file with a PCM or float `fmt ` chunk and a `data` chunk holding `data`
+    fn wav(format: u16, channels: u16, bits_per_sample: u16, data: &[u8]) -> Vec<u8> {
+        let block_align = channels * bits_per_sample / 8;
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&format.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&44100u32.to_le_bytes());
+        bytes.extend_from_slice(&(44100 * block_align as u32).to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&bits_per_sample.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn decodes_16_bit_stereo_into_one_buffer_per_channel() {
+        let data: Vec<u8> = [i16::MIN, 16384, 0, i16::MAX]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+
+        let audio = decode_wav(&wav(FORMAT_PCM, 2, 16, &data)).expect("WAV should decode");
+
+        assert_eq!(audio.sample_rate, 44100.0);
+        assert_eq!(audio.channels[0], vec![-1.0, 0.0]);
+        assert_eq!(audio.channels[1], vec![0.5, 32767.0 / 32768.0]);
+    }
+
+    #[test]
+    fn decodes_24_bit_with_the_sign_extended() {
+        let data = [0x00, 0x00, 0x80, 0x00, 0x00, 0x40, 0xFF, 0xFF, 0xFF];
+
+        let audio = decode_wav(&wav(FORMAT_PCM, 1, 24, &data)).expect("WAV should decode");
+
+        assert_eq!(audio.channels, vec![vec![-1.0, 0.5, -1.0 / 8388608.0]]);
+    }
+
+    #[test]
+    fn decodes_32_bit_float_as_is() {
+        let data: Vec<u8> = [0.25f32, -0.75]
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+
+        let audio = decode_wav(&wav(FORMAT_FLOAT, 1, 32, &data)).expect("WAV should decode");
+
+        assert_eq!(audio.channels, vec![vec![0.25, -0.75]]);
+    }
+
+    #[test]
+    fn rejects_chunks_running_past_the_end_of_the_file() {
+        let mut truncated = wav(FORMAT_PCM, 1, 16, &[0; 8]);
+        truncated.truncate(truncated.len() - 2);
+        let mut oversized = wav(FORMAT_PCM, 1, 16, &[0; 8]);
+        let data_size_at = oversized.len() - 12;
+        oversized[data_size_at..data_size_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(decode_wav(&truncated).is_err());
+        assert!(decode_wav(&oversized).is_err());
+    }
+}
@@ -4,20 +4,38 @@ mod engine;
 mod nodes;
 mod render;
 
+use crate::nodes::audio_effect::AutoPanRate;
+use crate::nodes::audio_effect::BitcrusherPreset;
+use crate::nodes::audio_effect::CompressorParameters;
+use crate::nodes::audio_effect::CompressorPreset;
+use crate::nodes::audio_effect::DelayDivision;
+use crate::nodes::audio_effect::FilterTone;
 use crate::nodes::audio_effect::FilterType;
+use crate::nodes::audio_effect::PanPosition;
 use engine::errors::GameError;
 use engine::errors::GameResult;
+use engine::game_config::AnalyserWidgetConfig;
 use engine::game_config::AudioConfig;
 use engine::game_config::CardColorConfig;
+use engine::game_config::CardParametersWidgetConfig;
 use engine::game_config::CardsRowWidgetConfig;
 use engine::game_config::DebugHudConfig;
 use engine::game_config::GameConfig;
 use engine::game_config::GraphWidgetConfig;
 use engine::game_engine::GameEngine;
+use nodes::envelope::EnvelopePreset;
+use nodes::lfo::LfoPreset;
 use nodes::note_effect::ChangeLenType;
 use nodes::note_effect::ScaleType;
 use nodes::note_generator::NoteName;
+use nodes::oscillator::FmPreset;
+use nodes::oscillator::NoiseColor;
 use nodes::oscillator::WaveShape;
+use nodes::oscillator::WavetablePreset;
+use nodes::percussion::DrumVoice;
+use nodes::sampler::SampleInstrument;
+use nodes::voice::GlideTime;
+use nodes::voice::UnisonVoices;
 use render::widgets::card_widget::CardType;
 use render::RenderCtx;
 
@@ -52,9 +70,67 @@ async fn run() -> GameResult<()> {
             CardType::ChordInScale(NoteName::A, ScaleType::Minor),
             CardType::Oscillator(WaveShape::Sine),
             CardType::Oscillator(WaveShape::Square),
+            CardType::Oscillator(WaveShape::Sawtooth),
+            CardType::Oscillator(WaveShape::Triangle),
+            CardType::Oscillator(WaveShape::Wavetable(WavetablePreset::Organ)),
+            CardType::Oscillator(WaveShape::Wavetable(WavetablePreset::Brass)),
+            CardType::Oscillator(WaveShape::Wavetable(WavetablePreset::Bell)),
+            CardType::Oscillator(WaveShape::Fm(FmPreset::ElectricPiano)),
+            CardType::Oscillator(WaveShape::Fm(FmPreset::Bell)),
+            CardType::Oscillator(WaveShape::Fm(FmPreset::Bass)),
+            CardType::Noise(NoiseColor::White),
+            CardType::Noise(NoiseColor::Pink),
+            CardType::Noise(NoiseColor::Brown),
+            CardType::Drum(None),
+            CardType::Drum(Some(DrumVoice::Kick)),
+            CardType::Drum(Some(DrumVoice::Snare)),
+            CardType::Drum(Some(DrumVoice::HiHat)),
+            CardType::Drum(Some(DrumVoice::Clap)),
+            CardType::Sampler(SampleInstrument::Keys),
+            CardType::Sampler(SampleInstrument::Pad),
+            CardType::Unison(UnisonVoices::Two),
+            CardType::Unison(UnisonVoices::Four),
+            CardType::Unison(UnisonVoices::Seven),
+            CardType::Glide(GlideTime::Short),
+            CardType::Glide(GlideTime::Medium),
+            CardType::Glide(GlideTime::Long),
+            CardType::Envelope(EnvelopePreset::Pluck),
+            CardType::Envelope(EnvelopePreset::Keys),
+            CardType::Envelope(EnvelopePreset::Pad),
+            CardType::Envelope(EnvelopePreset::Swell),
+            CardType::Lfo(LfoPreset::SlowSine),
+            CardType::Lfo(LfoPreset::FastSine),
+            CardType::Lfo(LfoPreset::QuarterTriangle),
+            CardType::Lfo(LfoPreset::EighthSquare),
             CardType::Distortion,
             CardType::Reverb,
+            CardType::Delay(DelayDivision::Quarter),
+            CardType::Delay(DelayDivision::DottedEighth),
+            CardType::Delay(DelayDivision::EighthTriplet),
+            CardType::PingPongDelay(DelayDivision::DottedEighth),
+            CardType::Chorus,
+            CardType::Flanger,
+            CardType::Phaser,
+            CardType::Compressor(CompressorPreset::Gentle),
+            CardType::Compressor(CompressorPreset::Punchy),
+            CardType::Compressor(CompressorPreset::Glue),
+            CardType::Compressor(CompressorPreset::Squash),
             CardType::Filter(FilterType::LowPass),
+            CardType::FilterPreset(FilterType::LowPass, FilterTone::Dark),
+            CardType::FilterPreset(FilterType::LowPass, FilterTone::Resonant),
+            CardType::FilterPreset(FilterType::HighPass, FilterTone::Bright),
+            CardType::Filter(FilterType::BandPass),
+            CardType::FilterPreset(FilterType::LowShelf, FilterTone::Dark),
+            CardType::Filter(FilterType::Peaking),
+            CardType::FilterPreset(FilterType::HighShelf, FilterTone::Bright),
+            CardType::Pan(PanPosition::Left),
+            CardType::Pan(PanPosition::Right),
+            CardType::AutoPan(AutoPanRate::Quarter),
+            CardType::AutoPan(AutoPanRate::Half),
+            CardType::AutoPan(AutoPanRate::Bar),
+            CardType::Bitcrusher(BitcrusherPreset::EightBit),
+            CardType::Bitcrusher(BitcrusherPreset::FourBit),
+            CardType::Bitcrusher(BitcrusherPreset::Telephone),
         ],
         graph_widget: GraphWidgetConfig {
             location: vec2(0.5, 0.5),
@@ -64,23 +140,39 @@ async fn run() -> GameResult<()> {
             location: vec2(0.5, 0.85),
             size: vec2(0.9, 0.13),
         },
+        analyser_widget: AnalyserWidgetConfig {
+            location: vec2(0.85, 0.2),
+            size: vec2(0.25, 0.2),
+        },
+        card_parameters_widget: CardParametersWidgetConfig {
+            location: vec2(0.5, 0.2),
+            size: vec2(0.4, 0.3),
+        },
         debug_hud: Some(DebugHudConfig { buffer_size: 100 }),
         audio: AudioConfig {
             attack_time: 0.001,
             release_time: 0.002,
-            max_schedule_ahead: 120.0,
             output_gain: 0.8,
+            param_smoothing: 0.02,
+            max_polyphony: 48,
+            steal_fade: 0.01,
+            swap_crossfade: 0.05,
+            master_compressor: CompressorParameters::new(-12.0, 6.0, 3.0, 0.005, 0.2),
+            limiter_ceiling: -1.0,
         },
         card_colors: CardColorConfig {
             note_generator: Color::from_hex(0xF7567C),
             note_effect: Color::from_hex(0xFCBA04),
             oscillator: Color::from_hex(0x99E1D9),
+            envelope: Color::from_hex(0xA8D672),
             audio_effect: Color::from_hex(0xC2AED6),
+            lfo: Color::from_hex(0xF4A6C1),
+            voice: Color::from_hex(0x8FB8ED),
         },
     };
 
     let render_ctx = RenderCtx::new(vec2(screen_width(), screen_height())).await?;
-    let mut game_engine = GameEngine::new(render_ctx, game_config)?;
+    let mut game_engine = GameEngine::new(render_ctx, game_config).await?;
 
     loop {
         game_engine.update().await?;
@@ -1,11 +1,30 @@
 use serde::{Deserialize, Serialize};
-use web_sys::BiquadFilterType;
+
+use crate::core::Random;
+
+use super::lfo::Lfo;
+use super::lfo::LfoRate;
+use super::note_generator::MusicTime;
+use super::note_generator::NoteDuration;
+use super::oscillator::WaveShape;
+
+/// Keeps the echoes of a delay decaying, a feedback of 1.0 or more would grow forever
+pub const MAX_DELAY_FEEDBACK: f32 = 0.95;
+/// Slowest tempo a piece can be played at, delay lines are sized for it
+pub const MIN_BPM: u32 = 40;
 
 #[derive(Clone, PartialEq)]
 pub enum AudioEffect {
     Filter(FilterParameters),
     Distortion(DistortionParameters),
     Reverb(ReverbParameters),
+    Delay(DelayParameters),
+    Chorus(ModulatedDelayParameters),
+    Flanger(ModulatedDelayParameters),
+    Phaser(PhaserParameters),
+    Compressor(CompressorParameters),
+    Pan(PanParameters),
+    Bitcrusher(BitcrusherParameters),
 }
 
 #[derive(Clone, PartialEq)]
@@ -29,16 +48,123 @@ pub struct ReverbParameters {
     pub dry_level: f32,  // Amount of original signal
 }
 
+#[derive(Clone, PartialEq)]
+pub struct DelayParameters {
+    pub time: MusicTime, // Delay time, follows the piece BPM
+    pub feedback: f32,   // Level of each echo relative to the previous one
+    pub damping: f32,    // Cutoff of the lowpass filter inside the feedback loop, in Hz
+    pub wet_level: f32,  // Amount of echoes
+    pub dry_level: f32,  // Amount of original signal
+    pub ping_pong: bool, // Echoes alternate between the left and right channel
+}
+
+/// Short delay lines with an LFO sweeping their delay time, used by chorus and flanger
+#[derive(Clone, PartialEq)]
+pub struct ModulatedDelayParameters {
+    pub voices: u32,    // Delay lines, each with its own LFO rate and pan
+    pub delay: f32,     // Delay time the LFO sweeps around, in seconds
+    pub depth: f32,     // How far the LFO sweeps the delay time, in seconds
+    pub rate: f32,      // LFO rate of the first voice in Hz
+    pub feedback: f32,  // Amount of each delay line fed back into itself
+    pub wet_level: f32, // Amount of processed signal
+    pub dry_level: f32, // Amount of original signal
+}
+
+/// Chain of all-pass filters with an LFO sweeping their frequency, mixed with
+/// the dry signal to create moving notches
+#[derive(Clone, PartialEq)]
+pub struct PhaserParameters {
+    pub stages: u32,    // Number of all-pass filters
+    pub frequency: f32, // Center frequency the LFO sweeps around, in Hz
+    pub depth: f32,     // Sweep relative to the center frequency, 0.0 to 1.0
+    pub rate: f32,      // LFO rate in Hz
+    pub wet_level: f32, // Amount of processed signal
+    pub dry_level: f32, // Amount of original signal
+}
+
+/// Settings of a `DynamicsCompressorNode`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CompressorParameters {
+    pub threshold: f32, // Level in dB above which the signal is compressed
+    pub knee: f32,      // Range in dB above the threshold over which the curve eases in
+    pub ratio: f32,     // dB of input change for 1 dB of output change
+    pub attack: f32,    // Seconds to reduce the gain by 10dB
+    pub release: f32,   // Seconds to increase the gain by 10dB
+}
+
+/// Position of the signal in the stereo field, swept by an LFO when `depth` is above 0
+#[derive(Clone, Copy, PartialEq)]
+pub struct PanParameters {
+    pub position: f32,     // -1.0 is left, 1.0 is right
+    pub depth: f32,        // How far the LFO sweeps around the position, 0.0 to 1.0
+    pub period: MusicTime, // Length of a full left to right and back sweep
+}
+
+/// Lo-fi effect quantizing the amplitude and holding samples to lower the sample rate
+#[derive(Clone, Copy, PartialEq)]
+pub struct BitcrusherParameters {
+    pub bits: f32,        // Amplitude resolution, fractional bits give in-between steps
+    pub sample_rate: f32, // Rate the held samples change at, in Hz
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BitcrusherPreset {
+    EightBit,
+    FourBit,
+    Telephone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanPosition {
+    Left,
+    Center,
+    Right,
+}
+
+/// Sweep length of the auto-pan, follows the piece BPM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoPanRate {
+    Quarter,
+    Half,
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompressorPreset {
+    Gentle,
+    Punchy,
+    Glue,
+    Squash,
+}
+
+/// Note division a delay is synced to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DelayDivision {
+    Quarter,
+    DottedEighth,
+    EighthTriplet,
+}
+
+// NOTE: new variants must only be appended, saved pieces store the variant index
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FilterType {
     LowPass,
     HighPass,
-    // BandPass,
-    // LowShelf,
-    // HighShelf,
-    // Peaking,
     Notch,
-    // AllPass,
+    AllPass,
+    BandPass,
+    LowShelf,
+    HighShelf,
+    Peaking,
+}
+
+/// Cutoff and Q presets shared by every filter type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterTone {
+    Dark,
+    Mid,
+    Bright,
+    Resonant,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -48,16 +174,366 @@ pub enum DistortionCurve {
 }
 
 impl FilterType {
-    pub fn to_biquad_type(&self) -> BiquadFilterType {
+    pub fn from_int(i: u32) -> Option<FilterType> {
+        match i {
+            0 => Some(FilterType::LowPass),
+            1 => Some(FilterType::HighPass),
+            2 => Some(FilterType::Notch),
+            3 => Some(FilterType::AllPass),
+            4 => Some(FilterType::BandPass),
+            5 => Some(FilterType::LowShelf),
+            6 => Some(FilterType::HighShelf),
+            7 => Some(FilterType::Peaking),
+            _ => None,
+        }
+    }
+
+    pub fn to_int(self) -> u32 {
         match self {
-            FilterType::LowPass => BiquadFilterType::Lowpass,
-            FilterType::HighPass => BiquadFilterType::Highpass,
-            // FilterType::BandPass => BiquadFilterType::Bandpass,
-            // FilterType::LowShelf => BiquadFilterType::Lowshelf,
-            // FilterType::HighShelf => BiquadFilterType::Highshelf,
-            // FilterType::Peaking => BiquadFilterType::Peaking,
-            FilterType::Notch => BiquadFilterType::Notch,
-            // FilterType::AllPass => BiquadFilterType::Allpass,
+            FilterType::LowPass => 0,
+            FilterType::HighPass => 1,
+            FilterType::Notch => 2,
+            FilterType::AllPass => 3,
+            FilterType::BandPass => 4,
+            FilterType::LowShelf => 5,
+            FilterType::HighShelf => 6,
+            FilterType::Peaking => 7,
+        }
+    }
+
+    /// Web Audio reads the Q of lowpass and highpass filters in dB, of the others linearly
+    pub fn q_in_db(&self) -> bool {
+        matches!(self, FilterType::LowPass | FilterType::HighPass)
+    }
+
+    /// Whether the filter boosts or cuts by `FilterParameters::gain`
+    pub fn uses_gain(&self) -> bool {
+        matches!(
+            self,
+            FilterType::LowShelf | FilterType::HighShelf | FilterType::Peaking
+        )
+    }
+}
+
+impl FilterTone {
+    pub fn from_int(i: u32) -> Option<FilterTone> {
+        match i {
+            0 => Some(FilterTone::Dark),
+            1 => Some(FilterTone::Mid),
+            2 => Some(FilterTone::Bright),
+            3 => Some(FilterTone::Resonant),
+            _ => None,
+        }
+    }
+
+    pub fn to_int(self) -> u32 {
+        match self {
+            FilterTone::Dark => 0,
+            FilterTone::Mid => 1,
+            FilterTone::Bright => 2,
+            FilterTone::Resonant => 3,
+        }
+    }
+
+    pub fn frequency(&self) -> f32 {
+        match self {
+            FilterTone::Dark => 400.0,
+            FilterTone::Mid | FilterTone::Resonant => 1000.0,
+            FilterTone::Bright => 4000.0,
+        }
+    }
+
+    pub fn q(&self, filter_type: FilterType) -> f32 {
+        match (self, filter_type.q_in_db()) {
+            (FilterTone::Resonant, true) => 12.0,
+            (FilterTone::Resonant, false) => 8.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl DistortionParameters {
+    pub fn input_gain(&self) -> f32 {
+        1.0 + self.amount * 10.0
+    }
+
+    pub fn compensation_gain(&self) -> f32 {
+        match self.curve_type {
+            DistortionCurve::SoftClip => 1.0 / (1.0 + self.amount * 0.5),
+            DistortionCurve::HardClip => 0.3 / (1.0 + self.amount),
+        }
+    }
+
+    /// Wave shaper curve mapping inputs in [-1, 1] to distorted outputs
+    pub fn curve(&self, samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|i| {
+                let x = (i as f32 / (samples - 1) as f32) * 2.0 - 1.0;
+
+                match self.curve_type {
+                    DistortionCurve::SoftClip => x.tanh() * (1.0 + self.amount * 0.5),
+                    DistortionCurve::HardClip => {
+                        let k = self.amount * 100.0;
+                        let deg = std::f32::consts::PI / 180.0;
+
+                        if x.abs() < 0.001 {
+                            x
+                        } else {
+                            let distorted =
+                                ((3.0 + k) * x * 20.0 * deg) / (std::f32::consts::PI + k * x.abs());
+
+                            distorted.clamp(-1.0, 1.0)
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+impl ReverbParameters {
+    /// Noise decaying over `decay_time`, after a short pre-delay
+    pub fn impulse_response(&self, sample_rate: f32, random: &mut Random) -> Vec<f32> {
+        let length = (sample_rate * self.decay_time) as usize;
+        let pre_delay_samples = (sample_rate * 0.02) as usize;
+        let decay_exponent = 2.0;
+
+        (0..length)
+            .map(|i| {
+                if i < pre_delay_samples {
+                    return 0.0;
+                }
+                let normalized_time =
+                    (i - pre_delay_samples) as f32 / (length - pre_delay_samples) as f32;
+                let decay = (1.0 - normalized_time).powf(decay_exponent);
+                (random.next_f32() * 2.0 - 1.0) * decay
+            })
+            .collect()
+    }
+}
+
+impl DelayParameters {
+    pub fn delay_seconds(&self, bpm: u32) -> f32 {
+        self.time.to_seconds(bpm) as f32
+    }
+
+    /// Longest delay time the echo can reach, the longest division at the slowest tempo
+    /// or at `bpm` when a piece is played even slower
+    pub fn max_delay_seconds(&self, bpm: u32) -> f32 {
+        DelayDivision::ALL
+            .iter()
+            .map(|division| division.time())
+            .chain([self.time])
+            .map(|time| time.to_seconds(bpm.min(MIN_BPM)) as f32)
+            .fold(0.0, f32::max)
+    }
+
+    pub fn feedback_gain(&self) -> f32 {
+        self.feedback.clamp(0.0, MAX_DELAY_FEEDBACK)
+    }
+
+    /// Lowpass filter damping every repeat of the echo
+    pub fn damping_filter(&self) -> FilterParameters {
+        FilterParameters {
+            filter_type: FilterType::LowPass,
+            frequency: self.damping,
+            // Butterworth response, a resonant peak above unity gain could undo the
+            // feedback clamp
+            q: -3.0103,
+            gain: 0.0,
+        }
+    }
+}
+
+impl ModulatedDelayParameters {
+    /// LFO of every voice, rates are spread so the voices don't move in lockstep
+    pub fn voice_lfos(&self) -> Vec<Lfo> {
+        (0..self.voices)
+            .map(|i| {
+                let rate = self.rate * (1.0 + 0.25 * i as f32);
+                Lfo::new(WaveShape::Sine, LfoRate::Hertz(rate), 1.0)
+            })
+            .collect()
+    }
+
+    /// Pan of every voice, spread evenly across the stereo field
+    pub fn voice_pans(&self) -> Vec<f32> {
+        if self.voices <= 1 {
+            return vec![0.0];
+        }
+        (0..self.voices)
+            .map(|i| 2.0 * i as f32 / (self.voices - 1) as f32 - 1.0)
+            .collect()
+    }
+
+    pub fn feedback_gain(&self) -> f32 {
+        self.feedback.clamp(0.0, MAX_DELAY_FEEDBACK)
+    }
+
+    /// Wet level shared by the voices, keeps the loudness close to a single voice
+    pub fn wet_gain(&self) -> f32 {
+        self.wet_level / (self.voices.max(1) as f32).sqrt()
+    }
+
+    /// Longest delay time the LFO reaches
+    pub fn max_delay(&self) -> f32 {
+        self.delay + self.depth
+    }
+}
+
+impl PhaserParameters {
+    pub fn lfo(&self) -> Lfo {
+        Lfo::new(WaveShape::Sine, LfoRate::Hertz(self.rate), 1.0)
+    }
+
+    /// Frequency the all-pass filters rest at and how far the LFO sweeps it
+    pub fn sweep(&self) -> (f32, f32) {
+        (self.frequency, self.frequency * self.depth.clamp(0.0, 0.95))
+    }
+
+    pub fn stage_filter(&self, frequency: f32) -> FilterParameters {
+        FilterParameters {
+            filter_type: FilterType::AllPass,
+            frequency,
+            q: 0.7,
+            gain: 0.0,
+        }
+    }
+}
+
+impl PanParameters {
+    pub fn lfo(&self) -> Lfo {
+        Lfo::new(WaveShape::Sine, LfoRate::Synced(self.period), 1.0)
+    }
+
+    /// Pan the LFO rests at and how far it sweeps it, the sweep never goes past the
+    /// edges of the stereo field
+    pub fn sweep(&self) -> (f32, f32) {
+        let position = self.position.clamp(-1.0, 1.0);
+        (position, self.depth.clamp(0.0, 1.0 - position.abs()))
+    }
+}
+
+impl BitcrusherParameters {
+    pub fn new(bits: f32, sample_rate: f32) -> Self {
+        Self { bits, sample_rate }
+    }
+
+    /// Rounds to one of `2^bits` levels, from -1.0 up to one step below 1.0
+    // NOTE: rounds half up like `Math.round` in the worklet, so both give the same steps
+    pub fn quantize(&self, x: f32) -> f32 {
+        let steps = 2f32.powf(self.bits.max(1.0) - 1.0);
+        let level = (x.clamp(-1.0, 1.0) * steps + 0.5).floor();
+        level.clamp(-steps, steps - 1.0) / steps
+    }
+
+    /// Frames every sample is held for at the sample rate of the context
+    pub fn hold_frames(&self, context_sample_rate: f32) -> f32 {
+        (context_sample_rate / self.sample_rate.max(1.0)).max(1.0)
+    }
+
+    /// Staircase for a `WaveShaperNode` standing in for the worklet, it can only
+    /// reduce the bit depth
+    pub fn curve(&self, samples: usize) -> Vec<f32> {
+        (0..samples)
+            .map(|i| self.quantize((i as f32 / (samples - 1) as f32) * 2.0 - 1.0))
+            .collect()
+    }
+}
+
+impl CompressorParameters {
+    pub fn new(threshold: f32, knee: f32, ratio: f32, attack: f32, release: f32) -> Self {
+        Self {
+            threshold,
+            knee,
+            ratio,
+            attack,
+            release,
+        }
+    }
+
+    /// Fast compressor at the highest ratio Web Audio allows, holding peaks at `ceiling` dB
+    pub fn limiter(ceiling: f32) -> Self {
+        Self::new(ceiling, 0.0, 20.0, 0.001, 0.05)
+    }
+
+    /// Static curve of the compressor, output level in dB for a steady input level in dB.
+    /// Within the knee the ratio eases in quadratically
+    pub fn output_level(&self, input: f32) -> f32 {
+        let over = input - self.threshold;
+        let slope = 1.0 / self.ratio - 1.0;
+        if over <= 0.0 {
+            input
+        } else if over < self.knee {
+            input + slope * over * over / (2.0 * self.knee)
+        } else {
+            input + slope * (over - self.knee / 2.0)
+        }
+    }
+
+    /// Gain Web Audio adds after compressing, derived from the level a full scale
+    /// input comes out at
+    pub fn makeup_gain(&self) -> f32 {
+        let full_range_gain = 10f32.powf(self.output_level(0.0) / 20.0);
+        (1.0 / full_range_gain).powf(0.6)
+    }
+}
+
+impl CompressorPreset {
+    pub fn parameters(self) -> CompressorParameters {
+        match self {
+            CompressorPreset::Gentle => CompressorParameters::new(-18.0, 12.0, 2.0, 0.02, 0.25),
+            // Slow attack lets the transients through before clamping down
+            CompressorPreset::Punchy => CompressorParameters::new(-20.0, 6.0, 4.0, 0.03, 0.15),
+            CompressorPreset::Glue => CompressorParameters::new(-14.0, 8.0, 2.5, 0.01, 0.3),
+            CompressorPreset::Squash => CompressorParameters::new(-30.0, 3.0, 12.0, 0.003, 0.1),
+        }
+    }
+}
+
+impl BitcrusherPreset {
+    pub fn parameters(self) -> BitcrusherParameters {
+        match self {
+            BitcrusherPreset::EightBit => BitcrusherParameters::new(8.0, 22050.0),
+            BitcrusherPreset::FourBit => BitcrusherParameters::new(4.0, 11025.0),
+            BitcrusherPreset::Telephone => BitcrusherParameters::new(8.0, 8000.0),
+        }
+    }
+}
+
+impl PanPosition {
+    pub fn position(self) -> f32 {
+        match self {
+            PanPosition::Left => -0.6,
+            PanPosition::Center => 0.0,
+            PanPosition::Right => 0.6,
+        }
+    }
+}
+
+impl AutoPanRate {
+    pub fn period(self) -> MusicTime {
+        match self {
+            AutoPanRate::Quarter => NoteDuration::Quarter.into(),
+            AutoPanRate::Half => NoteDuration::Half.into(),
+            AutoPanRate::Bar => NoteDuration::Whole.into(),
+        }
+    }
+}
+
+impl DelayDivision {
+    pub const ALL: [DelayDivision; 3] = [
+        DelayDivision::Quarter,
+        DelayDivision::DottedEighth,
+        DelayDivision::EighthTriplet,
+    ];
+
+    pub fn time(self) -> MusicTime {
+        let eighth: MusicTime = NoteDuration::Eighth.into();
+        match self {
+            DelayDivision::Quarter => NoteDuration::Quarter.into(),
+            DelayDivision::DottedEighth => eighth * 3 / 2,
+            DelayDivision::EighthTriplet => eighth * 2 / 3,
         }
     }
 }
@@ -73,6 +549,12 @@ impl AudioEffect {
         })
     }
 
+    /// Shelves and peaking filters boost by 6dB, the gain is ignored by the other types
+    pub fn new_tuned_filter(filter_type: FilterType, tone: FilterTone) -> Self {
+        let gain = if filter_type.uses_gain() { 6.0 } else { 0.0 };
+        Self::new_filter(filter_type, tone.frequency(), tone.q(filter_type), gain)
+    }
+
     pub fn new_distortion(amount: f32, curve_type: DistortionCurve) -> Self {
         AudioEffect::Distortion(DistortionParameters { amount, curve_type })
     }
@@ -85,6 +567,83 @@ impl AudioEffect {
         })
     }
 
+    pub fn new_delay(
+        time: MusicTime,
+        feedback: f32,
+        damping: f32,
+        wet_level: f32,
+        dry_level: f32,
+        ping_pong: bool,
+    ) -> Self {
+        AudioEffect::Delay(DelayParameters {
+            time,
+            feedback: feedback.clamp(0.0, MAX_DELAY_FEEDBACK),
+            damping,
+            wet_level,
+            dry_level,
+            ping_pong,
+        })
+    }
+
+    pub fn new_chorus(voices: u32, delay: f32, depth: f32, rate: f32) -> Self {
+        AudioEffect::Chorus(ModulatedDelayParameters {
+            voices,
+            delay,
+            depth,
+            rate,
+            feedback: 0.0,
+            wet_level: 0.5,
+            dry_level: 0.8,
+        })
+    }
+
+    pub fn new_flanger(delay: f32, depth: f32, rate: f32, feedback: f32) -> Self {
+        AudioEffect::Flanger(ModulatedDelayParameters {
+            voices: 1,
+            delay,
+            depth,
+            rate,
+            feedback: feedback.clamp(0.0, MAX_DELAY_FEEDBACK),
+            wet_level: 0.6,
+            dry_level: 0.7,
+        })
+    }
+
+    pub fn new_phaser(stages: u32, frequency: f32, depth: f32, rate: f32) -> Self {
+        AudioEffect::Phaser(PhaserParameters {
+            stages,
+            frequency,
+            depth,
+            rate,
+            wet_level: 0.5,
+            dry_level: 0.5,
+        })
+    }
+
+    pub fn new_compressor(parameters: CompressorParameters) -> Self {
+        AudioEffect::Compressor(parameters)
+    }
+
+    pub fn new_pan(position: f32, depth: f32, period: MusicTime) -> Self {
+        AudioEffect::Pan(PanParameters {
+            position,
+            depth,
+            period,
+        })
+    }
+
+    pub fn new_fixed_pan(position: PanPosition) -> Self {
+        Self::new_pan(position.position(), 0.0, AutoPanRate::Bar.period())
+    }
+
+    pub fn new_auto_pan(rate: AutoPanRate) -> Self {
+        Self::new_pan(0.0, 0.8, rate.period())
+    }
+
+    pub fn new_bitcrusher(parameters: BitcrusherParameters) -> Self {
+        AudioEffect::Bitcrusher(parameters)
+    }
+
     // Default constructors
     pub fn default_filter() -> Self {
         Self::new_filter(FilterType::LowPass, 1000.0, 1.0, 0.0)
@@ -98,6 +657,32 @@ impl AudioEffect {
         Self::new_reverb(2.0, 0.5, 0.5)
     }
 
+    pub fn default_delay() -> Self {
+        Self::new_delay(DelayDivision::Quarter.time(), 0.4, 3000.0, 0.4, 1.0, false)
+    }
+
+    pub fn default_chorus() -> Self {
+        Self::new_chorus(3, 0.02, 0.004, 0.8)
+    }
+
+    pub fn default_flanger() -> Self {
+        // NOTE: Web Audio delays inside a feedback loop can't go below 128 frames,
+        // about 3ms, so the sweep stays above that
+        Self::new_flanger(0.005, 0.002, 0.25, 0.6)
+    }
+
+    pub fn default_phaser() -> Self {
+        Self::new_phaser(4, 800.0, 0.8, 0.5)
+    }
+
+    pub fn default_pan() -> Self {
+        Self::new_fixed_pan(PanPosition::Center)
+    }
+
+    pub fn default_bitcrusher() -> Self {
+        Self::new_bitcrusher(BitcrusherPreset::EightBit.parameters())
+    }
+
     // Helper methods to check effect type
     pub fn is_filter(&self) -> bool {
         matches!(self, AudioEffect::Filter(_))
@@ -111,6 +696,10 @@ impl AudioEffect {
         matches!(self, AudioEffect::Reverb(_))
     }
 
+    pub fn is_delay(&self) -> bool {
+        matches!(self, AudioEffect::Delay(_))
+    }
+
     // Getter methods with pattern matching
     pub fn as_filter(&self) -> Option<&FilterParameters> {
         match self {
@@ -132,4 +721,11 @@ impl AudioEffect {
             _ => None,
         }
     }
+
+    pub fn as_delay(&self) -> Option<&DelayParameters> {
+        match self {
+            AudioEffect::Delay(params) => Some(params),
+            _ => None,
+        }
+    }
 }
@@ -1,16 +1,42 @@
 use std::cell::RefCell;
+use std::mem::discriminant;
 
-use crate::render::widgets::card_widget::CardType;
+use crate::core::Random;
+use crate::render::widgets::card_widget::PieceCard;
 
 use super::audio_effect::AudioEffect;
+use super::envelope::Envelope;
+use super::lfo::Lfo;
+use super::lfo::ModulationTarget;
 use super::note_effect::NoteEffect;
 use super::note_generator::NoteGenerator;
 use super::oscillator::Oscillator;
+use super::parameters::EffectMix;
+use super::voice::Glide;
+use super::voice::Unison;
 use super::{AudioNode, AudioNodeType};
 
+/// Whether a card is heard while the graph plays, set during playback and never saved
+/// with the piece
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CardPlayback {
+    #[default]
+    Normal,
+    /// Generators play silence for as long as their notes would last, effects are bypassed
+    Muted,
+    /// Only soloed generator blocks are heard, the others keep their place in the loop
+    Soloed,
+}
+
 #[derive(PartialEq, Clone)]
 pub struct AudioGraph {
     nodes: Vec<RefCell<AudioNode>>,
+    /// Seed of everything random the graph plays, the same piece always gets the same one
+    seed: u64,
+    /// Mix of each audio effect, in the order of `audio_effects`
+    mixes: Vec<EffectMix>,
+    /// Playback of each card, in the order of `nodes`
+    playback: Vec<CardPlayback>,
 }
 
 impl AudioGraph {
@@ -18,33 +44,49 @@ impl AudioGraph {
     /// Groups note generators into blocks, applies effects to the combined group,
     /// and returns the processed blocks in sequence
     pub fn process_note_generators(&self) -> NoteGenerator {
-        let mut blocks: Vec<(Vec<NoteGenerator>, Vec<NoteEffect>)> = Vec::new();
+        let mut blocks: Vec<(Vec<NoteGenerator>, Vec<NoteEffect>, bool)> = Vec::new();
         let mut current_generators: Vec<NoteGenerator> = Vec::new();
         let mut current_effects: Vec<NoteEffect> = Vec::new();
+        let mut current_soloed = false;
         let mut consuming_effects = false;
 
-        for node_ref in &self.nodes {
+        for (card, node_ref) in self.nodes.iter().enumerate() {
+            let playback = self.card_playback(card);
             match &*node_ref.borrow() {
                 AudioNode::NoteGenerator(ng) => {
-                    if !consuming_effects {
-                        current_generators.push(ng.clone());
-                    } else {
+                    if consuming_effects {
                         consuming_effects = false;
-                        blocks.push((current_generators, current_effects));
+                        blocks.push((current_generators, current_effects, current_soloed));
                         current_generators = Vec::new();
-                        current_generators.push(ng.clone());
                         current_effects = Vec::new();
+                        current_soloed = false;
                     }
+                    // NOTE: a muted generator keeps its length, so the notes after it
+                    // stay in place
+                    let generator = match playback {
+                        CardPlayback::Muted => NoteGenerator::new(ng.loop_length, vec![]),
+                        _ => ng.with_card(card),
+                    };
+                    current_generators.push(generator);
+                    current_soloed |= playback == CardPlayback::Soloed;
                 }
                 AudioNode::NoteEffect(effect) => {
                     consuming_effects = true;
-                    current_effects.push(effect.clone());
+                    if playback != CardPlayback::Muted {
+                        current_effects.push(effect.clone());
+                    }
                 }
-                AudioNode::Oscillator(_) | AudioNode::AudioEffect(_) => {
+                AudioNode::Oscillator(_)
+                | AudioNode::Envelope(_)
+                | AudioNode::AudioEffect(_)
+                | AudioNode::Lfo(_)
+                | AudioNode::Unison(_)
+                | AudioNode::Glide(_) => {
                     if !current_generators.is_empty() {
-                        blocks.push((current_generators, current_effects));
+                        blocks.push((current_generators, current_effects, current_soloed));
                         current_generators = Vec::new();
                         current_effects = Vec::new();
+                        current_soloed = false;
                     }
                     consuming_effects = false;
                 }
@@ -52,11 +94,12 @@ impl AudioGraph {
         }
 
         if !current_generators.is_empty() {
-            blocks.push((current_generators, current_effects));
+            blocks.push((current_generators, current_effects, current_soloed));
         }
 
+        let any_soloed = blocks.iter().any(|(_, _, soloed)| *soloed);
         let mut result: Vec<NoteGenerator> = Vec::new();
-        for (generators, effects) in blocks {
+        for (generators, effects, soloed) in blocks {
             if generators.is_empty() {
                 continue;
             }
@@ -65,6 +108,9 @@ impl AudioGraph {
             for effect in &effects {
                 processed_generator = effect.apply(processed_generator);
             }
+            if any_soloed && !soloed {
+                processed_generator.notes.clear();
+            }
             result.push(processed_generator);
         }
 
@@ -102,27 +148,103 @@ impl AudioGraph {
             .collect();
 
         let nodes = vec![ngs, osc, aes].concat();
+        let mixes = vec![EffectMix::default(); audio_effects.len()];
+
+        let playback = vec![CardPlayback::Normal; nodes.len()];
 
-        AudioGraph { nodes }
+        AudioGraph {
+            nodes,
+            seed: 0,
+            mixes,
+            playback,
+        }
     }
 
-    pub fn from_cards(cards: Vec<CardType>) -> Option<Self> {
+    pub fn from_cards(cards: Vec<PieceCard>) -> Option<Self> {
         if Self::is_valid(&cards) {
             let nodes = cards
                 .iter()
-                .map(|c| RefCell::new(AudioNode::from_card(c)))
+                .map(|c| RefCell::new(AudioNode::from_piece_card(c)))
                 .collect();
-            Some(Self { nodes })
+            // NOTE: tuning parameters doesn't reseed, only the cards themselves do
+            let ids: Vec<u8> = cards
+                .iter()
+                .flat_map(|c| c.card_type.to_id().to_le_bytes())
+                .collect();
+            let seed = Random::seed_from_bytes(&ids);
+            let mixes = cards
+                .iter()
+                .filter(|c| c.as_type() == AudioNodeType::AudioEffect)
+                .map(|c| c.mix)
+                .collect();
+            let playback = vec![CardPlayback::Normal; cards.len()];
+            Some(Self {
+                nodes,
+                seed,
+                mixes,
+                playback,
+            })
         } else {
             None
         }
     }
 
+    /// Same graph with the cards muted or soloed as in `playback`, one per card
+    pub fn with_playback(self, playback: Vec<CardPlayback>) -> Self {
+        Self { playback, ..self }
+    }
+
+    fn card_playback(&self, card: usize) -> CardPlayback {
+        self.playback.get(card).copied().unwrap_or_default()
+    }
+
     pub fn nodes(&self) -> &Vec<RefCell<AudioNode>> {
         &self.nodes
     }
 
-    fn is_valid(cards: &Vec<CardType>) -> bool {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether the graphs only differ in the parameters of their audio effects, in which
+    /// case a playing graph can be updated without restarting it
+    pub fn has_same_layout(&self, other: &AudioGraph) -> bool {
+        self.nodes.len() == other.nodes.len()
+            && self
+                .nodes
+                .iter()
+                .zip(&other.nodes)
+                .enumerate()
+                .all(|(card, (a, b))| match (&*a.borrow(), &*b.borrow()) {
+                    (AudioNode::AudioEffect(a), AudioNode::AudioEffect(b)) => {
+                        discriminant(a) == discriminant(b)
+                    }
+                    // NOTE: muting an effect only bypasses it, other cards change the notes
+                    (a, b) => a == b && self.card_playback(card) == other.card_playback(card),
+                })
+    }
+
+    /// Whether the graphs are made of the same cards and only differ in what is muted or
+    /// soloed
+    pub fn has_same_cards(&self, other: &AudioGraph) -> bool {
+        self.nodes == other.nodes && self.mixes == other.mixes
+    }
+
+    /// Whether the graphs play their notes through the same effects and LFOs, which can
+    /// then be kept playing when one graph takes over from the other
+    pub fn has_same_effect_chain(&self, other: &AudioGraph) -> bool {
+        let audio_effects = self.audio_effects();
+        // NOTE: reverbs draw their impulse response from the seed
+        let has_reverb = audio_effects
+            .iter()
+            .any(|effect| matches!(effect, AudioEffect::Reverb(_)));
+        audio_effects == other.audio_effects()
+            && self.effect_mixes() == other.effect_mixes()
+            && self.modulations() == other.modulations()
+            && (self.seed == other.seed || !has_reverb)
+    }
+
+    fn is_valid(cards: &Vec<PieceCard>) -> bool {
         let mut maybe_before = None;
         let mut maybe_current = None;
         let mut valid = true;
@@ -184,6 +306,48 @@ impl AudioGraph {
             .next()
     }
 
+    pub fn envelope(&self) -> Option<Envelope> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let borrowed = node.borrow();
+                if let AudioNode::Envelope(ref envelope) = *borrowed {
+                    Some(*envelope)
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
+    pub fn unison(&self) -> Option<Unison> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let borrowed = node.borrow();
+                if let AudioNode::Unison(ref unison) = *borrowed {
+                    Some(*unison)
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
+    pub fn glide(&self) -> Option<Glide> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                let borrowed = node.borrow();
+                if let AudioNode::Glide(ref glide) = *borrowed {
+                    Some(*glide)
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
     pub fn audio_effects(&self) -> Vec<AudioEffect> {
         self.nodes
             .iter()
@@ -197,9 +361,224 @@ impl AudioGraph {
             })
             .collect()
     }
+
+    /// Mixes of the audio effects, muted effects are bypassed
+    pub fn effect_mixes(&self) -> Vec<EffectMix> {
+        let effect_cards = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(*node.borrow(), AudioNode::AudioEffect(_)));
+        self.mixes
+            .iter()
+            .zip(effect_cards)
+            .map(|(mix, (card, _))| match self.card_playback(card) {
+                CardPlayback::Muted => EffectMix {
+                    bypassed: true,
+                    ..*mix
+                },
+                _ => *mix,
+            })
+            .collect()
+    }
+
+    /// LFOs of the graph paired with the param of the card that follows each of them
+    pub fn modulations(&self) -> Vec<(Lfo, ModulationTarget)> {
+        let mut modulations = vec![];
+        let mut effect_index = 0;
+        let mut pending: Vec<Lfo> = vec![];
+
+        for node_ref in &self.nodes {
+            let target = match &*node_ref.borrow() {
+                AudioNode::Lfo(lfo) => {
+                    pending.push(*lfo);
+                    continue;
+                }
+                AudioNode::Oscillator(_) => Some(ModulationTarget::OscillatorDetune),
+                AudioNode::AudioEffect(effect) => {
+                    effect_index += 1;
+                    match effect {
+                        AudioEffect::Filter(_) => {
+                            Some(ModulationTarget::FilterCutoff(effect_index - 1))
+                        }
+                        _ => Some(ModulationTarget::OutputGain),
+                    }
+                }
+                AudioNode::Envelope(_) | AudioNode::Unison(_) | AudioNode::Glide(_) => {
+                    Some(ModulationTarget::OutputGain)
+                }
+                AudioNode::NoteGenerator(_) | AudioNode::NoteEffect(_) => None,
+            };
+            // NOTE: LFOs in front of note cards have nothing to modulate yet,
+            // they stay pending until the next sound card
+            if let Some(target) = target {
+                modulations.extend(pending.drain(..).map(|lfo| (lfo, target)));
+            }
+        }
+        for lfo in pending {
+            modulations.push((lfo, ModulationTarget::OutputGain));
+        }
+        modulations
+    }
 }
 
 enum CheckingStage {
     NoteGenerators,
     AudioEffects,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::audio_backend::recording::BackendEvent;
+    use crate::engine::audio_backend::recording::RecordingBackend;
+    use crate::engine::audio_backend::NodeKind;
+    use crate::engine::audio_engine::AudioEngine;
+    use crate::engine::test_fixtures::audio_config;
+    use crate::engine::test_fixtures::graph;
+    use crate::nodes::audio_effect::FilterType;
+    use crate::nodes::note_generator::NoteName;
+    use crate::nodes::oscillator::NoiseColor;
+    use crate::nodes::parameters::CardParameters;
+    use crate::render::widgets::card_widget::CardType;
+    use crate::render::Samples;
+
+    const SAMPLE_RATE: f32 = 8000.0;
+    const BPM: u32 = 120;
+
+    fn noisy_reverb(note: NoteName, color: NoiseColor) -> AudioGraph {
+        graph(vec![
+            CardType::NoteGenerator(Some(note)),
+            CardType::Noise(color),
+            CardType::Reverb,
+            CardType::Filter(FilterType::LowPass),
+            CardType::Reverb,
+        ])
+    }
+
+    /// Buffers the engine sets on nodes of `kind` when it plays the first notes of the
+    /// graph, in the order it sets them
+    fn played_buffers(graph: &AudioGraph, kind: NodeKind) -> Vec<Vec<Vec<f32>>> {
+        let audio_config = audio_config();
+        let mut engine = AudioEngine::new(
+            RecordingBackend::new(SAMPLE_RATE),
+            Samples::new(),
+            &audio_config,
+        )
+        .expect("Engine should start");
+        engine
+            .interpret_graph(BPM, graph, &audio_config)
+            .expect("Graph should play");
+        engine
+            .schedule_notes(0.1, &audio_config)
+            .expect("Notes should be scheduled");
+
+        let backend = engine.backend();
+        backend
+            .events()
+            .into_iter()
+            .filter_map(|event| match event {
+                BackendEvent::SetBuffer(node, buffer) if backend.node_kind(node) == Some(kind) => {
+                    backend.buffer(buffer)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Noise the first note of the graph loops
+    fn noise_buffer(graph: &AudioGraph) -> Vec<Vec<f32>> {
+        played_buffers(graph, NodeKind::BufferSource)
+            .into_iter()
+            .next()
+            .expect("Graph should play noise")
+    }
+
+    /// Impulse responses of the reverbs of the graph, in effect chain order
+    fn reverb_irs(graph: &AudioGraph) -> Vec<Vec<Vec<f32>>> {
+        played_buffers(graph, NodeKind::Convolver)
+    }
+
+    #[test]
+    fn same_cards_give_the_same_seed_noise_and_reverb() {
+        let graph_a = noisy_reverb(NoteName::A, NoiseColor::Pink);
+        let graph_b = noisy_reverb(NoteName::A, NoiseColor::Pink);
+
+        assert_eq!(graph_a.seed(), graph_b.seed());
+        assert_eq!(noise_buffer(&graph_a), noise_buffer(&graph_b));
+        assert_eq!(reverb_irs(&graph_a), reverb_irs(&graph_b));
+    }
+
+    #[test]
+    fn different_cards_give_different_seed_noise_and_reverb() {
+        let graph_a = noisy_reverb(NoteName::A, NoiseColor::Pink);
+        let graph_b = noisy_reverb(NoteName::C, NoiseColor::Pink);
+
+        assert_ne!(graph_a.seed(), graph_b.seed());
+        assert_ne!(noise_buffer(&graph_a), noise_buffer(&graph_b));
+        assert_ne!(reverb_irs(&graph_a), reverb_irs(&graph_b));
+    }
+
+    #[test]
+    fn reverbs_of_one_graph_get_their_own_impulse_response() {
+        let graph = noisy_reverb(NoteName::A, NoiseColor::White);
+
+        let irs = reverb_irs(&graph);
+
+        assert_eq!(irs.len(), 2);
+        assert_ne!(irs[0], irs[1]);
+    }
+
+    #[test]
+    fn tuning_parameters_keeps_the_seed() {
+        let cards: Vec<PieceCard> = vec![
+            CardType::NoteGenerator(Some(NoteName::A)),
+            CardType::Noise(NoiseColor::Brown),
+            CardType::Reverb,
+        ]
+        .into_iter()
+        .map(PieceCard::from)
+        .collect();
+        let mut tuned = cards.clone();
+        tuned[2].parameters = Some(CardParameters::Reverb {
+            decay_time: 5.0,
+            wet_level: 0.2,
+            dry_level: 0.8,
+        });
+
+        let graph = AudioGraph::from_cards(cards).expect("Graph should be valid");
+        let tuned = AudioGraph::from_cards(tuned).expect("Graph should be valid");
+
+        assert_eq!(graph.seed(), tuned.seed());
+    }
+
+    #[test]
+    fn reverbs_are_only_kept_across_graphs_with_the_same_seed() {
+        let filtered = |note| {
+            graph(vec![
+                CardType::NoteGenerator(Some(note)),
+                CardType::Noise(NoiseColor::White),
+                CardType::Filter(FilterType::LowPass),
+            ])
+        };
+
+        let reverb_a = noisy_reverb(NoteName::A, NoiseColor::White);
+        let reverb_c = noisy_reverb(NoteName::C, NoiseColor::White);
+
+        assert!(!reverb_a.has_same_effect_chain(&reverb_c));
+        assert!(filtered(NoteName::A).has_same_effect_chain(&filtered(NoteName::C)));
+    }
+
+    #[test]
+    fn muting_a_generator_keeps_the_cards_but_not_the_layout() {
+        let playing = noisy_reverb(NoteName::A, NoiseColor::White);
+        let mut playback = vec![CardPlayback::Normal; 5];
+        playback[0] = CardPlayback::Muted;
+
+        let muted = noisy_reverb(NoteName::A, NoiseColor::White).with_playback(playback);
+
+        assert!(muted.has_same_cards(&playing));
+        assert!(!muted.has_same_layout(&playing));
+        assert!(!noisy_reverb(NoteName::C, NoiseColor::White).has_same_cards(&playing));
+    }
+}
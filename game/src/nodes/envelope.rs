@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::GameTime;
+use crate::engine::game_config::AudioConfig;
+
+/// Gain envelope applied to every note of a lane, levels are relative to the output gain
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Envelope {
+    pub attack: GameTime,
+    pub decay: GameTime,
+    pub sustain: f32,
+    pub release: GameTime,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum EnvelopePreset {
+    Pluck,
+    Keys,
+    Pad,
+    Swell,
+}
+
+impl Envelope {
+    pub fn new(attack: GameTime, decay: GameTime, sustain: f32, release: GameTime) -> Self {
+        Self {
+            attack,
+            decay,
+            sustain,
+            release,
+        }
+    }
+
+    /// Envelope used when a lane has no envelope card
+    pub fn from_config(audio_config: &AudioConfig) -> Self {
+        Self::new(
+            audio_config.attack_time,
+            0.0,
+            1.0,
+            audio_config.release_time,
+        )
+    }
+
+    /// Points of the piecewise linear gain curve for a note held for `duration`,
+    /// as offsets from the note start. The release starts from whatever level
+    /// the note reached, so it's never cut short by an early note end
+    pub fn breakpoints(&self, duration: GameTime) -> Vec<(GameTime, f32)> {
+        let mut points = vec![(0.0, 0.0)];
+        let decay_end = self.attack + self.decay;
+
+        if duration > self.attack {
+            points.push((self.attack, 1.0));
+            if duration > decay_end {
+                points.push((decay_end, self.sustain));
+            }
+        }
+        points.push((duration, self.held_level_at(duration)));
+        points.push((duration + self.release, 0.0));
+        points
+    }
+
+    /// Level of the curve described by `breakpoints` at `time` since the note start
+    pub fn level_at(breakpoints: &[(GameTime, f32)], time: GameTime) -> f32 {
+        for pair in breakpoints.windows(2) {
+            let (t0, v0) = pair[0];
+            let (t1, v1) = pair[1];
+            if time < t1 {
+                if t1 <= t0 {
+                    return v1;
+                }
+                let fraction = ((time - t0) / (t1 - t0)).max(0.0) as f32;
+                return v0 + (v1 - v0) * fraction;
+            }
+        }
+        0.0
+    }
+
+    /// Time from the note start until the voice is silent
+    pub fn tail_end(&self, duration: GameTime) -> GameTime {
+        duration + self.release
+    }
+
+    fn held_level_at(&self, time: GameTime) -> f32 {
+        if time < self.attack {
+            (time / self.attack) as f32
+        } else if time < self.attack + self.decay {
+            let fraction = ((time - self.attack) / self.decay) as f32;
+            1.0 + (self.sustain - 1.0) * fraction
+        } else {
+            self.sustain
+        }
+    }
+}
+
+impl EnvelopePreset {
+    pub fn envelope(self) -> Envelope {
+        match self {
+            EnvelopePreset::Pluck => Envelope::new(0.005, 0.2, 0.0, 0.08),
+            EnvelopePreset::Keys => Envelope::new(0.01, 0.3, 0.6, 0.4),
+            EnvelopePreset::Pad => Envelope::new(0.4, 0.6, 0.8, 1.5),
+            EnvelopePreset::Swell => Envelope::new(1.0, 0.0, 1.0, 2.0),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use super::note_generator::MusicTime;
+use super::note_generator::NoteDuration;
+use super::oscillator::WaveShape;
+
+/// Max vibrato deviation at full depth
+const MAX_DETUNE_CENTS: f32 = 100.0;
+/// Keeps a fully modulated cutoff above 0 Hz
+const MAX_CUTOFF_DEVIATION: f32 = 0.9;
+
+/// Low frequency oscillator modulating a parameter of the next card in the graph
+#[derive(Clone, Copy, PartialEq)]
+pub struct Lfo {
+    pub shape: WaveShape,
+    pub rate: LfoRate,
+    pub depth: f32, // 0.0 to 1.0, scaled to the range of the modulated param
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LfoRate {
+    Hertz(f32),
+    Synced(MusicTime),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum LfoPreset {
+    SlowSine,
+    FastSine,
+    QuarterTriangle,
+    EighthSquare,
+}
+
+/// Param an LFO ends up modulating, decided by the card that follows it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModulationTarget {
+    /// Vibrato
+    OscillatorDetune,
+    /// Wah, index into the audio effects of the graph
+    FilterCutoff(usize),
+    /// Tremolo on the output of the whole chain
+    OutputGain,
+}
+
+impl Lfo {
+    pub fn new(shape: WaveShape, rate: LfoRate, depth: f32) -> Self {
+        Self { shape, rate, depth }
+    }
+
+    pub fn frequency(&self, bpm: u32) -> f32 {
+        match self.rate {
+            LfoRate::Hertz(hertz) => hertz,
+            LfoRate::Synced(period) => 1.0 / period.to_seconds(bpm) as f32,
+        }
+    }
+
+    /// Value the target param rests at and the amplitude the LFO swings it by
+    pub fn modulation_range(&self, target: ModulationTarget, base_value: f32) -> (f32, f32) {
+        match target {
+            ModulationTarget::OscillatorDetune => (base_value, self.depth * MAX_DETUNE_CENTS),
+            ModulationTarget::FilterCutoff(_) => {
+                (base_value, base_value * self.depth * MAX_CUTOFF_DEVIATION)
+            }
+            // Swing below unity so tremolo never boosts the signal
+            ModulationTarget::OutputGain => (1.0 - self.depth / 2.0, self.depth / 2.0),
+        }
+    }
+}
+
+impl LfoPreset {
+    pub fn lfo(self) -> Lfo {
+        match self {
+            LfoPreset::SlowSine => Lfo::new(WaveShape::Sine, LfoRate::Hertz(0.5), 0.6),
+            LfoPreset::FastSine => Lfo::new(WaveShape::Sine, LfoRate::Hertz(6.0), 0.3),
+            LfoPreset::QuarterTriangle => Lfo::new(
+                WaveShape::Triangle,
+                LfoRate::Synced(NoteDuration::Quarter.into()),
+                0.5,
+            ),
+            LfoPreset::EighthSquare => Lfo::new(
+                WaveShape::Square,
+                LfoRate::Synced(NoteDuration::Eighth.into()),
+                0.5,
+            ),
+        }
+    }
+}
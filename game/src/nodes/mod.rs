@@ -1,25 +1,41 @@
 use audio_effect::AudioEffect;
+use audio_effect::FilterTone;
+use envelope::Envelope;
+use lfo::Lfo;
 use note_effect::NoteEffect;
 use note_effect::NoteEffectType;
 use note_effect::Scale;
 use note_generator::NoteGenerator;
 use oscillator::Oscillator;
+use voice::Glide;
+use voice::Unison;
 
 use crate::nodes::audio_effect::DistortionCurve;
 use crate::render::widgets::card_widget::CardType;
+use crate::render::widgets::card_widget::PieceCard;
 
 pub mod audio_effect;
 pub mod audio_graph;
+pub mod envelope;
+pub mod lfo;
 pub mod note_effect;
 pub mod note_generator;
 pub mod oscillator;
+pub mod parameters;
+pub mod percussion;
+pub mod sampler;
+pub mod voice;
 
 #[derive(PartialEq, Clone)]
 pub enum AudioNode {
     NoteGenerator(NoteGenerator),
     NoteEffect(NoteEffect),
     Oscillator(Oscillator),
+    Envelope(Envelope),
     AudioEffect(AudioEffect),
+    Lfo(Lfo),
+    Unison(Unison),
+    Glide(Glide),
 }
 
 impl AudioNode {
@@ -39,6 +55,15 @@ impl AudioNode {
         }
     }
 
+    /// Node of the card with the parameters the player tuned applied over its preset
+    pub fn from_piece_card(card: &PieceCard) -> Self {
+        let mut node = Self::from_card(&card.card_type);
+        if let (AudioNode::AudioEffect(effect), Some(parameters)) = (&mut node, card.parameters) {
+            parameters.apply(effect);
+        }
+        node
+    }
+
     pub fn from_card(card: &CardType) -> Self {
         match card {
             CardType::NoteGenerator(Some(note_name)) => {
@@ -49,12 +74,42 @@ impl AudioNode {
             }
             CardType::Oscillator(wave) => Self::Oscillator(Oscillator::new(*wave)),
             CardType::Filter(filter_type) => {
-                Self::AudioEffect(AudioEffect::new_filter(*filter_type, 1000.0, 1.0, 0.0))
+                Self::AudioEffect(AudioEffect::new_tuned_filter(*filter_type, FilterTone::Mid))
+            }
+            CardType::FilterPreset(filter_type, tone) => {
+                Self::AudioEffect(AudioEffect::new_tuned_filter(*filter_type, *tone))
             }
             CardType::Distortion => {
                 Self::AudioEffect(AudioEffect::new_distortion(0.03, DistortionCurve::SoftClip))
             }
             CardType::Reverb => Self::AudioEffect(AudioEffect::new_reverb(2.0, 0.5, 0.5)),
+            CardType::Delay(division) => Self::AudioEffect(AudioEffect::new_delay(
+                division.time(),
+                0.4,
+                3000.0,
+                0.4,
+                1.0,
+                false,
+            )),
+            CardType::Chorus => Self::AudioEffect(AudioEffect::default_chorus()),
+            CardType::Flanger => Self::AudioEffect(AudioEffect::default_flanger()),
+            CardType::Phaser => Self::AudioEffect(AudioEffect::default_phaser()),
+            CardType::Compressor(preset) => {
+                Self::AudioEffect(AudioEffect::new_compressor(preset.parameters()))
+            }
+            CardType::Pan(position) => Self::AudioEffect(AudioEffect::new_fixed_pan(*position)),
+            CardType::AutoPan(rate) => Self::AudioEffect(AudioEffect::new_auto_pan(*rate)),
+            CardType::Bitcrusher(preset) => {
+                Self::AudioEffect(AudioEffect::new_bitcrusher(preset.parameters()))
+            }
+            CardType::PingPongDelay(division) => Self::AudioEffect(AudioEffect::new_delay(
+                division.time(),
+                0.5,
+                2500.0,
+                0.5,
+                1.0,
+                true,
+            )),
             CardType::ChordInScale(root, scale_type) => Self::NoteEffect(NoteEffect::new(
                 NoteEffectType::Scale(Scale::new(*root, *scale_type)),
             )),
@@ -62,6 +117,13 @@ impl AudioNode {
                 Self::NoteEffect(NoteEffect::new(NoteEffectType::ChangeLen(*amount)))
             }
             CardType::BlankNoteEffect => Self::NoteEffect(NoteEffect::new(NoteEffectType::Blank)),
+            CardType::Envelope(preset) => Self::Envelope(preset.envelope()),
+            CardType::Lfo(preset) => Self::Lfo(preset.lfo()),
+            CardType::Noise(color) => Self::Oscillator(Oscillator::Noise(*color)),
+            CardType::Drum(voice) => Self::Oscillator(Oscillator::Drum(*voice)),
+            CardType::Sampler(instrument) => Self::Oscillator(Oscillator::Sampler(*instrument)),
+            CardType::Unison(voices) => Self::Unison(voices.unison()),
+            CardType::Glide(time) => Self::Glide(time.glide()),
         }
     }
 
@@ -70,7 +132,10 @@ impl AudioNode {
             AudioNode::NoteGenerator(_) => AudioNodeType::NoteGenerator,
             AudioNode::NoteEffect(_) => AudioNodeType::NoteEffect,
             AudioNode::Oscillator(_) => AudioNodeType::Oscillator,
+            AudioNode::Envelope(_) => AudioNodeType::Envelope,
             AudioNode::AudioEffect(_) => AudioNodeType::AudioEffect,
+            AudioNode::Lfo(_) => AudioNodeType::Lfo,
+            AudioNode::Unison(_) | AudioNode::Glide(_) => AudioNodeType::Voice,
         }
     }
 }
@@ -80,7 +145,10 @@ pub enum AudioNodeType {
     NoteGenerator,
     NoteEffect,
     Oscillator,
+    Envelope,
     AudioEffect,
+    Lfo,
+    Voice,
 }
 
 impl AudioNodeType {
@@ -121,10 +189,44 @@ impl AudioNodeType {
                 vec![AudioNodeType::NoteGenerator, AudioNodeType::NoteEffect]
             }
             AudioNodeType::Oscillator => {
-                vec![AudioNodeType::NoteGenerator, AudioNodeType::NoteEffect]
+                vec![
+                    AudioNodeType::NoteGenerator,
+                    AudioNodeType::NoteEffect,
+                    AudioNodeType::Lfo,
+                ]
+            }
+            AudioNodeType::Voice => {
+                vec![
+                    AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Lfo,
+                ]
+            }
+            AudioNodeType::Envelope => {
+                vec![
+                    AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Lfo,
+                ]
             }
             AudioNodeType::AudioEffect => {
-                vec![AudioNodeType::Oscillator, AudioNodeType::AudioEffect]
+                vec![
+                    AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
+                    AudioNodeType::AudioEffect,
+                    AudioNodeType::Lfo,
+                ]
+            }
+            AudioNodeType::Lfo => {
+                vec![
+                    AudioNodeType::NoteGenerator,
+                    AudioNodeType::NoteEffect,
+                    AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
+                    AudioNodeType::AudioEffect,
+                ]
             }
         }
         .iter()
@@ -141,14 +243,24 @@ impl AudioNodeType {
                 vec![AudioNodeType::NoteGenerator, AudioNodeType::NoteEffect]
             }
             AudioNodeType::Oscillator => {
-                vec![AudioNodeType::NoteGenerator, AudioNodeType::NoteEffect]
+                vec![
+                    AudioNodeType::NoteGenerator,
+                    AudioNodeType::NoteEffect,
+                    AudioNodeType::Lfo,
+                ]
             }
-            AudioNodeType::AudioEffect => {
+            AudioNodeType::Voice
+            | AudioNodeType::Envelope
+            | AudioNodeType::AudioEffect
+            | AudioNodeType::Lfo => {
                 vec![
                     AudioNodeType::NoteGenerator,
                     AudioNodeType::NoteEffect,
                     AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
                     AudioNodeType::AudioEffect,
+                    AudioNodeType::Lfo,
                 ]
             }
         }
@@ -164,6 +276,7 @@ impl AudioNodeType {
                     AudioNodeType::NoteGenerator,
                     AudioNodeType::NoteEffect,
                     AudioNodeType::Oscillator,
+                    AudioNodeType::Lfo,
                 ]
             }
             AudioNodeType::NoteEffect => {
@@ -171,13 +284,36 @@ impl AudioNodeType {
                     AudioNodeType::NoteGenerator,
                     AudioNodeType::NoteEffect,
                     AudioNodeType::Oscillator,
+                    AudioNodeType::Lfo,
                 ]
             }
             AudioNodeType::Oscillator => {
-                vec![AudioNodeType::AudioEffect]
+                vec![
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
+                    AudioNodeType::AudioEffect,
+                    AudioNodeType::Lfo,
+                ]
             }
+            AudioNodeType::Voice => {
+                vec![
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
+                    AudioNodeType::AudioEffect,
+                    AudioNodeType::Lfo,
+                ]
+            }
+            AudioNodeType::Envelope => vec![AudioNodeType::AudioEffect, AudioNodeType::Lfo],
             AudioNodeType::AudioEffect => {
-                vec![AudioNodeType::AudioEffect]
+                vec![AudioNodeType::AudioEffect, AudioNodeType::Lfo]
+            }
+            AudioNodeType::Lfo => {
+                vec![
+                    AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
+                    AudioNodeType::AudioEffect,
+                ]
             }
         }
         .iter()
@@ -187,28 +323,30 @@ impl AudioNodeType {
 
     fn allowed_after_loose(&self, t: &AudioNodeType) -> bool {
         match self {
-            AudioNodeType::NoteGenerator => {
+            AudioNodeType::NoteGenerator | AudioNodeType::NoteEffect => {
                 vec![
                     AudioNodeType::NoteGenerator,
                     AudioNodeType::NoteEffect,
                     AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
                     AudioNodeType::AudioEffect,
+                    AudioNodeType::Lfo,
                 ]
             }
-            AudioNodeType::NoteEffect => {
+            AudioNodeType::Oscillator
+            | AudioNodeType::Voice
+            | AudioNodeType::Envelope
+            | AudioNodeType::AudioEffect
+            | AudioNodeType::Lfo => {
                 vec![
-                    AudioNodeType::NoteGenerator,
-                    AudioNodeType::NoteEffect,
                     AudioNodeType::Oscillator,
+                    AudioNodeType::Voice,
+                    AudioNodeType::Envelope,
                     AudioNodeType::AudioEffect,
+                    AudioNodeType::Lfo,
                 ]
             }
-            AudioNodeType::Oscillator => {
-                vec![AudioNodeType::AudioEffect]
-            }
-            AudioNodeType::AudioEffect => {
-                vec![AudioNodeType::AudioEffect]
-            }
         }
         .iter()
         .find(|&c| c == t)
@@ -1,5 +1,4 @@
 use crate::nodes::note_generator::Note;
-use crate::nodes::note_generator::NoteEvent;
 use crate::nodes::note_generator::NoteGenerator;
 use crate::nodes::note_generator::NoteName;
 use serde::{Deserialize, Serialize};
@@ -30,8 +29,8 @@ impl NoteEffect {
 
                         vec![
                             event.clone(),
-                            NoteEvent::new(third, event.start, event.duration),
-                            NoteEvent::new(fifth, event.start, event.duration),
+                            event.with_note(third),
+                            event.with_note(fifth),
                         ]
                     })
                     .collect();
@@ -48,7 +47,7 @@ impl NoteEffect {
 
                         chord_notes
                             .into_iter()
-                            .map(|note| NoteEvent::new(note, event.start, event.duration))
+                            .map(|note| event.with_note(note))
                             .collect::<Vec<_>>()
                     })
                     .collect();
@@ -65,7 +64,7 @@ impl NoteEffect {
 
                         chord_notes
                             .into_iter()
-                            .map(|note| NoteEvent::new(note, event.start, event.duration))
+                            .map(|note| event.with_note(note))
                             .collect::<Vec<_>>()
                     })
                     .collect();
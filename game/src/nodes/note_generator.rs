@@ -28,6 +28,16 @@ impl NoteGenerator {
         NoteGenerator::new(NoteDuration::Quarter.into(), vec![note_event])
     }
 
+    /// Same notes, marked as generated by the card at `card`
+    pub fn with_card(&self, card: usize) -> NoteGenerator {
+        let notes = self
+            .notes
+            .iter()
+            .map(|note| NoteEvent { card, ..*note })
+            .collect();
+        NoteGenerator::new(self.loop_length, notes)
+    }
+
     /// Combine multiple note generators into a single one
     pub fn combine(generators: &[NoteGenerator]) -> NoteGenerator {
         if generators.is_empty() {
@@ -76,7 +86,7 @@ impl From<NoteDuration> for MusicTime {
 /// Represents time in musical terms, independently of BPM
 ///
 /// Given the BPM, you can convert `MusicTime` to `GameTime`
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct MusicTime {
     ticks: u32,
 }
@@ -129,6 +139,8 @@ pub struct NoteEvent {
     pub note: Note,
     pub start: MusicTime,
     pub duration: MusicTime,
+    /// Index of the card in the graph that generated the note
+    pub card: usize,
 }
 
 impl NoteEvent {
@@ -137,9 +149,15 @@ impl NoteEvent {
             note,
             start,
             duration,
+            card: 0,
         }
     }
 
+    /// Note played at the same time, coming from the same card
+    pub fn with_note(&self, note: Note) -> Self {
+        Self { note, ..*self }
+    }
+
     pub fn shifted(&self, time: MusicTime) -> Self {
         Self {
             start: self.start + time,
@@ -1,18 +1,182 @@
+use std::f32::consts::PI;
+
 use serde::{Deserialize, Serialize};
 
+use crate::core::Random;
+
+use super::percussion::DrumVoice;
+use super::sampler::SampleInstrument;
+
+/// Sound source of a lane
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub struct Oscillator {
-    pub wave_shape: WaveShape,
+pub enum Oscillator {
+    Wave(WaveShape),
+    Noise(NoiseColor),
+    /// Fixed drum voice, or a kit picking the voice from the note name when `None`
+    Drum(Option<DrumVoice>),
+    Sampler(SampleInstrument),
 }
 
+// NOTE: new variants must only be appended, saved pieces store the variant index
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WaveShape {
     Sine,
     Square,
+    Sawtooth,
+    Triangle,
+    Wavetable(WavetablePreset),
+    Fm(FmPreset),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WavetablePreset {
+    Organ,
+    Brass,
+    Bell,
+}
+
+/// Two-operator FM: a sine modulator at `ratio` times the note frequency
+/// drives the frequency of a sine carrier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FmPreset {
+    ElectricPiano,
+    Bell,
+    Bass,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoiseColor {
+    White,
+    Pink,
+    Brown,
 }
 
 impl Oscillator {
     pub fn new(wave_shape: WaveShape) -> Oscillator {
-        Oscillator { wave_shape }
+        Oscillator::Wave(wave_shape)
+    }
+}
+
+impl WaveShape {
+    /// One period of the waveform normalized to a peak of 1.0, the same way Web Audio
+    /// normalizes its built-in and periodic waves. For FM only the carrier is returned
+    pub fn single_cycle(self, len: usize) -> Vec<f32> {
+        let harmonics = match self {
+            WaveShape::Wavetable(preset) => preset.harmonics(),
+            _ => vec![],
+        };
+        let mut cycle: Vec<f32> = (0..len)
+            .map(|i| {
+                let phase = i as f32 / len as f32;
+                match self {
+                    WaveShape::Sine | WaveShape::Fm(_) => (2.0 * PI * phase).sin(),
+                    WaveShape::Square => {
+                        if phase < 0.5 {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    }
+                    WaveShape::Sawtooth => 2.0 * ((phase + 0.5) % 1.0) - 1.0,
+                    WaveShape::Triangle => 1.0 - 4.0 * ((phase + 0.25) % 1.0 - 0.5).abs(),
+                    WaveShape::Wavetable(_) => harmonics
+                        .iter()
+                        .enumerate()
+                        .map(|(k, amp)| amp * (2.0 * PI * k as f32 * phase).sin())
+                        .sum(),
+                }
+            })
+            .collect();
+
+        let peak = cycle.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        if peak > 0.0 {
+            cycle.iter_mut().for_each(|s| *s /= peak);
+        }
+        cycle
+    }
+}
+
+impl WavetablePreset {
+    /// Sine amplitudes per harmonic, index 0 is the DC offset as in `createPeriodicWave`
+    pub fn harmonics(self) -> Vec<f32> {
+        match self {
+            // Drawbar-like stack of octaves and fifths
+            WavetablePreset::Organ => vec![0.0, 1.0, 0.8, 0.6, 0.5, 0.0, 0.3, 0.0, 0.2],
+            WavetablePreset::Brass => {
+                vec![0.0, 1.0, 0.7, 0.55, 0.45, 0.35, 0.28, 0.22, 0.17, 0.13, 0.1]
+            }
+            // Sparse upper partials give a metallic, bell-like tone
+            WavetablePreset::Bell => {
+                vec![0.0, 1.0, 0.0, 0.2, 0.6, 0.0, 0.0, 0.4, 0.0, 0.0, 0.0, 0.25]
+            }
+        }
+    }
+}
+
+impl FmPreset {
+    /// Modulator frequency relative to the note frequency
+    pub fn ratio(self) -> f32 {
+        match self {
+            FmPreset::ElectricPiano => 1.0,
+            // Inharmonic ratio gives the clangorous partials of a bell
+            FmPreset::Bell => 3.5,
+            FmPreset::Bass => 0.5,
+        }
+    }
+
+    /// Peak frequency deviation of the carrier relative to the modulator frequency
+    pub fn index(self) -> f32 {
+        match self {
+            FmPreset::ElectricPiano => 1.5,
+            FmPreset::Bell => 3.0,
+            FmPreset::Bass => 2.0,
+        }
+    }
+
+    /// Modulator frequency and the gain applied to it for a note at `frequency`
+    pub fn modulator(self, frequency: f32) -> (f32, f32) {
+        let modulator_frequency = frequency * self.ratio();
+        (modulator_frequency, modulator_frequency * self.index())
+    }
+}
+
+/// Length of the looping buffer noise and drum voices play
+const NOISE_BUFFER_SECONDS: f32 = 2.0;
+
+impl NoiseColor {
+    pub fn buffer(self, sample_rate: f32, random: &mut Random) -> Vec<f32> {
+        let len = (sample_rate * NOISE_BUFFER_SECONDS) as usize;
+        self.generate(len, || random.next_f32())
+    }
+
+    /// `len` samples of noise in [-1, 1] shaped from the white noise `random` produces
+    pub fn generate(self, len: usize, mut random: impl FnMut() -> f32) -> Vec<f32> {
+        let mut white = || random() * 2.0 - 1.0;
+        match self {
+            NoiseColor::White => (0..len).map(|_| white()).collect(),
+            // Paul Kellet's economy filter, -3dB per octave
+            NoiseColor::Pink => {
+                let (mut b0, mut b1, mut b2) = (0.0, 0.0, 0.0);
+                (0..len)
+                    .map(|_| {
+                        let w = white();
+                        b0 = 0.99765 * b0 + w * 0.0990460;
+                        b1 = 0.96300 * b1 + w * 0.2965164;
+                        b2 = 0.57000 * b2 + w * 1.0526913;
+                        (b0 + b1 + b2 + w * 0.1848) * 0.11
+                    })
+                    .collect()
+            }
+            // Leaky integration of white noise, -6dB per octave
+            NoiseColor::Brown => {
+                let mut last = 0.0;
+                (0..len)
+                    .map(|_| {
+                        last = (last + 0.02 * white()) / 1.02;
+                        last * 3.5
+                    })
+                    .collect()
+            }
+        }
     }
 }
@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+
+use super::audio_effect::AudioEffect;
+use super::audio_effect::MAX_DELAY_FEEDBACK;
+
+/// Values of an effect card the player can tune, the rest of the effect keeps the
+/// card's preset
+// NOTE: new variants must only be appended, saved pieces store the variant index
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CardParameters {
+    Filter {
+        frequency: f32,
+        q: f32,
+        gain: f32,
+    },
+    Distortion {
+        amount: f32,
+    },
+    Reverb {
+        decay_time: f32,
+        wet_level: f32,
+        dry_level: f32,
+    },
+    Delay {
+        feedback: f32,
+        damping: f32,
+        wet_level: f32,
+        dry_level: f32,
+    },
+    ModulatedDelay {
+        rate: f32,
+        feedback: f32,
+        wet_level: f32,
+        dry_level: f32,
+    },
+    Phaser {
+        frequency: f32,
+        depth: f32,
+        rate: f32,
+        wet_level: f32,
+        dry_level: f32,
+    },
+    Compressor {
+        threshold: f32,
+        knee: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+    },
+    Pan {
+        position: f32,
+        depth: f32,
+    },
+    Bitcrusher {
+        bits: f32,
+        sample_rate: f32,
+    },
+}
+
+/// How much of an effect card is heard, every effect is crossfaded with its input
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EffectMix {
+    /// A bypassed card passes its input through untouched
+    pub bypassed: bool,
+    /// Share of the effect in the output, the rest is the input
+    pub wet: f32,
+}
+
+impl Default for EffectMix {
+    fn default() -> Self {
+        Self {
+            bypassed: false,
+            wet: 1.0,
+        }
+    }
+}
+
+impl EffectMix {
+    pub const RANGE: ParameterRange = ParameterRange::linear("Mix", 0.0, 1.0);
+
+    /// Gains of the input and of the effect output
+    pub fn gains(&self) -> (f32, f32) {
+        if self.bypassed {
+            (1.0, 0.0)
+        } else {
+            let wet = self.wet.clamp(0.0, 1.0);
+            (1.0 - wet, wet)
+        }
+    }
+}
+
+/// Bounds of the control editing a single parameter
+#[derive(Clone, Copy, Debug)]
+pub struct ParameterRange {
+    pub name: &'static str,
+    pub min: f32,
+    pub max: f32,
+    /// Frequencies and times are edited on a log scale, so every octave gets the same room
+    pub logarithmic: bool,
+}
+
+impl ParameterRange {
+    const fn linear(name: &'static str, min: f32, max: f32) -> Self {
+        Self {
+            name,
+            min,
+            max,
+            logarithmic: false,
+        }
+    }
+
+    const fn logarithmic(name: &'static str, min: f32, max: f32) -> Self {
+        Self {
+            name,
+            min,
+            max,
+            logarithmic: true,
+        }
+    }
+}
+
+impl CardParameters {
+    pub fn from_effect(effect: &AudioEffect) -> Self {
+        match effect {
+            AudioEffect::Filter(params) => CardParameters::Filter {
+                frequency: params.frequency,
+                q: params.q,
+                gain: params.gain,
+            },
+            AudioEffect::Distortion(params) => CardParameters::Distortion {
+                amount: params.amount,
+            },
+            AudioEffect::Reverb(params) => CardParameters::Reverb {
+                decay_time: params.decay_time,
+                wet_level: params.wet_level,
+                dry_level: params.dry_level,
+            },
+            AudioEffect::Delay(params) => CardParameters::Delay {
+                feedback: params.feedback,
+                damping: params.damping,
+                wet_level: params.wet_level,
+                dry_level: params.dry_level,
+            },
+            AudioEffect::Chorus(params) | AudioEffect::Flanger(params) => {
+                CardParameters::ModulatedDelay {
+                    rate: params.rate,
+                    feedback: params.feedback,
+                    wet_level: params.wet_level,
+                    dry_level: params.dry_level,
+                }
+            }
+            AudioEffect::Phaser(params) => CardParameters::Phaser {
+                frequency: params.frequency,
+                depth: params.depth,
+                rate: params.rate,
+                wet_level: params.wet_level,
+                dry_level: params.dry_level,
+            },
+            AudioEffect::Compressor(params) => CardParameters::Compressor {
+                threshold: params.threshold,
+                knee: params.knee,
+                ratio: params.ratio,
+                attack: params.attack,
+                release: params.release,
+            },
+            AudioEffect::Pan(params) => CardParameters::Pan {
+                position: params.position,
+                depth: params.depth,
+            },
+            AudioEffect::Bitcrusher(params) => CardParameters::Bitcrusher {
+                bits: params.bits,
+                sample_rate: params.sample_rate,
+            },
+        }
+    }
+
+    /// Overrides the preset values of the effect, parameters of another kind of effect
+    /// are ignored
+    pub fn apply(&self, effect: &mut AudioEffect) {
+        match (self, effect) {
+            (CardParameters::Filter { frequency, q, gain }, AudioEffect::Filter(params)) => {
+                params.frequency = *frequency;
+                params.q = *q;
+                params.gain = *gain;
+            }
+            (CardParameters::Distortion { amount }, AudioEffect::Distortion(params)) => {
+                params.amount = *amount;
+            }
+            (
+                CardParameters::Reverb {
+                    decay_time,
+                    wet_level,
+                    dry_level,
+                },
+                AudioEffect::Reverb(params),
+            ) => {
+                params.decay_time = *decay_time;
+                params.wet_level = *wet_level;
+                params.dry_level = *dry_level;
+            }
+            (
+                CardParameters::Delay {
+                    feedback,
+                    damping,
+                    wet_level,
+                    dry_level,
+                },
+                AudioEffect::Delay(params),
+            ) => {
+                params.feedback = *feedback;
+                params.damping = *damping;
+                params.wet_level = *wet_level;
+                params.dry_level = *dry_level;
+            }
+            (
+                CardParameters::ModulatedDelay {
+                    rate,
+                    feedback,
+                    wet_level,
+                    dry_level,
+                },
+                AudioEffect::Chorus(params) | AudioEffect::Flanger(params),
+            ) => {
+                params.rate = *rate;
+                params.feedback = *feedback;
+                params.wet_level = *wet_level;
+                params.dry_level = *dry_level;
+            }
+            (
+                CardParameters::Phaser {
+                    frequency,
+                    depth,
+                    rate,
+                    wet_level,
+                    dry_level,
+                },
+                AudioEffect::Phaser(params),
+            ) => {
+                params.frequency = *frequency;
+                params.depth = *depth;
+                params.rate = *rate;
+                params.wet_level = *wet_level;
+                params.dry_level = *dry_level;
+            }
+            (
+                CardParameters::Compressor {
+                    threshold,
+                    knee,
+                    ratio,
+                    attack,
+                    release,
+                },
+                AudioEffect::Compressor(params),
+            ) => {
+                params.threshold = *threshold;
+                params.knee = *knee;
+                params.ratio = *ratio;
+                params.attack = *attack;
+                params.release = *release;
+            }
+            (CardParameters::Pan { position, depth }, AudioEffect::Pan(params)) => {
+                params.position = *position;
+                params.depth = *depth;
+            }
+            (CardParameters::Bitcrusher { bits, sample_rate }, AudioEffect::Bitcrusher(params)) => {
+                params.bits = *bits;
+                params.sample_rate = *sample_rate;
+            }
+            _ => (),
+        }
+    }
+
+    /// Every parameter paired with the range of its control, in display order
+    pub fn controls(&mut self) -> Vec<(ParameterRange, &mut f32)> {
+        match self {
+            CardParameters::Filter { frequency, q, gain } => vec![
+                (
+                    ParameterRange::logarithmic("Cutoff", 20.0, 20000.0),
+                    frequency,
+                ),
+                (ParameterRange::linear("Q", 0.1, 20.0), q),
+                (ParameterRange::linear("Gain", -24.0, 24.0), gain),
+            ],
+            CardParameters::Distortion { amount } => {
+                vec![(ParameterRange::linear("Amount", 0.0, 0.5), amount)]
+            }
+            CardParameters::Reverb {
+                decay_time,
+                wet_level,
+                dry_level,
+            } => vec![
+                (ParameterRange::logarithmic("Decay", 0.1, 10.0), decay_time),
+                (ParameterRange::linear("Wet", 0.0, 1.0), wet_level),
+                (ParameterRange::linear("Dry", 0.0, 1.0), dry_level),
+            ],
+            CardParameters::Delay {
+                feedback,
+                damping,
+                wet_level,
+                dry_level,
+            } => vec![
+                (
+                    ParameterRange::linear("Feedback", 0.0, MAX_DELAY_FEEDBACK),
+                    feedback,
+                ),
+                (
+                    ParameterRange::logarithmic("Damping", 200.0, 16000.0),
+                    damping,
+                ),
+                (ParameterRange::linear("Wet", 0.0, 1.0), wet_level),
+                (ParameterRange::linear("Dry", 0.0, 1.0), dry_level),
+            ],
+            CardParameters::ModulatedDelay {
+                rate,
+                feedback,
+                wet_level,
+                dry_level,
+            } => vec![
+                (ParameterRange::logarithmic("Rate", 0.05, 10.0), rate),
+                (
+                    ParameterRange::linear("Feedback", 0.0, MAX_DELAY_FEEDBACK),
+                    feedback,
+                ),
+                (ParameterRange::linear("Wet", 0.0, 1.0), wet_level),
+                (ParameterRange::linear("Dry", 0.0, 1.0), dry_level),
+            ],
+            CardParameters::Phaser {
+                frequency,
+                depth,
+                rate,
+                wet_level,
+                dry_level,
+            } => vec![
+                (
+                    ParameterRange::logarithmic("Center", 100.0, 5000.0),
+                    frequency,
+                ),
+                (ParameterRange::linear("Depth", 0.0, 0.95), depth),
+                (ParameterRange::logarithmic("Rate", 0.05, 10.0), rate),
+                (ParameterRange::linear("Wet", 0.0, 1.0), wet_level),
+                (ParameterRange::linear("Dry", 0.0, 1.0), dry_level),
+            ],
+            CardParameters::Compressor {
+                threshold,
+                knee,
+                ratio,
+                attack,
+                release,
+            } => vec![
+                (ParameterRange::linear("Threshold", -60.0, 0.0), threshold),
+                (ParameterRange::linear("Knee", 0.0, 40.0), knee),
+                (ParameterRange::linear("Ratio", 1.0, 20.0), ratio),
+                (ParameterRange::logarithmic("Attack", 0.001, 1.0), attack),
+                (ParameterRange::logarithmic("Release", 0.01, 1.0), release),
+            ],
+            CardParameters::Pan { position, depth } => vec![
+                (ParameterRange::linear("Position", -1.0, 1.0), position),
+                (ParameterRange::linear("Depth", 0.0, 1.0), depth),
+            ],
+            CardParameters::Bitcrusher { bits, sample_rate } => vec![
+                (ParameterRange::linear("Bits", 1.0, 16.0), bits),
+                (
+                    ParameterRange::logarithmic("Rate", 1000.0, 48000.0),
+                    sample_rate,
+                ),
+            ],
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::GameTime;
+
+use super::audio_effect::FilterParameters;
+use super::audio_effect::FilterType;
+use super::envelope::Envelope;
+use super::note_generator::NoteName;
+use super::oscillator::WaveShape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DrumVoice {
+    Kick,
+    Snare,
+    HiHat,
+    Clap,
+}
+
+/// One sound a drum hit is layered from
+#[derive(Clone, PartialEq)]
+pub struct DrumLayer {
+    pub source: DrumSource,
+    pub filter: Option<FilterParameters>,
+    /// One-shot envelope, the layer is silent after its attack and decay
+    pub envelope: Envelope,
+    pub level: f32,
+    /// Delay from the note start
+    pub offset: GameTime,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DrumSource {
+    /// Oscillator sweeping exponentially from `start_frequency` to `end_frequency`
+    Tone {
+        shape: WaveShape,
+        start_frequency: f32,
+        end_frequency: f32,
+        sweep_time: GameTime,
+    },
+    Noise,
+}
+
+impl DrumVoice {
+    /// Kit mapping following the General MIDI drum map, toms fall back to the kick
+    pub fn from_note_name(note_name: NoteName) -> DrumVoice {
+        match note_name {
+            NoteName::D | NoteName::CSharp | NoteName::E => DrumVoice::Snare,
+            NoteName::DSharp => DrumVoice::Clap,
+            NoteName::FSharp | NoteName::GSharp | NoteName::ASharp => DrumVoice::HiHat,
+            NoteName::C | NoteName::F | NoteName::G | NoteName::A | NoteName::B => DrumVoice::Kick,
+        }
+    }
+
+    pub fn layers(self) -> Vec<DrumLayer> {
+        match self {
+            DrumVoice::Kick => vec![DrumLayer::tone(
+                WaveShape::Sine,
+                (150.0, 45.0, 0.12),
+                Envelope::new(0.002, 0.35, 0.0, 0.0),
+                1.0,
+            )],
+            DrumVoice::Snare => vec![
+                DrumLayer::tone(
+                    WaveShape::Triangle,
+                    (220.0, 180.0, 0.05),
+                    Envelope::new(0.001, 0.1, 0.0, 0.0),
+                    0.5,
+                ),
+                DrumLayer::noise(1500.0, Envelope::new(0.001, 0.18, 0.0, 0.0), 0.7, 0.0),
+            ],
+            DrumVoice::HiHat => vec![DrumLayer::noise(
+                7000.0,
+                Envelope::new(0.001, 0.05, 0.0, 0.0),
+                0.5,
+                0.0,
+            )],
+            // A few quick bursts followed by a longer tail, like several hands clapping
+            DrumVoice::Clap => vec![
+                DrumLayer::noise(1000.0, Envelope::new(0.001, 0.012, 0.0, 0.0), 0.8, 0.0),
+                DrumLayer::noise(1000.0, Envelope::new(0.001, 0.012, 0.0, 0.0), 0.8, 0.012),
+                DrumLayer::noise(1000.0, Envelope::new(0.001, 0.012, 0.0, 0.0), 0.8, 0.024),
+                DrumLayer::noise(1000.0, Envelope::new(0.001, 0.2, 0.0, 0.0), 0.7, 0.036),
+            ],
+        }
+    }
+}
+
+impl DrumLayer {
+    fn tone(
+        shape: WaveShape,
+        (start_frequency, end_frequency, sweep_time): (f32, f32, GameTime),
+        envelope: Envelope,
+        level: f32,
+    ) -> Self {
+        Self {
+            source: DrumSource::Tone {
+                shape,
+                start_frequency,
+                end_frequency,
+                sweep_time,
+            },
+            filter: None,
+            envelope,
+            level,
+            offset: 0.0,
+        }
+    }
+
+    fn noise(highpass: f32, envelope: Envelope, level: f32, offset: GameTime) -> Self {
+        Self {
+            source: DrumSource::Noise,
+            filter: Some(FilterParameters {
+                filter_type: FilterType::HighPass,
+                frequency: highpass,
+                q: 0.0,
+                gain: 0.0,
+            }),
+            envelope,
+            level,
+            offset,
+        }
+    }
+
+    /// Note length the envelope is played for, so that it decays to silence
+    pub fn duration(&self) -> GameTime {
+        self.envelope.attack + self.envelope.decay
+    }
+
+    /// Time from the note start until the layer is silent
+    pub fn end(&self) -> GameTime {
+        self.offset + self.envelope.tail_end(self.duration())
+    }
+
+    /// Frequency of a tone layer at `time` since the layer start
+    pub fn frequency_at(&self, time: GameTime) -> f32 {
+        match self.source {
+            DrumSource::Tone {
+                start_frequency,
+                end_frequency,
+                sweep_time,
+                ..
+            } => {
+                let fraction = (time / sweep_time).clamp(0.0, 1.0) as f32;
+                start_frequency * (end_frequency / start_frequency).powf(fraction)
+            }
+            DrumSource::Noise => 0.0,
+        }
+    }
+}
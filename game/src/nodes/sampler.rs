@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use super::note_generator::Note;
+use super::note_generator::NoteName;
+
+/// Built-in sampled instruments, saved pieces reference them by variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SampleInstrument {
+    Keys,
+    Pad,
+}
+
+/// WAV files in `resources/samples`, loaded once at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SampleAsset {
+    KeysC2,
+    KeysC3,
+    KeysC4,
+    PadC3,
+}
+
+/// Range of notes played by repitching a single sample
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SampleZone {
+    pub asset: SampleAsset,
+    /// Pitch the sample was recorded at
+    pub root: Note,
+    pub low: Note,
+    pub high: Note,
+    /// Loop start and end in frames of the sample, the sample plays once if `None`
+    pub loop_points: Option<(usize, usize)>,
+}
+
+impl SampleInstrument {
+    pub fn zones(self) -> Vec<SampleZone> {
+        match self {
+            SampleInstrument::Keys => vec![
+                SampleZone::new(
+                    SampleAsset::KeysC2,
+                    Note::new(2, NoteName::C),
+                    (Note::new(0, NoteName::C), Note::new(2, NoteName::FSharp)),
+                ),
+                SampleZone::new(
+                    SampleAsset::KeysC3,
+                    Note::new(3, NoteName::C),
+                    (Note::new(2, NoteName::G), Note::new(3, NoteName::FSharp)),
+                ),
+                SampleZone::new(
+                    SampleAsset::KeysC4,
+                    Note::new(4, NoteName::C),
+                    (Note::new(3, NoteName::G), Note::new(7, NoteName::B)),
+                ),
+            ],
+            // Loop spans a whole number of periods of the sample, so it repeats without clicks
+            SampleInstrument::Pad => vec![SampleZone::new(
+                SampleAsset::PadC3,
+                Note::new(3, NoteName::C),
+                (Note::new(0, NoteName::C), Note::new(7, NoteName::B)),
+            )
+            .with_loop(11025, 33075)],
+        }
+    }
+
+    /// Zone whose range contains `note`, or the one with the closest root
+    pub fn zone_for(self, note: &Note) -> Option<SampleZone> {
+        let zones = self.zones();
+        let semitones = note.to_semitones();
+        zones
+            .iter()
+            .find(|zone| (zone.low.to_semitones()..=zone.high.to_semitones()).contains(&semitones))
+            .or_else(|| {
+                zones
+                    .iter()
+                    .min_by_key(|zone| (zone.root.to_semitones() - semitones).abs())
+            })
+            .copied()
+    }
+}
+
+impl SampleAsset {
+    pub const ALL: [SampleAsset; 4] = [
+        SampleAsset::KeysC2,
+        SampleAsset::KeysC3,
+        SampleAsset::KeysC4,
+        SampleAsset::PadC3,
+    ];
+
+    pub fn path(self) -> &'static str {
+        match self {
+            SampleAsset::KeysC2 => "resources/samples/keys_c2.wav",
+            SampleAsset::KeysC3 => "resources/samples/keys_c3.wav",
+            SampleAsset::KeysC4 => "resources/samples/keys_c4.wav",
+            SampleAsset::PadC3 => "resources/samples/pad_c3.wav",
+        }
+    }
+}
+
+impl SampleZone {
+    fn new(asset: SampleAsset, root: Note, (low, high): (Note, Note)) -> Self {
+        Self {
+            asset,
+            root,
+            low,
+            high,
+            loop_points: None,
+        }
+    }
+
+    fn with_loop(mut self, start: usize, end: usize) -> Self {
+        self.loop_points = Some((start, end));
+        self
+    }
+
+    /// Playback rate that shifts the sample from its root to `note`
+    pub fn playback_rate(&self, note: &Note) -> f32 {
+        let semitones = note.to_semitones() - self.root.to_semitones();
+        2f32.powf(semitones as f32 / 12.0)
+    }
+}
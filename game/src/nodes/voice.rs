@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::GameTime;
+use crate::nodes::note_generator::NoteEvent;
+
+/// Plays every note with several detuned oscillators spread across the stereo field
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Unison {
+    pub voices: u32,
+    /// Detune of the outermost voices in cents
+    pub detune: f32,
+    /// Pan of the outermost voices, 0.0 to 1.0
+    pub spread: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum UnisonVoices {
+    Two,
+    Four,
+    Seven,
+}
+
+/// Mono voice: a single oscillator plays every note of the lane, sliding between pitches
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Glide {
+    /// Time to reach the pitch of the next note
+    pub time: GameTime,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GlideTime {
+    Short,
+    Medium,
+    Long,
+}
+
+impl Unison {
+    pub fn new(voices: u32, detune: f32, spread: f32) -> Self {
+        Self {
+            voices,
+            detune,
+            spread,
+        }
+    }
+
+    /// Detune in cents and pan of every voice, spaced evenly between the outermost ones
+    pub fn voice_offsets(&self) -> Vec<(f32, f32)> {
+        if self.voices <= 1 {
+            return vec![(0.0, 0.0)];
+        }
+        (0..self.voices)
+            .map(|i| {
+                let position = 2.0 * i as f32 / (self.voices - 1) as f32 - 1.0;
+                (position * self.detune, position * self.spread)
+            })
+            .collect()
+    }
+
+    /// Gain of each voice, keeps the overall loudness close to a single oscillator
+    pub fn voice_gain(&self) -> f32 {
+        1.0 / (self.voices.max(1) as f32).sqrt()
+    }
+}
+
+impl UnisonVoices {
+    pub fn unison(self) -> Unison {
+        match self {
+            UnisonVoices::Two => Unison::new(2, 10.0, 0.5),
+            UnisonVoices::Four => Unison::new(4, 15.0, 0.7),
+            UnisonVoices::Seven => Unison::new(7, 25.0, 0.9),
+        }
+    }
+}
+
+impl Glide {
+    pub fn new(time: GameTime) -> Self {
+        Self { time }
+    }
+
+    /// Notes a mono voice plays, in order of their start. Of notes starting together
+    /// only the highest one is kept
+    pub fn mono_notes(notes: &[NoteEvent], bpm: u32) -> Vec<MonoNote> {
+        let mut notes = notes.to_vec();
+        notes.sort_by(|a, b| {
+            a.start
+                .cmp(&b.start)
+                .then(b.note.to_semitones().cmp(&a.note.to_semitones()))
+        });
+        notes.dedup_by(|b, a| a.start == b.start);
+        notes
+            .iter()
+            .map(|note_event| MonoNote {
+                frequency: note_event.note.to_frequancy(),
+                start: note_event.start.to_seconds(bpm),
+                duration: note_event.duration.to_seconds(bpm),
+            })
+            .collect()
+    }
+
+    /// Pitch slides into every note as (from, to, start, end). A slide is cut short
+    /// when the next note starts before it ends
+    pub fn ramps(&self, notes: &[MonoNote]) -> Vec<(f32, f32, GameTime, GameTime)> {
+        let mut previous_frequency = match notes.first() {
+            Some(first) => first.frequency,
+            None => return vec![],
+        };
+        let mut ramps = vec![];
+        for (i, note) in notes.iter().enumerate() {
+            let mut end = note.start + self.time;
+            if let Some(next) = notes.get(i + 1) {
+                end = end.min(next.start);
+            }
+            ramps.push((previous_frequency, note.frequency, note.start, end));
+            previous_frequency = note.frequency;
+        }
+        ramps
+    }
+
+    /// Start and duration of every run of notes played legato, that is starting
+    /// before the release of the previous note has ended. Only the first note of
+    /// a phrase triggers the envelope
+    pub fn phrases(notes: &[MonoNote], release: GameTime) -> Vec<(GameTime, GameTime)> {
+        let mut phrases: Vec<(GameTime, GameTime)> = vec![];
+        for note in notes {
+            let end = note.start + note.duration;
+            match phrases.last_mut() {
+                Some((start, duration)) if note.start < *start + *duration + release => {
+                    *duration = duration.max(end - *start);
+                }
+                _ => phrases.push((note.start, note.duration)),
+            }
+        }
+        phrases
+    }
+
+    /// Frequency the `ramps` reach at `time`, following Web Audio's exponential ramps
+    pub fn frequency_at(ramps: &[(f32, f32, GameTime, GameTime)], time: GameTime) -> f32 {
+        let Some(&(from, to, start, end)) = ramps
+            .iter()
+            .rev()
+            .find(|(_, _, start, _)| *start <= time)
+            .or(ramps.first())
+        else {
+            return 0.0;
+        };
+        if time <= start {
+            from
+        } else if time >= end {
+            to
+        } else {
+            let fraction = ((time - start) / (end - start)) as f32;
+            from * (to / from).powf(fraction)
+        }
+    }
+}
+
+/// Note of a mono voice, in seconds from the start of playback
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MonoNote {
+    pub frequency: f32,
+    pub start: GameTime,
+    pub duration: GameTime,
+}
+
+impl GlideTime {
+    pub fn glide(self) -> Glide {
+        match self {
+            GlideTime::Short => Glide::new(0.05),
+            GlideTime::Medium => Glide::new(0.15),
+            GlideTime::Long => Glide::new(0.4),
+        }
+    }
+}
@@ -8,46 +8,83 @@ pub mod texture;
 pub mod widgets;
 
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::engine::errors::GameError;
 use crate::engine::errors::GameResult;
+use crate::engine::wav::decode_wav;
+use crate::engine::wav::DecodedAudio;
+use crate::nodes::sampler::SampleAsset;
+use macroquad::file::load_file;
 use macroquad::math::Vec2;
 use macroquad::texture::load_texture;
 use macroquad::texture::Texture2D;
 use texture::TextureAsset;
-use web_sys::AudioContext;
 
 pub type Assets = HashMap<TextureAsset, Texture2D>;
+pub type Samples = HashMap<SampleAsset, Rc<DecodedAudio>>;
 
 pub struct RenderCtx {
     assets: Assets,
+    samples: Samples,
     pub screen_size: Vec2,
 }
 
 impl RenderCtx {
     pub async fn new(screen_size: Vec2) -> GameResult<Self> {
         let assets = Self::load_assets().await?;
+        let samples = Self::load_samples().await?;
         Ok(RenderCtx {
             assets,
+            samples,
             screen_size,
         })
     }
 
+    pub fn samples(&self) -> &Samples {
+        &self.samples
+    }
+
     async fn load_assets() -> GameResult<Assets> {
         let files = [
-            (TextureAsset::DISTORTION, "resources/distortion.png"),
-            (TextureAsset::FASTER, "resources/faster.png"),
-            (TextureAsset::HIGHPASS, "resources/highpass.png"),
-            (TextureAsset::LOGO, "resources/logo.png"),
-            (TextureAsset::LOWPASS, "resources/lowpass.png"),
-            (TextureAsset::NOTCH, "resources/notch.png"),
-            (TextureAsset::NOTE, "resources/note.png"),
-            (TextureAsset::PIANO, "resources/piano.png"),
-            (TextureAsset::SINE, "resources/sine.png"),
-            (TextureAsset::SLOWER, "resources/slower.png"),
-            (TextureAsset::SQUARE, "resources/square.png"),
-            (TextureAsset::REVERB, "resources/reverb.png"),
-            (TextureAsset::CHORD, "resources/chord.png"),
+            (TextureAsset::Distortion, "resources/distortion.png"),
+            (TextureAsset::Faster, "resources/faster.png"),
+            (TextureAsset::HighPass, "resources/highpass.png"),
+            (TextureAsset::Logo, "resources/logo.png"),
+            (TextureAsset::LowPass, "resources/lowpass.png"),
+            (TextureAsset::Notch, "resources/notch.png"),
+            (TextureAsset::Note, "resources/note.png"),
+            (TextureAsset::Piano, "resources/piano.png"),
+            (TextureAsset::Sine, "resources/sine.png"),
+            (TextureAsset::Slower, "resources/slower.png"),
+            (TextureAsset::Square, "resources/square.png"),
+            (TextureAsset::Sawtooth, "resources/sawtooth.png"),
+            (TextureAsset::Triangle, "resources/triangle.png"),
+            (TextureAsset::Wavetable, "resources/wavetable.png"),
+            (TextureAsset::Reverb, "resources/reverb.png"),
+            (TextureAsset::Chord, "resources/chord.png"),
+            (TextureAsset::Envelope, "resources/envelope.png"),
+            (TextureAsset::Lfo, "resources/lfo.png"),
+            (TextureAsset::Fm, "resources/fm.png"),
+            (TextureAsset::Noise, "resources/noise.png"),
+            (TextureAsset::Drum, "resources/drum.png"),
+            (TextureAsset::Unison, "resources/unison.png"),
+            (TextureAsset::Sampler, "resources/sampler.png"),
+            (TextureAsset::Glide, "resources/glide.png"),
+            (TextureAsset::Delay, "resources/delay.png"),
+            (TextureAsset::PingPong, "resources/ping_pong.png"),
+            (TextureAsset::AllPass, "resources/allpass.png"),
+            (TextureAsset::Chorus, "resources/chorus.png"),
+            (TextureAsset::Flanger, "resources/flanger.png"),
+            (TextureAsset::Phaser, "resources/phaser.png"),
+            (TextureAsset::Compressor, "resources/compressor.png"),
+            (TextureAsset::BandPass, "resources/bandpass.png"),
+            (TextureAsset::LowShelf, "resources/lowshelf.png"),
+            (TextureAsset::HighShelf, "resources/highshelf.png"),
+            (TextureAsset::Peaking, "resources/peaking.png"),
+            (TextureAsset::Pan, "resources/pan.png"),
+            (TextureAsset::AutoPan, "resources/autopan.png"),
+            (TextureAsset::Bitcrusher, "resources/bitcrusher.png"),
         ];
 
         let mut assets = HashMap::new();
@@ -60,12 +97,20 @@ impl RenderCtx {
 
         Ok(assets)
     }
+
+    async fn load_samples() -> GameResult<Samples> {
+        let mut samples = HashMap::new();
+        for asset in SampleAsset::ALL {
+            let bytes = load_file(asset.path())
+                .await
+                .map_err(|_e| GameError::msg("failed to load sample"))?;
+            samples.insert(asset, Rc::new(decode_wav(&bytes)?));
+        }
+
+        Ok(samples)
+    }
 }
 
 pub trait Render {
     fn render(&self, render_ctx: &RenderCtx) -> GameResult<()>;
 }
-
-pub trait RenderAudio {
-    fn render_audio(&self, audio_context: &AudioContext) -> GameResult<()>;
-}
@@ -11,39 +11,89 @@ use super::RenderCtx;
 
 #[derive(Clone, Copy)]
 pub enum Shape {
-    BLANK,
-    DISTORTION,
-    FASTER,
-    HIGHPASS,
-    LOGO,
-    LOWPASS,
-    NOTCH,
-    PIANO,
-    SINE,
-    SLOWER,
-    SQUARE,
-    NOTE,
-    REVERB,
-    CHORD,
+    Blank,
+    Distortion,
+    Faster,
+    HighPass,
+    Logo,
+    LowPass,
+    Notch,
+    Piano,
+    Sine,
+    Slower,
+    Square,
+    Sawtooth,
+    Triangle,
+    Wavetable,
+    Note,
+    Reverb,
+    Chord,
+    Envelope,
+    Lfo,
+    Fm,
+    Noise,
+    Drum,
+    Unison,
+    Sampler,
+    Glide,
+    Delay,
+    PingPong,
+    AllPass,
+    Chorus,
+    Flanger,
+    Phaser,
+    Compressor,
+    BandPass,
+    LowShelf,
+    HighShelf,
+    Peaking,
+    Pan,
+    AutoPan,
+    Bitcrusher,
 }
 
 impl Shape {
     fn to_texture_asset(&self) -> Option<TextureAsset> {
         match self {
-            Shape::DISTORTION => Some(TextureAsset::DISTORTION),
-            Shape::FASTER => Some(TextureAsset::FASTER),
-            Shape::HIGHPASS => Some(TextureAsset::HIGHPASS),
-            Shape::LOGO => Some(TextureAsset::LOGO),
-            Shape::LOWPASS => Some(TextureAsset::LOWPASS),
-            Shape::NOTCH => Some(TextureAsset::NOTCH),
-            Shape::NOTE => Some(TextureAsset::NOTE),
-            Shape::PIANO => Some(TextureAsset::PIANO),
-            Shape::SINE => Some(TextureAsset::SINE),
-            Shape::SLOWER => Some(TextureAsset::SLOWER),
-            Shape::SQUARE => Some(TextureAsset::SQUARE),
-            Shape::REVERB => Some(TextureAsset::REVERB),
-            Shape::CHORD => Some(TextureAsset::CHORD),
-            Shape::BLANK => None,
+            Shape::Distortion => Some(TextureAsset::Distortion),
+            Shape::Faster => Some(TextureAsset::Faster),
+            Shape::HighPass => Some(TextureAsset::HighPass),
+            Shape::Logo => Some(TextureAsset::Logo),
+            Shape::LowPass => Some(TextureAsset::LowPass),
+            Shape::Notch => Some(TextureAsset::Notch),
+            Shape::Note => Some(TextureAsset::Note),
+            Shape::Piano => Some(TextureAsset::Piano),
+            Shape::Sine => Some(TextureAsset::Sine),
+            Shape::Slower => Some(TextureAsset::Slower),
+            Shape::Square => Some(TextureAsset::Square),
+            Shape::Sawtooth => Some(TextureAsset::Sawtooth),
+            Shape::Triangle => Some(TextureAsset::Triangle),
+            Shape::Wavetable => Some(TextureAsset::Wavetable),
+            Shape::Reverb => Some(TextureAsset::Reverb),
+            Shape::Chord => Some(TextureAsset::Chord),
+            Shape::Envelope => Some(TextureAsset::Envelope),
+            Shape::Lfo => Some(TextureAsset::Lfo),
+            Shape::Fm => Some(TextureAsset::Fm),
+            Shape::Noise => Some(TextureAsset::Noise),
+            Shape::Drum => Some(TextureAsset::Drum),
+            Shape::Unison => Some(TextureAsset::Unison),
+            Shape::Sampler => Some(TextureAsset::Sampler),
+            Shape::Glide => Some(TextureAsset::Glide),
+            Shape::Delay => Some(TextureAsset::Delay),
+            Shape::PingPong => Some(TextureAsset::PingPong),
+            Shape::AllPass => Some(TextureAsset::AllPass),
+            Shape::Chorus => Some(TextureAsset::Chorus),
+            Shape::Flanger => Some(TextureAsset::Flanger),
+            Shape::Phaser => Some(TextureAsset::Phaser),
+            Shape::Compressor => Some(TextureAsset::Compressor),
+            Shape::BandPass => Some(TextureAsset::BandPass),
+            Shape::LowShelf => Some(TextureAsset::LowShelf),
+            Shape::HighShelf => Some(TextureAsset::HighShelf),
+            Shape::Peaking => Some(TextureAsset::Peaking),
+            Shape::Pan => Some(TextureAsset::Pan),
+            Shape::AutoPan => Some(TextureAsset::AutoPan),
+            Shape::Bitcrusher => Some(TextureAsset::Bitcrusher),
+            Shape::Blank => None,
         }
     }
 
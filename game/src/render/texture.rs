@@ -8,19 +8,44 @@ use super::RenderCtx;
 
 #[derive(Hash, PartialEq, Eq)]
 pub enum TextureAsset {
-    DISTORTION,
-    FASTER,
-    HIGHPASS,
-    LOGO,
-    LOWPASS,
-    NOTCH,
-    NOTE,
-    PIANO,
-    SINE,
-    SLOWER,
-    SQUARE,
-    REVERB,
-    CHORD,
+    Distortion,
+    Faster,
+    HighPass,
+    Logo,
+    LowPass,
+    Notch,
+    Note,
+    Piano,
+    Sine,
+    Slower,
+    Square,
+    Sawtooth,
+    Triangle,
+    Wavetable,
+    Reverb,
+    Chord,
+    Envelope,
+    Lfo,
+    Fm,
+    Noise,
+    Drum,
+    Unison,
+    Sampler,
+    Glide,
+    Delay,
+    PingPong,
+    AllPass,
+    Chorus,
+    Flanger,
+    Phaser,
+    Compressor,
+    BandPass,
+    LowShelf,
+    HighShelf,
+    Peaking,
+    Pan,
+    AutoPan,
+    Bitcrusher,
 }
 
 pub struct Texture {
@@ -0,0 +1,114 @@
+use std::cell::RefCell;
+
+use macroquad::color::Color;
+use macroquad::color::GRAY;
+use macroquad::color::WHITE;
+use macroquad::math::vec2;
+use macroquad::math::Vec2;
+use macroquad::shapes::draw_line;
+use macroquad::shapes::draw_rectangle;
+
+use crate::engine::analyser::Analysis;
+use crate::engine::analyser::MIN_DECIBELS;
+use crate::engine::errors::GameResult;
+use crate::render::rectangle_boundary::RectangleBoundary;
+use crate::render::Render;
+use crate::render::RenderCtx;
+
+use super::rectangle_widget::RectangleWidget;
+
+/// Bars of the spectrum, spread evenly over octaves
+const SPECTRUM_BANDS: usize = 64;
+/// Lowest frequency of the spectrum, around the bottom of hearing
+const MIN_FREQUENCY: f32 = 20.0;
+const BACKGROUND: Color = Color::new(0.0, 0.0, 0.0, 0.8);
+const SPECTRUM_COLOR: Color = Color::new(0.6, 0.4, 1.0, 1.0);
+const WAVEFORM_THICKNESS: f32 = 1.5;
+
+/// Oscilloscope of the mix on top and its spectrum on a log frequency axis below
+pub struct AnalyserWidget {
+    center: Vec2,
+    size: Vec2,
+    analysis: RefCell<Option<Analysis>>,
+}
+
+impl AnalyserWidget {
+    pub fn new(center: Vec2, size: Vec2) -> Self {
+        Self {
+            center,
+            size,
+            analysis: RefCell::new(None),
+        }
+    }
+
+    /// Analysis drawn from the next frame on
+    pub fn set_analysis(&self, analysis: Analysis) {
+        *self.analysis.borrow_mut() = Some(analysis);
+    }
+
+    fn render_waveform(&self, waveform: &[f32], top_left: Vec2, size: Vec2) {
+        let middle = top_left.y + size.y / 2.0;
+        draw_line(top_left.x, middle, top_left.x + size.x, middle, 1.0, GRAY);
+
+        // NOTE: the waveform has more samples than the widget has pixels, every pixel
+        // shows one of them
+        let points = (size.x as usize).clamp(2, waveform.len().max(2));
+        let point = |i: usize| {
+            let sample = waveform
+                .get(i * waveform.len() / points)
+                .copied()
+                .unwrap_or(0.0)
+                .clamp(-1.0, 1.0);
+            vec2(
+                top_left.x + size.x * i as f32 / (points - 1) as f32,
+                middle - sample * size.y / 2.0,
+            )
+        };
+        for i in 1..points {
+            let (start, end) = (point(i - 1), point(i));
+            draw_line(start.x, start.y, end.x, end.y, WAVEFORM_THICKNESS, WHITE);
+        }
+    }
+
+    fn render_spectrum(&self, analysis: &Analysis, top_left: Vec2, size: Vec2) {
+        let bands = analysis.spectrum.log_bands(SPECTRUM_BANDS, MIN_FREQUENCY);
+        let band_width = size.x / bands.len() as f32;
+        for (i, decibels) in bands.iter().enumerate() {
+            let height = size.y * (1.0 - decibels / MIN_DECIBELS).clamp(0.0, 1.0);
+            draw_rectangle(
+                top_left.x + band_width * i as f32,
+                top_left.y + size.y - height,
+                band_width * 0.8,
+                height,
+                SPECTRUM_COLOR,
+            );
+        }
+    }
+}
+
+impl RectangleBoundary for AnalyserWidget {
+    fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    fn size(&self) -> Vec2 {
+        self.size
+    }
+}
+
+impl Render for AnalyserWidget {
+    fn render(&self, render_ctx: &RenderCtx) -> GameResult<()> {
+        RectangleWidget::with_boundary(self.center, self.size, Some(BACKGROUND), 2.0, GRAY)
+            .render(render_ctx)?;
+
+        let analysis = self.analysis.borrow();
+        let Some(analysis) = analysis.as_ref() else {
+            return Ok(());
+        };
+        let top_left = self.top_left() * render_ctx.screen_size;
+        let half = self.size * render_ctx.screen_size * vec2(1.0, 0.5);
+        self.render_waveform(&analysis.waveform, top_left, half);
+        self.render_spectrum(analysis, top_left + vec2(0.0, half.y), half);
+        Ok(())
+    }
+}
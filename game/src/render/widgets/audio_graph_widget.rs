@@ -1,18 +1,31 @@
 use std::cell::RefCell;
 
+use macroquad::color::Color;
+use macroquad::color::WHITE;
 use macroquad::math::vec2;
 use macroquad::math::Vec2;
+use macroquad::shapes::draw_line;
 
+use crate::engine::audio_engine::Playhead;
 use crate::engine::errors::GameResult;
 use crate::engine::game_config::CardColorConfig;
+use crate::nodes::audio_graph::CardPlayback;
+use crate::nodes::parameters::CardParameters;
+use crate::nodes::parameters::EffectMix;
 use crate::nodes::AudioNodeType;
 use crate::render::draggable_card_buffer::DraggableCardBuffer;
+use crate::render::hover::Hover;
 use crate::render::rectangle_boundary::RectangleBoundary;
 use crate::render::Render;
 use crate::render::RenderCtx;
 
-use super::card_widget::{Card, CardType};
+use super::card_widget::{Card, CardType, PieceCard};
 use super::grid_widget::GridWidget;
+use super::rectangle_widget::RectangleWidget;
+
+/// Opacity of the highlight on a card whose note just started, it fades as the note plays
+const PULSE_ALPHA: f32 = 0.4;
+const PLAYHEAD_THICKNESS: f32 = 3.0;
 
 pub struct AudioGraphWidget {
     center: Vec2,
@@ -21,6 +34,8 @@ pub struct AudioGraphWidget {
     card_size: Vec2,
     grid: GridWidget,
     card_colors: CardColorConfig,
+    selected: Option<usize>,
+    playhead: Option<Playhead>,
 }
 
 impl AudioGraphWidget {
@@ -38,6 +53,8 @@ impl AudioGraphWidget {
             card_size,
             grid,
             card_colors,
+            selected: None,
+            playhead: None,
         };
         result.organize_cards();
         result
@@ -53,6 +70,147 @@ impl AudioGraphWidget {
             card.borrow_mut().size = new_size;
         }
     }
+
+    pub fn piece_cards(&self) -> Vec<PieceCard> {
+        self.cards
+            .iter()
+            .map(|card| card.borrow().piece_card())
+            .collect()
+    }
+
+    pub fn set_piece_cards(&mut self, piece_cards: Vec<PieceCard>) {
+        use macroquad::color::BLACK;
+        self.cards = piece_cards
+            .iter()
+            .map(|c| {
+                RefCell::new(
+                    Card::new(
+                        vec2(0.0, 0.0),
+                        self.card_size,
+                        c.card_type.get_color(&self.card_colors),
+                        BLACK,
+                        c.card_type,
+                    )
+                    .with_parameters(c.parameters)
+                    .with_mix(c.mix),
+                )
+            })
+            .collect();
+        self.selected = None;
+        self.organize_cards();
+    }
+
+    /// Selects the card under the mouse, or clears the selection if there is none
+    pub fn select_card_at(&mut self, mouse_pos: Vec2) {
+        self.selected = self
+            .cards
+            .iter()
+            .position(|card| card.borrow().is_hovered_over(mouse_pos));
+    }
+
+    pub fn deselect(&mut self) {
+        self.selected = None;
+    }
+
+    pub fn selected_card(&self) -> Option<(usize, PieceCard)> {
+        self.selected.and_then(|i| {
+            self.cards
+                .get(i)
+                .map(|card| (i, card.borrow().piece_card()))
+        })
+    }
+
+    /// Playhead of the playing graph, `None` when nothing plays
+    pub fn set_playhead(&mut self, playhead: Option<Playhead>) {
+        self.playhead = playhead;
+    }
+
+    pub fn set_card_parameters(&mut self, i: usize, parameters: CardParameters, mix: EffectMix) {
+        if let Some(card) = self.cards.get(i) {
+            let mut card = card.borrow_mut();
+            card.set_parameters(parameters);
+            card.set_mix(mix);
+        }
+    }
+
+    /// Bypasses the effect card under the mouse or brings it back, returns whether there
+    /// was one
+    pub fn toggle_bypass_at(&mut self, mouse_pos: Vec2) -> bool {
+        let Some(card) = self
+            .cards
+            .iter()
+            .find(|card| card.borrow().is_hovered_over(mouse_pos))
+        else {
+            return false;
+        };
+        let mut card = card.borrow_mut();
+        if card.as_type() != AudioNodeType::AudioEffect {
+            return false;
+        }
+        let mix = card.piece_card().mix;
+        card.set_mix(EffectMix {
+            bypassed: !mix.bypassed,
+            ..mix
+        });
+        true
+    }
+
+    /// Mutes the generator or effect card under the mouse or brings it back, returns
+    /// whether there was one
+    pub fn toggle_mute_at(&mut self, mouse_pos: Vec2) -> bool {
+        self.toggle_playback_at(
+            mouse_pos,
+            CardPlayback::Muted,
+            &[
+                AudioNodeType::NoteGenerator,
+                AudioNodeType::NoteEffect,
+                AudioNodeType::AudioEffect,
+            ],
+        )
+    }
+
+    /// Solos the generator card under the mouse or brings it back, returns whether there
+    /// was one
+    pub fn toggle_solo_at(&mut self, mouse_pos: Vec2) -> bool {
+        self.toggle_playback_at(
+            mouse_pos,
+            CardPlayback::Soloed,
+            &[AudioNodeType::NoteGenerator],
+        )
+    }
+
+    fn toggle_playback_at(
+        &mut self,
+        mouse_pos: Vec2,
+        playback: CardPlayback,
+        node_types: &[AudioNodeType],
+    ) -> bool {
+        let Some(card) = self
+            .cards
+            .iter()
+            .find(|card| card.borrow().is_hovered_over(mouse_pos))
+        else {
+            return false;
+        };
+        let mut card = card.borrow_mut();
+        if !node_types.contains(&card.as_type()) {
+            return false;
+        }
+        if card.playback() == playback {
+            card.set_playback(CardPlayback::Normal);
+        } else {
+            card.set_playback(playback);
+        }
+        true
+    }
+
+    /// Playback of each card, in the order of `piece_cards`
+    pub fn card_playback(&self) -> Vec<CardPlayback> {
+        self.cards
+            .iter()
+            .map(|card| card.borrow().playback())
+            .collect()
+    }
 }
 
 impl DraggableCardBuffer for AudioGraphWidget {
@@ -65,7 +223,10 @@ impl DraggableCardBuffer for AudioGraphWidget {
     }
 
     fn remove_card(&mut self, i: usize) -> RefCell<Card> {
-        self.cards.remove(i)
+        let card = self.cards.remove(i);
+        // NOTE: a card taken out of the graph is heard again once it's put back
+        card.borrow_mut().set_playback(CardPlayback::Normal);
+        card
     }
 
     fn insert_card(&mut self, i: usize, card: RefCell<Card>) {
@@ -123,20 +284,7 @@ impl DraggableCardBuffer for AudioGraphWidget {
     }
 
     fn set_cards(&mut self, card_types: Vec<CardType>) {
-        use macroquad::color::BLACK;
-        self.cards = card_types
-            .iter()
-            .map(|t| {
-                RefCell::new(Card::new(
-                    vec2(0.0, 0.0),
-                    self.card_size,
-                    t.get_color(&self.card_colors),
-                    BLACK,
-                    *t,
-                ))
-            })
-            .collect();
-        self.organize_cards();
+        self.set_piece_cards(card_types.into_iter().map(PieceCard::from).collect());
     }
 }
 
@@ -150,12 +298,55 @@ impl RectangleBoundary for AudioGraphWidget {
     }
 }
 
+impl AudioGraphWidget {
+    /// Pulses the cards whose notes sound and draws the loop progress under the graph
+    fn render_playhead(&self, render_ctx: &RenderCtx, playhead: &Playhead) -> GameResult<()> {
+        // NOTE: of the notes a card plays together only the latest one shows
+        let mut active_cards = playhead.active_cards.clone();
+        active_cards.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        active_cards.dedup_by_key(|(card, _)| *card);
+
+        for (card, progress) in active_cards {
+            let Some(card) = self.cards.get(card) else {
+                continue;
+            };
+            let card = card.borrow();
+            let color = Color {
+                a: PULSE_ALPHA * (1.0 - progress),
+                ..WHITE
+            };
+            RectangleWidget::with_boundary(card.center, card.size, Some(color), 3.0, color)
+                .render(render_ctx)?;
+        }
+
+        let start = self.bottom_left() * render_ctx.screen_size;
+        let width = self.size.x * render_ctx.screen_size.x * playhead.position;
+        draw_line(
+            start.x,
+            start.y,
+            start.x + width,
+            start.y,
+            PLAYHEAD_THICKNESS,
+            WHITE,
+        );
+        Ok(())
+    }
+}
+
 impl Render for AudioGraphWidget {
     fn render(&self, render_ctx: &RenderCtx) -> GameResult<()> {
         self.grid.render(render_ctx)?;
         for c in &self.cards {
             c.borrow().render(render_ctx)?;
         }
+        if let Some(playhead) = &self.playhead {
+            self.render_playhead(render_ctx, playhead)?;
+        }
+        if let Some(card) = self.selected.and_then(|i| self.cards.get(i)) {
+            let card = card.borrow();
+            RectangleWidget::with_boundary(card.center, card.size, None, 5.0, WHITE)
+                .render(render_ctx)?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,178 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use macroquad::math::Vec2;
+use macroquad::prelude::*;
+use macroquad::ui::{hash, root_ui, Skin};
+
+use crate::engine::errors::GameResult;
+use crate::nodes::parameters::CardParameters;
+use crate::nodes::parameters::EffectMix;
+use crate::render::hover::Hover;
+use crate::render::rectangle_boundary::RectangleBoundary;
+use crate::render::Render;
+use crate::render::RenderCtx;
+
+use super::card_widget::PieceCard;
+
+/// Sliders for the parameters of the card selected in the audio graph
+pub struct CardParametersWidget {
+    position: Vec2,
+    size: Vec2,
+    card: RefCell<Option<(usize, CardParameters, EffectMix)>>,
+    changed: Cell<bool>,
+}
+
+impl CardParametersWidget {
+    pub fn new(position: Vec2, size: Vec2) -> Self {
+        Self {
+            position,
+            size,
+            card: RefCell::new(None),
+            changed: Cell::new(false),
+        }
+    }
+
+    /// Shows the parameters of the selected card, cards without any hide the widget
+    pub fn show(&self, selected: Option<(usize, PieceCard)>) {
+        *self.card.borrow_mut() = selected.and_then(|(i, card)| {
+            card.parameters
+                .or(card.card_type.default_parameters())
+                .map(|parameters| (i, parameters, card.mix))
+        });
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.card.borrow().is_some()
+    }
+
+    /// Index of the graph card with its new parameters and mix, if a slider moved since the
+    /// last call
+    pub fn handle_change(&self) -> Option<(usize, CardParameters, EffectMix)> {
+        if self.changed.replace(false) {
+            *self.card.borrow()
+        } else {
+            None
+        }
+    }
+}
+
+impl RectangleBoundary for CardParametersWidget {
+    fn center(&self) -> Vec2 {
+        self.position
+    }
+
+    fn size(&self) -> Vec2 {
+        self.size
+    }
+}
+
+impl Hover for CardParametersWidget {
+    fn is_hovered_over(&self, relative_mouse_position: Vec2) -> bool {
+        self.is_visible() && self.is_inside(relative_mouse_position)
+    }
+}
+
+impl Render for CardParametersWidget {
+    fn render(&self, render_ctx: &RenderCtx) -> GameResult<()> {
+        let mut card = self.card.borrow_mut();
+        let Some((index, parameters, mix)) = card.as_mut() else {
+            return Ok(());
+        };
+
+        let center = self.position * render_ctx.screen_size;
+        let size = self.size * render_ctx.screen_size;
+        let top_left = center - 0.5 * size;
+
+        let dark_skin = {
+            let label_style = root_ui()
+                .style_builder()
+                .text_color(WHITE)
+                .font_size(24)
+                .build();
+
+            let black_image = Image::gen_image_color(1, 1, BLACK);
+            let window_style = root_ui()
+                .style_builder()
+                .background(black_image)
+                .background_margin(RectOffset::new(0., 0., 0., 0.))
+                .margin(RectOffset::new(0., 0., 0., 0.))
+                .build();
+
+            Skin {
+                window_style,
+                label_style,
+                ..root_ui().default_skin()
+            }
+        };
+
+        root_ui().push_skin(&dark_skin);
+        root_ui().window(
+            hash!(
+                "card_parameters",
+                *index,
+                render_ctx.screen_size.x.to_bits(),
+                render_ctx.screen_size.y.to_bits()
+            ),
+            top_left,
+            size,
+            |ui| {
+                let controls = parameters
+                    .controls()
+                    .into_iter()
+                    .chain([(EffectMix::RANGE, &mut mix.wet)]);
+                for (i, (range, value)) in controls.enumerate() {
+                    if range.logarithmic {
+                        // NOTE: the slider moves the exponent, the label shows the actual value
+                        let position = value.log10();
+                        let mut new_position = position;
+                        ui.slider(
+                            hash!("card_parameter", i),
+                            &format!("{} {}", range.name, format_value(*value)),
+                            range.min.log10()..range.max.log10(),
+                            &mut new_position,
+                        );
+                        if new_position != position {
+                            *value = 10f32.powf(new_position);
+                            self.changed.set(true);
+                        }
+                    } else {
+                        let previous = *value;
+                        ui.slider(
+                            hash!("card_parameter", i),
+                            range.name,
+                            range.min..range.max,
+                            value,
+                        );
+                        if *value != previous {
+                            self.changed.set(true);
+                        }
+                    }
+                }
+            },
+        );
+        root_ui().pop_skin();
+
+        let border_thickness = 2.0;
+        draw_rectangle_lines(
+            top_left.x - border_thickness,
+            top_left.y - border_thickness,
+            size.x + 2.0 * border_thickness,
+            size.y + 2.0 * border_thickness,
+            border_thickness,
+            WHITE,
+        );
+
+        Ok(())
+    }
+}
+
+fn format_value(value: f32) -> String {
+    if value >= 100.0 {
+        format!("{:.0}", value)
+    } else if value >= 1.0 {
+        format!("{:.2}", value)
+    } else {
+        format!("{:.3}", value)
+    }
+}
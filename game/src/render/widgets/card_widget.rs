@@ -6,11 +6,30 @@ use serde::{Deserialize, Serialize};
 
 use crate::engine::errors::GameResult;
 use crate::engine::game_config::CardColorConfig;
+use crate::nodes::audio_effect::AutoPanRate;
+use crate::nodes::audio_effect::BitcrusherPreset;
+use crate::nodes::audio_effect::CompressorPreset;
+use crate::nodes::audio_effect::DelayDivision;
+use crate::nodes::audio_effect::FilterTone;
 use crate::nodes::audio_effect::FilterType;
+use crate::nodes::audio_effect::PanPosition;
+use crate::nodes::audio_graph::CardPlayback;
+use crate::nodes::envelope::EnvelopePreset;
+use crate::nodes::lfo::LfoPreset;
 use crate::nodes::note_effect::ChangeLenType;
 use crate::nodes::note_effect::ScaleType;
 use crate::nodes::note_generator::NoteName;
+use crate::nodes::oscillator::FmPreset;
+use crate::nodes::oscillator::NoiseColor;
 use crate::nodes::oscillator::WaveShape;
+use crate::nodes::oscillator::WavetablePreset;
+use crate::nodes::parameters::CardParameters;
+use crate::nodes::parameters::EffectMix;
+use crate::nodes::percussion::DrumVoice;
+use crate::nodes::sampler::SampleInstrument;
+use crate::nodes::voice::GlideTime;
+use crate::nodes::voice::UnisonVoices;
+use crate::nodes::AudioNode;
 use crate::nodes::AudioNodeType;
 use crate::render::hover::Hover;
 use crate::render::rectangle_boundary::RectangleBoundary;
@@ -21,6 +40,7 @@ use crate::render::RenderCtx;
 use super::rectangle_widget::RectangleWidget;
 
 const MARGIN_PERSENTAGE: f32 = 0.2;
+const BYPASSED_ALPHA: f32 = 0.3;
 
 #[derive(Clone, Copy, Debug)]
 pub struct Card {
@@ -29,9 +49,22 @@ pub struct Card {
     pub background_color: Color,
     pub foreground_color: Color,
     card_type: CardType,
+    parameters: Option<CardParameters>,
+    mix: EffectMix,
+    playback: CardPlayback,
     is_dragged: bool,
 }
 
+/// Card of a piece together with the parameters the player tuned on it
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PieceCard {
+    pub card_type: CardType,
+    /// `None` keeps the preset of the card
+    pub parameters: Option<CardParameters>,
+    /// Only used by effect cards
+    pub mix: EffectMix,
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum CardType {
     NoteGenerator(Option<NoteName>),
@@ -42,6 +75,23 @@ pub enum CardType {
     Filter(FilterType),
     Distortion,
     Reverb,
+    Envelope(EnvelopePreset),
+    Lfo(LfoPreset),
+    Noise(NoiseColor),
+    Drum(Option<DrumVoice>),
+    Unison(UnisonVoices),
+    Sampler(SampleInstrument),
+    Glide(GlideTime),
+    Delay(DelayDivision),
+    PingPongDelay(DelayDivision),
+    Chorus,
+    Flanger,
+    Phaser,
+    Compressor(CompressorPreset),
+    FilterPreset(FilterType, FilterTone),
+    Pan(PanPosition),
+    AutoPan(AutoPanRate),
+    Bitcrusher(BitcrusherPreset),
 }
 
 impl CardType {
@@ -73,21 +123,28 @@ impl CardType {
             // Blank: 251 (1 values)
             CardType::BlankNoteEffect => 251,
 
-            // Oscillator: 300-301 (2 values)
+            // Oscillator: 300-303 (4 values), wavetables: 310-312, FM: 320-322 (3 values each)
             CardType::Oscillator(wave) => {
                 300 + match wave {
                     WaveShape::Sine => 0,
                     WaveShape::Square => 1,
+                    WaveShape::Sawtooth => 2,
+                    WaveShape::Triangle => 3,
+                    WaveShape::Wavetable(WavetablePreset::Organ) => 10,
+                    WaveShape::Wavetable(WavetablePreset::Brass) => 11,
+                    WaveShape::Wavetable(WavetablePreset::Bell) => 12,
+                    WaveShape::Fm(FmPreset::ElectricPiano) => 20,
+                    WaveShape::Fm(FmPreset::Bell) => 21,
+                    WaveShape::Fm(FmPreset::Bass) => 22,
                 }
             }
 
-            // Filter: 400-402 (3 values)
-            CardType::Filter(filter) => {
-                400 + match filter {
-                    FilterType::LowPass => 0,
-                    FilterType::HighPass => 1,
-                    FilterType::Notch => 2,
-                }
+            // Filter: 400-407 (8 values)
+            CardType::Filter(filter) => 400 + filter.to_int() as u16,
+
+            // FilterPreset: 410-441 (8 filter types * 4 tones = 32 values)
+            CardType::FilterPreset(filter, tone) => {
+                410 + filter.to_int() as u16 * 4 + tone.to_int() as u16
             }
 
             // Distortion: 500 (1 value)
@@ -95,6 +152,136 @@ impl CardType {
 
             // Reverb: 600 (1 value)
             CardType::Reverb => 600,
+
+            // Envelope: 700-703 (4 values)
+            CardType::Envelope(preset) => {
+                700 + match preset {
+                    EnvelopePreset::Pluck => 0,
+                    EnvelopePreset::Keys => 1,
+                    EnvelopePreset::Pad => 2,
+                    EnvelopePreset::Swell => 3,
+                }
+            }
+
+            // Lfo: 800-803 (4 values)
+            CardType::Lfo(preset) => {
+                800 + match preset {
+                    LfoPreset::SlowSine => 0,
+                    LfoPreset::FastSine => 1,
+                    LfoPreset::QuarterTriangle => 2,
+                    LfoPreset::EighthSquare => 3,
+                }
+            }
+
+            // Noise: 330-332 (3 values)
+            CardType::Noise(color) => {
+                330 + match color {
+                    NoiseColor::White => 0,
+                    NoiseColor::Pink => 1,
+                    NoiseColor::Brown => 2,
+                }
+            }
+
+            // Drum: 340-344 (kit and 4 voices)
+            CardType::Drum(None) => 340,
+            CardType::Drum(Some(voice)) => {
+                341 + match voice {
+                    DrumVoice::Kick => 0,
+                    DrumVoice::Snare => 1,
+                    DrumVoice::HiHat => 2,
+                    DrumVoice::Clap => 3,
+                }
+            }
+
+            // Unison: 900-902 (3 values)
+            CardType::Unison(voices) => {
+                900 + match voices {
+                    UnisonVoices::Two => 0,
+                    UnisonVoices::Four => 1,
+                    UnisonVoices::Seven => 2,
+                }
+            }
+
+            // Sampler: 350-351 (2 values)
+            CardType::Sampler(instrument) => {
+                350 + match instrument {
+                    SampleInstrument::Keys => 0,
+                    SampleInstrument::Pad => 1,
+                }
+            }
+
+            // Glide: 910-912 (3 values)
+            CardType::Glide(time) => {
+                910 + match time {
+                    GlideTime::Short => 0,
+                    GlideTime::Medium => 1,
+                    GlideTime::Long => 2,
+                }
+            }
+
+            // Delay: 1000-1002 (3 values)
+            CardType::Delay(division) => {
+                1000 + match division {
+                    DelayDivision::Quarter => 0,
+                    DelayDivision::DottedEighth => 1,
+                    DelayDivision::EighthTriplet => 2,
+                }
+            }
+
+            // Ping-pong delay: 1010-1012 (3 values)
+            CardType::PingPongDelay(division) => {
+                1010 + match division {
+                    DelayDivision::Quarter => 0,
+                    DelayDivision::DottedEighth => 1,
+                    DelayDivision::EighthTriplet => 2,
+                }
+            }
+
+            // Chorus: 1100 (1 value)
+            CardType::Chorus => 1100,
+
+            // Flanger: 1200 (1 value)
+            CardType::Flanger => 1200,
+
+            // Phaser: 1300 (1 value)
+            CardType::Phaser => 1300,
+
+            // Compressor: 1400-1403 (4 values)
+            CardType::Compressor(preset) => {
+                1400 + match preset {
+                    CompressorPreset::Gentle => 0,
+                    CompressorPreset::Punchy => 1,
+                    CompressorPreset::Glue => 2,
+                    CompressorPreset::Squash => 3,
+                }
+            }
+
+            // Pan: 1500-1502 (3 values)
+            CardType::Pan(position) => {
+                1500 + match position {
+                    PanPosition::Left => 0,
+                    PanPosition::Center => 1,
+                    PanPosition::Right => 2,
+                }
+            }
+
+            // AutoPan: 1600-1602 (3 values)
+            CardType::AutoPan(rate) => {
+                1600 + match rate {
+                    AutoPanRate::Quarter => 0,
+                    AutoPanRate::Half => 1,
+                    AutoPanRate::Bar => 2,
+                }
+            }
+
+            // Bitcrusher: 1700-1702 (3 values)
+            CardType::Bitcrusher(preset) => {
+                1700 + match preset {
+                    BitcrusherPreset::EightBit => 0,
+                    BitcrusherPreset::FourBit => 1,
+                    BitcrusherPreset::Telephone => 2,
+                }
+            }
         }
     }
 
@@ -123,14 +310,49 @@ impl CardType {
             201 => Some(CardType::ChangeLen(ChangeLenType::Half)),
             251 => Some(CardType::BlankNoteEffect),
 
-            // Oscillator: 300-301
+            // Oscillator: 300-303, 310-312, 320-322
             300 => Some(CardType::Oscillator(WaveShape::Sine)),
             301 => Some(CardType::Oscillator(WaveShape::Square)),
-
-            // Filter: 400-402
-            400 => Some(CardType::Filter(FilterType::LowPass)),
-            401 => Some(CardType::Filter(FilterType::HighPass)),
-            402 => Some(CardType::Filter(FilterType::Notch)),
+            302 => Some(CardType::Oscillator(WaveShape::Sawtooth)),
+            303 => Some(CardType::Oscillator(WaveShape::Triangle)),
+            310 => Some(CardType::Oscillator(WaveShape::Wavetable(
+                WavetablePreset::Organ,
+            ))),
+            311 => Some(CardType::Oscillator(WaveShape::Wavetable(
+                WavetablePreset::Brass,
+            ))),
+            312 => Some(CardType::Oscillator(WaveShape::Wavetable(
+                WavetablePreset::Bell,
+            ))),
+            320 => Some(CardType::Oscillator(WaveShape::Fm(FmPreset::ElectricPiano))),
+            321 => Some(CardType::Oscillator(WaveShape::Fm(FmPreset::Bell))),
+            322 => Some(CardType::Oscillator(WaveShape::Fm(FmPreset::Bass))),
+
+            // Noise: 330-332
+            330 => Some(CardType::Noise(NoiseColor::White)),
+            331 => Some(CardType::Noise(NoiseColor::Pink)),
+            332 => Some(CardType::Noise(NoiseColor::Brown)),
+
+            // Drum: 340-344
+            340 => Some(CardType::Drum(None)),
+            341 => Some(CardType::Drum(Some(DrumVoice::Kick))),
+            342 => Some(CardType::Drum(Some(DrumVoice::Snare))),
+            343 => Some(CardType::Drum(Some(DrumVoice::HiHat))),
+            344 => Some(CardType::Drum(Some(DrumVoice::Clap))),
+
+            // Sampler: 350-351
+            350 => Some(CardType::Sampler(SampleInstrument::Keys)),
+            351 => Some(CardType::Sampler(SampleInstrument::Pad)),
+
+            // Filter: 400-407
+            400..=407 => FilterType::from_int((id - 400) as u32).map(CardType::Filter),
+
+            // FilterPreset: 410-441
+            410..=441 => {
+                let filter = FilterType::from_int((id - 410) as u32 / 4)?;
+                let tone = FilterTone::from_int((id - 410) as u32 % 4)?;
+                Some(CardType::FilterPreset(filter, tone))
+            }
 
             // Distortion: 500
             500 => Some(CardType::Distortion),
@@ -138,6 +360,68 @@ impl CardType {
             // Reverb: 600
             600 => Some(CardType::Reverb),
 
+            // Envelope: 700-703
+            700 => Some(CardType::Envelope(EnvelopePreset::Pluck)),
+            701 => Some(CardType::Envelope(EnvelopePreset::Keys)),
+            702 => Some(CardType::Envelope(EnvelopePreset::Pad)),
+            703 => Some(CardType::Envelope(EnvelopePreset::Swell)),
+
+            // Lfo: 800-803
+            800 => Some(CardType::Lfo(LfoPreset::SlowSine)),
+            801 => Some(CardType::Lfo(LfoPreset::FastSine)),
+            802 => Some(CardType::Lfo(LfoPreset::QuarterTriangle)),
+            803 => Some(CardType::Lfo(LfoPreset::EighthSquare)),
+
+            // Unison: 900-902
+            900 => Some(CardType::Unison(UnisonVoices::Two)),
+            901 => Some(CardType::Unison(UnisonVoices::Four)),
+            902 => Some(CardType::Unison(UnisonVoices::Seven)),
+
+            // Glide: 910-912
+            910 => Some(CardType::Glide(GlideTime::Short)),
+            911 => Some(CardType::Glide(GlideTime::Medium)),
+            912 => Some(CardType::Glide(GlideTime::Long)),
+
+            // Delay: 1000-1002
+            1000 => Some(CardType::Delay(DelayDivision::Quarter)),
+            1001 => Some(CardType::Delay(DelayDivision::DottedEighth)),
+            1002 => Some(CardType::Delay(DelayDivision::EighthTriplet)),
+
+            // Ping-pong delay: 1010-1012
+            1010 => Some(CardType::PingPongDelay(DelayDivision::Quarter)),
+            1011 => Some(CardType::PingPongDelay(DelayDivision::DottedEighth)),
+            1012 => Some(CardType::PingPongDelay(DelayDivision::EighthTriplet)),
+
+            // Chorus: 1100
+            1100 => Some(CardType::Chorus),
+
+            // Flanger: 1200
+            1200 => Some(CardType::Flanger),
+
+            // Phaser: 1300
+            1300 => Some(CardType::Phaser),
+
+            // Compressor: 1400-1403
+            1400 => Some(CardType::Compressor(CompressorPreset::Gentle)),
+            1401 => Some(CardType::Compressor(CompressorPreset::Punchy)),
+            1402 => Some(CardType::Compressor(CompressorPreset::Glue)),
+            1403 => Some(CardType::Compressor(CompressorPreset::Squash)),
+
+            // Pan: 1500-1502
+            1500 => Some(CardType::Pan(PanPosition::Left)),
+            1501 => Some(CardType::Pan(PanPosition::Center)),
+            1502 => Some(CardType::Pan(PanPosition::Right)),
+
+            // AutoPan: 1600-1602
+            1600 => Some(CardType::AutoPan(AutoPanRate::Quarter)),
+            1601 => Some(CardType::AutoPan(AutoPanRate::Half)),
+            1602 => Some(CardType::AutoPan(AutoPanRate::Bar)),
+
+            // Bitcrusher: 1700-1702
+            1700 => Some(CardType::Bitcrusher(BitcrusherPreset::EightBit)),
+            1701 => Some(CardType::Bitcrusher(BitcrusherPreset::FourBit)),
+            1702 => Some(CardType::Bitcrusher(BitcrusherPreset::Telephone)),
+
             // Invalid ID
             _ => None,
         }
@@ -147,20 +431,47 @@ impl CardType {
 impl CardType {
     pub fn as_shape(&self) -> Shape {
         match self {
-            CardType::NoteGenerator(Some(_)) => Shape::NOTE,
-            CardType::NoteGenerator(None) => Shape::BLANK,
-            CardType::ChordInScale(_, _) => Shape::CHORD,
-            CardType::ChangeLen(ChangeLenType::Half) => Shape::FASTER,
-            CardType::ChangeLen(ChangeLenType::Double) => Shape::SLOWER,
-            CardType::ChangeLen(ChangeLenType::Tripplets) => Shape::FASTER,
-            CardType::BlankNoteEffect => Shape::BLANK,
-            CardType::Oscillator(WaveShape::Sine) => Shape::SINE,
-            CardType::Oscillator(WaveShape::Square) => Shape::SQUARE,
-            CardType::Filter(FilterType::Notch) => Shape::NOTCH,
-            CardType::Filter(FilterType::LowPass) => Shape::LOWPASS,
-            CardType::Filter(FilterType::HighPass) => Shape::HIGHPASS,
-            CardType::Distortion => Shape::DISTORTION,
-            CardType::Reverb => Shape::REVERB,
+            CardType::NoteGenerator(Some(_)) => Shape::Note,
+            CardType::NoteGenerator(None) => Shape::Blank,
+            CardType::ChordInScale(_, _) => Shape::Chord,
+            CardType::ChangeLen(ChangeLenType::Half) => Shape::Faster,
+            CardType::ChangeLen(ChangeLenType::Double) => Shape::Slower,
+            CardType::ChangeLen(ChangeLenType::Tripplets) => Shape::Faster,
+            CardType::BlankNoteEffect => Shape::Blank,
+            CardType::Oscillator(WaveShape::Sine) => Shape::Sine,
+            CardType::Oscillator(WaveShape::Square) => Shape::Square,
+            CardType::Oscillator(WaveShape::Sawtooth) => Shape::Sawtooth,
+            CardType::Oscillator(WaveShape::Triangle) => Shape::Triangle,
+            CardType::Oscillator(WaveShape::Wavetable(_)) => Shape::Wavetable,
+            CardType::Oscillator(WaveShape::Fm(_)) => Shape::Fm,
+            CardType::Filter(filter) | CardType::FilterPreset(filter, _) => match filter {
+                FilterType::Notch => Shape::Notch,
+                FilterType::LowPass => Shape::LowPass,
+                FilterType::HighPass => Shape::HighPass,
+                FilterType::AllPass => Shape::AllPass,
+                FilterType::BandPass => Shape::BandPass,
+                FilterType::LowShelf => Shape::LowShelf,
+                FilterType::HighShelf => Shape::HighShelf,
+                FilterType::Peaking => Shape::Peaking,
+            },
+            CardType::Distortion => Shape::Distortion,
+            CardType::Reverb => Shape::Reverb,
+            CardType::Envelope(_) => Shape::Envelope,
+            CardType::Lfo(_) => Shape::Lfo,
+            CardType::Noise(_) => Shape::Noise,
+            CardType::Drum(_) => Shape::Drum,
+            CardType::Unison(_) => Shape::Unison,
+            CardType::Sampler(_) => Shape::Sampler,
+            CardType::Glide(_) => Shape::Glide,
+            CardType::Delay(_) => Shape::Delay,
+            CardType::PingPongDelay(_) => Shape::PingPong,
+            CardType::Chorus => Shape::Chorus,
+            CardType::Flanger => Shape::Flanger,
+            CardType::Phaser => Shape::Phaser,
+            CardType::Compressor(_) => Shape::Compressor,
+            CardType::Pan(_) => Shape::Pan,
+            CardType::AutoPan(_) => Shape::AutoPan,
+            CardType::Bitcrusher(_) => Shape::Bitcrusher,
         }
     }
     pub fn as_type(&self) -> AudioNodeType {
@@ -173,6 +484,23 @@ impl CardType {
             CardType::Filter(_) => AudioNodeType::AudioEffect,
             CardType::Distortion => AudioNodeType::AudioEffect,
             CardType::Reverb => AudioNodeType::AudioEffect,
+            CardType::Envelope(_) => AudioNodeType::Envelope,
+            CardType::Lfo(_) => AudioNodeType::Lfo,
+            CardType::Noise(_) => AudioNodeType::Oscillator,
+            CardType::Drum(_) => AudioNodeType::Oscillator,
+            CardType::Unison(_) => AudioNodeType::Voice,
+            CardType::Sampler(_) => AudioNodeType::Oscillator,
+            CardType::Glide(_) => AudioNodeType::Voice,
+            CardType::Delay(_) => AudioNodeType::AudioEffect,
+            CardType::PingPongDelay(_) => AudioNodeType::AudioEffect,
+            CardType::Chorus => AudioNodeType::AudioEffect,
+            CardType::Flanger => AudioNodeType::AudioEffect,
+            CardType::Phaser => AudioNodeType::AudioEffect,
+            CardType::Compressor(_) => AudioNodeType::AudioEffect,
+            CardType::FilterPreset(_, _) => AudioNodeType::AudioEffect,
+            CardType::Pan(_) => AudioNodeType::AudioEffect,
+            CardType::AutoPan(_) => AudioNodeType::AudioEffect,
+            CardType::Bitcrusher(_) => AudioNodeType::AudioEffect,
         }
     }
 
@@ -191,6 +519,86 @@ impl CardType {
                 ChangeLenType::Half => Some("/2".to_string()),
                 ChangeLenType::Tripplets => Some("/3".to_string()),
             },
+            CardType::Oscillator(WaveShape::Wavetable(preset)) => match preset {
+                WavetablePreset::Organ => Some("Organ".to_string()),
+                WavetablePreset::Brass => Some("Brass".to_string()),
+                WavetablePreset::Bell => Some("Bell".to_string()),
+            },
+            CardType::Oscillator(WaveShape::Fm(preset)) => match preset {
+                FmPreset::ElectricPiano => Some("E.Piano".to_string()),
+                FmPreset::Bell => Some("FM Bell".to_string()),
+                FmPreset::Bass => Some("FM Bass".to_string()),
+            },
+            CardType::Envelope(preset) => match preset {
+                EnvelopePreset::Pluck => Some("Pluck".to_string()),
+                EnvelopePreset::Keys => Some("Keys".to_string()),
+                EnvelopePreset::Pad => Some("Pad".to_string()),
+                EnvelopePreset::Swell => Some("Swell".to_string()),
+            },
+            CardType::Lfo(preset) => match preset {
+                LfoPreset::SlowSine => Some("0.5Hz".to_string()),
+                LfoPreset::FastSine => Some("6Hz".to_string()),
+                LfoPreset::QuarterTriangle => Some("1/4".to_string()),
+                LfoPreset::EighthSquare => Some("1/8".to_string()),
+            },
+            CardType::Noise(color) => match color {
+                NoiseColor::White => Some("White".to_string()),
+                NoiseColor::Pink => Some("Pink".to_string()),
+                NoiseColor::Brown => Some("Brown".to_string()),
+            },
+            CardType::Drum(voice) => match voice {
+                None => Some("Kit".to_string()),
+                Some(DrumVoice::Kick) => Some("Kick".to_string()),
+                Some(DrumVoice::Snare) => Some("Snare".to_string()),
+                Some(DrumVoice::HiHat) => Some("Hi-hat".to_string()),
+                Some(DrumVoice::Clap) => Some("Clap".to_string()),
+            },
+            CardType::Unison(voices) => match voices {
+                UnisonVoices::Two => Some("x2".to_string()),
+                UnisonVoices::Four => Some("x4".to_string()),
+                UnisonVoices::Seven => Some("x7".to_string()),
+            },
+            CardType::Sampler(instrument) => match instrument {
+                SampleInstrument::Keys => Some("Keys".to_string()),
+                SampleInstrument::Pad => Some("Pad".to_string()),
+            },
+            CardType::Glide(time) => match time {
+                GlideTime::Short => Some("50ms".to_string()),
+                GlideTime::Medium => Some("150ms".to_string()),
+                GlideTime::Long => Some("400ms".to_string()),
+            },
+            CardType::Delay(division) | CardType::PingPongDelay(division) => match division {
+                DelayDivision::Quarter => Some("1/4".to_string()),
+                DelayDivision::DottedEighth => Some("1/8.".to_string()),
+                DelayDivision::EighthTriplet => Some("1/8T".to_string()),
+            },
+            CardType::Compressor(preset) => match preset {
+                CompressorPreset::Gentle => Some("Gentle".to_string()),
+                CompressorPreset::Punchy => Some("Punchy".to_string()),
+                CompressorPreset::Glue => Some("Glue".to_string()),
+                CompressorPreset::Squash => Some("Squash".to_string()),
+            },
+            CardType::FilterPreset(_, tone) => match tone {
+                FilterTone::Dark => Some("Dark".to_string()),
+                FilterTone::Mid => Some("Mid".to_string()),
+                FilterTone::Bright => Some("Bright".to_string()),
+                FilterTone::Resonant => Some("Res.".to_string()),
+            },
+            CardType::Pan(position) => match position {
+                PanPosition::Left => Some("L".to_string()),
+                PanPosition::Center => Some("C".to_string()),
+                PanPosition::Right => Some("R".to_string()),
+            },
+            CardType::AutoPan(rate) => match rate {
+                AutoPanRate::Quarter => Some("1/4".to_string()),
+                AutoPanRate::Half => Some("1/2".to_string()),
+                AutoPanRate::Bar => Some("1 bar".to_string()),
+            },
+            CardType::Bitcrusher(preset) => match preset {
+                BitcrusherPreset::EightBit => Some("8-bit".to_string()),
+                BitcrusherPreset::FourBit => Some("4-bit".to_string()),
+                BitcrusherPreset::Telephone => Some("Phone".to_string()),
+            },
             _ => None,
         }
     }
@@ -202,12 +610,23 @@ impl CardType {
         }
     }
 
+    /// Preset values of the tunable parameters, `None` for cards without any
+    pub fn default_parameters(&self) -> Option<CardParameters> {
+        match AudioNode::from_card(self) {
+            AudioNode::AudioEffect(effect) => Some(CardParameters::from_effect(&effect)),
+            _ => None,
+        }
+    }
+
     pub fn get_color(&self, colors: &CardColorConfig) -> Color {
         match self.as_type() {
             AudioNodeType::NoteGenerator => colors.note_generator,
             AudioNodeType::NoteEffect => colors.note_effect,
             AudioNodeType::Oscillator => colors.oscillator,
+            AudioNodeType::Envelope => colors.envelope,
             AudioNodeType::AudioEffect => colors.audio_effect,
+            AudioNodeType::Lfo => colors.lfo,
+            AudioNodeType::Voice => colors.voice,
         }
     }
 }
@@ -226,9 +645,21 @@ impl Card {
             background_color,
             foreground_color,
             card_type,
+            parameters: None,
+            mix: EffectMix::default(),
+            playback: CardPlayback::Normal,
             is_dragged: false,
         }
     }
+
+    pub fn with_parameters(self, parameters: Option<CardParameters>) -> Card {
+        Card { parameters, ..self }
+    }
+
+    pub fn with_mix(self, mix: EffectMix) -> Card {
+        Card { mix, ..self }
+    }
+
     pub fn start_dragging(&mut self) {
         self.is_dragged = true;
     }
@@ -257,6 +688,52 @@ impl Card {
     pub fn card_type(&self) -> CardType {
         self.card_type
     }
+
+    /// Parameters tuned by the player, `None` while the card keeps its preset
+    pub fn parameters(&self) -> Option<CardParameters> {
+        self.parameters
+    }
+
+    pub fn set_parameters(&mut self, parameters: CardParameters) {
+        self.parameters = Some(parameters);
+    }
+
+    pub fn set_mix(&mut self, mix: EffectMix) {
+        self.mix = mix;
+    }
+
+    /// Muted or soloed while playing, never saved with the piece
+    pub fn playback(&self) -> CardPlayback {
+        self.playback
+    }
+
+    pub fn set_playback(&mut self, playback: CardPlayback) {
+        self.playback = playback;
+    }
+
+    pub fn piece_card(&self) -> PieceCard {
+        PieceCard {
+            card_type: self.card_type,
+            parameters: self.parameters,
+            mix: self.mix,
+        }
+    }
+}
+
+impl PieceCard {
+    pub fn as_type(&self) -> AudioNodeType {
+        self.card_type.as_type()
+    }
+}
+
+impl From<CardType> for PieceCard {
+    fn from(card_type: CardType) -> Self {
+        PieceCard {
+            card_type,
+            parameters: None,
+            mix: EffectMix::default(),
+        }
+    }
 }
 
 impl RectangleBoundary for Card {
@@ -271,10 +748,26 @@ impl RectangleBoundary for Card {
 
 impl Render for Card {
     fn render(&self, render_ctx: &RenderCtx) -> GameResult<()> {
+        // Bypassed and muted cards fade out, so it's clear which ones are heard
+        let faded = self.mix.bypassed || self.playback == CardPlayback::Muted;
+        let (background_color, foreground_color) = if faded {
+            (
+                Color {
+                    a: BYPASSED_ALPHA,
+                    ..self.background_color
+                },
+                Color {
+                    a: BYPASSED_ALPHA,
+                    ..self.foreground_color
+                },
+            )
+        } else {
+            (self.background_color, self.foreground_color)
+        };
         let rect = RectangleWidget::with_boundary(
             self.center,
             self.size,
-            Some(self.background_color),
+            Some(background_color),
             5.0,
             GRAY,
         );
@@ -289,7 +782,7 @@ impl Render for Card {
             render_ctx,
             absolute_top_left + absolute_margin / 2.0,
             absolute_size * (1.0 - MARGIN_PERSENTAGE),
-            self.foreground_color,
+            foreground_color,
         )?;
 
         if let Some(label) = self.card_type.get_label() {
@@ -300,7 +793,23 @@ impl Render for Card {
             // Position text at the bottom of the card with some padding.
             let text_y = absolute_top_left.y + absolute_size.y - (font_size * 0.25);
 
-            draw_text(&label, text_x, text_y, font_size, self.foreground_color);
+            draw_text(&label, text_x, text_y, font_size, foreground_color);
+        }
+
+        let badge = match self.playback {
+            CardPlayback::Normal => None,
+            CardPlayback::Muted => Some("M"),
+            CardPlayback::Soloed => Some("S"),
+        };
+        if let Some(badge) = badge {
+            let font_size = absolute_size.y * 0.2;
+            draw_text(
+                badge,
+                absolute_top_left.x + font_size * 0.25,
+                absolute_top_left.y + font_size,
+                font_size,
+                self.foreground_color,
+            );
         }
 
         Ok(())
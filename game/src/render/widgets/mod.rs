@@ -1,5 +1,7 @@
+pub mod analyser_widget;
 pub mod arrow_widget;
 pub mod audio_graph_widget;
+pub mod card_parameters_widget;
 pub mod card_widget;
 pub mod cards_row_widget;
 pub mod error_popup_widget;
@@ -6,6 +6,7 @@ use macroquad::math::Vec2;
 use macroquad::ui::hash;
 use macroquad::ui::root_ui;
 
+use crate::engine::audio_engine::SwapQuantization;
 use crate::engine::game_settings::GameSettings;
 use crate::render::RenderCtx;
 use crate::{engine::errors::GameResult, render::Render};
@@ -122,6 +123,16 @@ impl Render for SettingsWidget {
                 ui.label(None, "");
                 ui.label(None, "Settings:");
                 ui.slider(hash!(), "Volume", 0.0..1.0, &mut settings.volume);
+                ui.slider(hash!(), "Pan", -1.0..1.0, &mut settings.pan);
+
+                let names = SwapQuantization::ALL.map(|quantization| quantization.name());
+                let mut selected = SwapQuantization::ALL
+                    .iter()
+                    .position(|quantization| *quantization == settings.swap_quantization)
+                    .unwrap_or(0);
+                ui.combo_box(hash!(), "Swap at", &names, &mut selected);
+                settings.swap_quantization = SwapQuantization::ALL[selected];
+                ui.checkbox(hash!(), "Analyser", &mut settings.show_analyser);
 
                 if settings.is_connected {
                     ui.label(None, "Piece Name:");
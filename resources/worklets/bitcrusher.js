@@ -0,0 +1,50 @@
+// Quantizes the amplitude to `bits` and holds every sample for `sampleRate / rate` frames.
+// NOTE: keep in sync with `BitcrusherParameters` and the offline renderer of the game
+class BitcrusherProcessor extends AudioWorkletProcessor {
+  static get parameterDescriptors() {
+    return [
+      { name: 'bits', defaultValue: 8, minValue: 1, maxValue: 16, automationRate: 'k-rate' },
+      { name: 'rate', defaultValue: 22050, minValue: 1, automationRate: 'k-rate' },
+    ];
+  }
+
+  constructor() {
+    super();
+    this.phases = [];
+    this.held = [];
+  }
+
+  process(inputs, outputs, parameters) {
+    const input = inputs[0];
+    const output = outputs[0];
+    const steps = Math.pow(2, Math.max(parameters.bits[0], 1) - 1);
+    const hold = Math.max(sampleRate / Math.max(parameters.rate[0], 1), 1);
+
+    for (let channel = 0; channel < output.length; channel++) {
+      const source = input[channel];
+      const target = output[channel];
+      if (!source) {
+        target.fill(0);
+        continue;
+      }
+
+      // The first frame is always sampled
+      let phase = this.phases[channel] ?? hold;
+      let held = this.held[channel] ?? 0;
+      for (let i = 0; i < target.length; i++) {
+        if (phase >= hold) {
+          phase -= hold;
+          const x = Math.min(Math.max(source[i], -1), 1);
+          held = Math.min(Math.max(Math.round(x * steps), -steps), steps - 1) / steps;
+        }
+        phase += 1;
+        target[i] = held;
+      }
+      this.phases[channel] = phase;
+      this.held[channel] = held;
+    }
+    return true;
+  }
+}
+
+registerProcessor('bitcrusher', BitcrusherProcessor);